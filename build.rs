@@ -1,6 +1,10 @@
 
 fn main() {
-    slint_build::compile("ui/main.slint").unwrap();
+    // Icons and images referenced with @image-url are compiled into the
+    // executable, so the installed program needs no assets folder
+    let config = slint_build::CompilerConfiguration::new()
+        .embed_resources(slint_build::EmbedResourcesKind::EmbedFiles);
+    slint_build::compile_with_config("ui/main.slint", config).unwrap();
 
     #[cfg(target_os = "windows")]
     {
@@ -0,0 +1,184 @@
+// examples/golden_fixtures.rs
+// Fixtures of the golden-file tests
+// ---------------------------------
+//
+//   cargo run --example golden_fixtures
+//
+// (Re)writes the contact lists in tests/fixtures that tests/golden.rs
+// converts. A case is added here, the fixtures regenerated and the new
+// golden file made with `UPDATE_GOLDEN=1 cargo test --test golden`; review
+// it before committing.
+
+use rust_xlsxwriter::{Workbook, XlsxError};
+use std::fs;
+use std::path::Path;
+
+enum Cell {
+    Text(&'static str),
+    Number(f64),
+}
+
+use Cell::{Number, Text};
+
+const HEADER: [&str; 6] = [
+    "Voornaam",
+    "Naam",
+    "Straat",
+    "Huisnummer",
+    "Mobiel nummer",
+    "E-mailadres",
+];
+
+fn write_list(path: &Path, header: &[&str], rows: &[Vec<Cell>]) -> Result<(), XlsxError> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet().set_name("Contacten")?;
+    for (col, name) in header.iter().enumerate() {
+        sheet.write_string(0, col as u16, *name)?;
+    }
+    for (row, cells) in rows.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            let (row, col) = (row as u32 + 1, col as u16);
+            match cell {
+                Text("") => {}
+                Text(value) => {
+                    sheet.write_string(row, col, *value)?;
+                }
+                Number(value) => {
+                    sheet.write_number(row, col, *value)?;
+                }
+            }
+        }
+    }
+    workbook.save(path)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    fs::create_dir_all(&dir)?;
+
+    // A clean list: mobile, international and landline notation
+    write_list(
+        &dir.join("normal.xlsx"),
+        &HEADER,
+        &[
+            vec![
+                Text("Jan"),
+                Text("Peeters"),
+                Text("Kerkstraat"),
+                Text("12"),
+                Text("0475 12 34 56"),
+                Text("jan.peeters@telenet.be"),
+            ],
+            vec![
+                Text("Marie"),
+                Text("Janssens"),
+                Text("Stationsstraat"),
+                Text("3A"),
+                Text("+32 486 11 22 33"),
+                Text(""),
+            ],
+            vec![
+                Text("Luc"),
+                Text("Claes"),
+                Text("Dorpsstraat"),
+                Number(7.0),
+                Text("011 31 22 44"),
+                Text("luc.claes@skynet.be"),
+            ],
+        ],
+    )?;
+
+    // What registers really hold: numbers as numbers, notes in phone cells,
+    // abbreviations, placeholders, no street, separators in values,
+    // duplicates and stray spaces
+    write_list(
+        &dir.join("edge_cases.xlsx"),
+        &HEADER,
+        &[
+            vec![
+                Text("Émile"),
+                Text("D'Hondt"),
+                Text("Kon. Albertlaan"),
+                Text("12 bus 3"),
+                Number(475123456.0),
+                Text("emile@telenet.be"),
+            ],
+            vec![
+                Text("An"),
+                Text("Smets"),
+                Text("Luikerstwg"),
+                Text("101"),
+                Text("0476 00 11 22 (dochter)"),
+                Text(""),
+            ],
+            vec![
+                Text("Test"),
+                Text("Test"),
+                Text("Kerkstraat"),
+                Text("1"),
+                Text("0000000000"),
+                Text("test@test.be"),
+            ],
+            vec![
+                Text("Geert"),
+                Text("Wouters"),
+                Text(""),
+                Text("14"),
+                Text("0477 44 55 66"),
+                Text(""),
+            ],
+            vec![
+                Text("Sofie"),
+                Text("Maes"),
+                Text("Weg; achter de kerk"),
+                Text("2"),
+                Text(""),
+                Text("sofie.maes@proximus.be"),
+            ],
+            vec![
+                Text("Tom"),
+                Text("Lenaerts"),
+                Text("Stationsstraat"),
+                Text("5"),
+                Text("0478 99 88 77"),
+                Text("tom@telenet.be"),
+            ],
+            vec![
+                Text("Tom"),
+                Text("Lenaerts"),
+                Text("Stationsstraat"),
+                Text("5"),
+                Text("0478/99.88.77"),
+                Text("tom@telenet.be"),
+            ],
+            vec![
+                Text("  Karel "),
+                Text("  Aerts"),
+                Text("Dorpsstraat  "),
+                Text("8"),
+                Text("0032 479 12 34 56"),
+                Text(""),
+            ],
+        ],
+    )?;
+
+    // No "Mobiel nummer" column: refused
+    write_list(
+        &dir.join("missing_column.xlsx"),
+        &["Voornaam", "Naam", "Straat", "Huisnummer", "E-mailadres"],
+        &[vec![
+            Text("Jan"),
+            Text("Peeters"),
+            Text("Kerkstraat"),
+            Text("12"),
+            Text("jan.peeters@telenet.be"),
+        ]],
+    )?;
+
+    // A zip header and nothing else: not a workbook
+    let mut broken = b"PK\x03\x04".to_vec();
+    broken.extend([0; 26]);
+    broken.extend(b"this is not a workbook\n");
+    fs::write(dir.join("broken.xlsx"), broken)?;
+    Ok(())
+}
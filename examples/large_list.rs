@@ -0,0 +1,49 @@
+// examples/large_list.rs
+// Large contact list for timing a conversion
+// ------------------------------------------
+//
+//   cargo run --release --example large_list -- 1000000 target/large.xlsx
+//   cargo build --release --bin be-alert-convert
+//   time target/release/be-alert-convert target/large.xlsx --out-dir target/large
+//
+// Writes a contact list with the given number of rows (default 100000), each
+// with a different mobile number and e-mail address, to time the command
+// line on a list the size of a large gemeente's extract.
+
+use rust_xlsxwriter::Workbook;
+use std::env;
+
+const HEADER: [&str; 6] = [
+    "Voornaam",
+    "Naam",
+    "Straat",
+    "Huisnummer",
+    "Mobiel nummer",
+    "E-mailadres",
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    let rows: u32 = match args.next() {
+        Some(rows) => rows.parse()?,
+        None => 100_000,
+    };
+    let path = args.next().unwrap_or_else(|| "large.xlsx".to_string());
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    for (col, name) in HEADER.iter().enumerate() {
+        sheet.write_string(0, col as u16, *name)?;
+    }
+    for i in 1..=rows {
+        sheet.write_string(i, 0, "Jan")?;
+        sheet.write_string(i, 1, format!("Peeters {}", i))?;
+        sheet.write_string(i, 2, "Kerkstraat")?;
+        sheet.write_number(i, 3, (i % 200 + 1) as f64)?;
+        sheet.write_string(i, 4, format!("0475 {:06}", i))?;
+        sheet.write_string(i, 5, format!("jan.peeters{}@telenet.be", i))?;
+    }
+    workbook.save(&path)?;
+    println!("{} rows written to {}", rows, path);
+    Ok(())
+}
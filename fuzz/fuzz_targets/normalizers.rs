@@ -0,0 +1,33 @@
+// fuzz/fuzz_targets/normalizers.rs
+// Fuzzing the cell normalizers
+// ----------------------------
+//
+//   cargo +nightly fuzz run normalizers
+//
+// Feeds arbitrary cell text to the phone, e-mail, house number and address
+// parsers. Besides not panicking they must keep the properties
+// tests/normalizers.rs checks on generated input: normalizing a number or an
+// e-mail address twice changes nothing, the house number is the leading
+// digits, the address split returns a prefix and a suffix of the address.
+
+#![no_main]
+
+use be_alert_convert::convert::{
+    extract_house_number, normalize_be_phone, normalize_email, split_address,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|cell: &str| {
+    let number = normalize_be_phone(cell);
+    assert_eq!(normalize_be_phone(&number), number);
+
+    let email = normalize_email(cell);
+    assert_eq!(normalize_email(&email), email);
+
+    let house_number = extract_house_number(cell);
+    assert!(cell.trim().starts_with(&house_number));
+
+    let (street, rest) = split_address(cell);
+    assert!(cell.trim().starts_with(&street));
+    assert!(cell.trim().ends_with(&rest));
+});
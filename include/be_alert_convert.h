@@ -0,0 +1,47 @@
+/*
+ * be_alert_convert.h
+ * C API of the BE-Alert BIN convertor (build with: cargo build --release --lib --features ffi)
+ *
+ * Converts an XLSX file held in memory to a BE-Alert CSV with the same rules
+ * as the desktop application. Nothing is written to disk.
+ *
+ *   BeAlertResult *r = be_alert_convert_buffer(bytes, len,
+ *       "{\"name\": \"alken.xlsx\", \"profile\": {\"unknown_phone\": \"blank\"}}");
+ *   if (r->ok) fwrite(r->csv, 1, r->csv_len, out);
+ *   puts(r->report_json);
+ *   be_alert_free_result(r);
+ */
+#ifndef BE_ALERT_CONVERT_H
+#define BE_ALERT_CONVERT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct BeAlertResult {
+    /* 1 when converted, 0 on error. */
+    int32_t ok;
+    /* CSV bytes (not NUL-terminated); NULL on error. */
+    uint8_t *csv;
+    size_t csv_len;
+    /* UTF-8 JSON: the summary (rows, warnings, rejected, cell_warnings, ...)
+       on success, {"error": "..."} on error. */
+    char *report_json;
+} BeAlertResult;
+
+/* options_json: NULL or {"name": "...", "profile": {...}}; the profile takes
+   the keys of a profile TOML file, missing keys are the Alken defaults.
+   Never returns NULL. */
+BeAlertResult *be_alert_convert_buffer(const uint8_t *input, size_t len, const char *options_json);
+
+/* Release a result (NULL is ignored). */
+void be_alert_free_result(BeAlertResult *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BE_ALERT_CONVERT_H */
@@ -0,0 +1,176 @@
+// src/abbreviations.rs
+// Street name abbreviations
+// -------------------------
+//
+// Registers abbreviate street names ("Kon. Albertlaan", "St. Jansstraat",
+// "Luikerstwg"). BE-Alert geocodes the address text and finds the full name
+// far more often, so with the profile's `expand_abbreviations` every word of
+// "Adres incl huisnummer" is looked up (case-insensitive) in the built-in
+// table and the profile's `[street_abbreviations]` before the CSV is written:
+//
+// - "kon." = "Koning"    the whole word
+// - "-str." = "straat"   a word ending ("Kerkstr." -> "Kerkstraat")
+// - "st." = "Sint-"      a value ending in '-' is joined to the next word
+//                        ("St. Jansstraat" -> "Sint-Jansstraat")
+//
+// A profile entry replaces the built-in one of the same key; an empty value
+// switches it off. Words in capitals get the expansion in capitals. The
+// built-in table holds only abbreviations with one reading: "Dr." (Dokter or
+// Dreef), "Ln." and a bare "Kon" are left to the profile.
+
+use std::collections::BTreeMap;
+
+const BUILT_IN: [(&str, &str); 11] = [
+    ("kon.", "Koning"),
+    ("st.", "Sint-"),
+    ("burg.", "Burgemeester"),
+    ("o.l.v.", "Onze-Lieve-Vrouw-"),
+    ("stwg", "steenweg"),
+    ("stwg.", "steenweg"),
+    ("-stwg", "steenweg"),
+    ("-stwg.", "steenweg"),
+    ("-str.", "straat"),
+    ("-str", "straat"),
+    ("-ln.", "laan"),
+];
+
+/// Abbreviation table of one profile.
+pub struct Abbreviations {
+    /// Whole words, lower case.
+    words: Vec<(String, String)>,
+    /// Word endings (without the leading '-'), lower case.
+    endings: Vec<(String, String)>,
+}
+
+impl Abbreviations {
+    /// The built-in table with the profile's entries over it.
+    pub fn new(profile_entries: &BTreeMap<String, String>) -> Self {
+        let mut table: BTreeMap<String, String> = BUILT_IN
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        for (key, value) in profile_entries {
+            table.insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+        let (mut words, mut endings) = (Vec::new(), Vec::new());
+        for (key, value) in table {
+            if value.is_empty() {
+                continue;
+            }
+            match key.strip_prefix('-') {
+                Some(ending) if !ending.is_empty() => endings.push((ending.to_string(), value)),
+                _ => words.push((key, value)),
+            }
+        }
+        // Longest ending first: "-stwg." before "-stwg"
+        endings.sort_by_key(|(ending, _)| std::cmp::Reverse(ending.len()));
+        Abbreviations { words, endings }
+    }
+
+    /// The address with its abbreviations written out; None when there are none.
+    pub fn expand(&self, address: &str) -> Option<String> {
+        let mut expanded = String::new();
+        let mut changed = false;
+        let mut join = false;
+        for word in address.split_whitespace() {
+            let replaced = self.expand_word(word);
+            changed |= replaced.is_some();
+            let text = replaced.unwrap_or_else(|| word.to_string());
+            if !expanded.is_empty() && !join {
+                expanded.push(' ');
+            }
+            join = text.ends_with('-');
+            expanded.push_str(&text);
+        }
+        changed.then_some(expanded)
+    }
+
+    fn expand_word(&self, word: &str) -> Option<String> {
+        let lower = word.to_lowercase();
+        let capitals =
+            word.chars().any(char::is_alphabetic) && !word.chars().any(char::is_lowercase);
+        let cased = |value: &str| {
+            if capitals {
+                value.to_uppercase()
+            } else {
+                value.to_string()
+            }
+        };
+        if let Some((_, value)) = self.words.iter().find(|(key, _)| *key == lower) {
+            return Some(cased(value));
+        }
+        self.endings.iter().find_map(|(ending, value)| {
+            let stem = lower.strip_suffix(ending.as_str())?;
+            // "Str." alone is not an ending; keep at least one letter before it
+            if stem.is_empty() || !stem.chars().all(char::is_alphabetic) {
+                return None;
+            }
+            Some(format!("{}{}", word.get(..stem.len())?, cased(value)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(address: &str) -> Option<String> {
+        Abbreviations::new(&BTreeMap::new()).expand(address)
+    }
+
+    #[test]
+    fn words_and_endings_are_written_out() {
+        assert_eq!(
+            expand("Kon. Albertlaan 12").as_deref(),
+            Some("Koning Albertlaan 12")
+        );
+        assert_eq!(expand("Luikerstwg 5").as_deref(), Some("Luikersteenweg 5"));
+        assert_eq!(expand("Kerkstr. 3").as_deref(), Some("Kerkstraat 3"));
+        assert_eq!(
+            expand("Stationsstwg. 1").as_deref(),
+            Some("Stationssteenweg 1")
+        );
+    }
+
+    #[test]
+    fn prefix_is_joined_to_the_next_word() {
+        assert_eq!(
+            expand("St. Jansstraat 4").as_deref(),
+            Some("Sint-Jansstraat 4")
+        );
+        assert_eq!(
+            expand("O.L.V. Kerkplein 1").as_deref(),
+            Some("ONZE-LIEVE-VROUW-Kerkplein 1")
+        );
+    }
+
+    #[test]
+    fn capitals_stay_capitals() {
+        assert_eq!(
+            expand("KON. ALBERTLAAN 1").as_deref(),
+            Some("KONING ALBERTLAAN 1")
+        );
+        assert_eq!(expand("KERKSTR 1").as_deref(), Some("KERKSTRAAT 1"));
+    }
+
+    #[test]
+    fn ambiguous_and_plain_words_are_kept() {
+        assert_eq!(expand("Dr. Van de Perrestraat 2"), None);
+        assert_eq!(expand("Kon Albertlaan 2"), None);
+        assert_eq!(expand("Beukenln. 2"), Some("Beukenlaan 2".to_string()));
+        assert_eq!(expand("Ln. 2"), None);
+        assert_eq!(expand("Str. 2"), None);
+        assert_eq!(expand("Kerkstraat 2"), None);
+    }
+
+    #[test]
+    fn profile_entries_add_and_switch_off() {
+        let entries = BTreeMap::from([
+            ("Dr.".to_string(), "Dreef".to_string()),
+            ("st.".to_string(), String::new()),
+        ]);
+        let table = Abbreviations::new(&entries);
+        assert_eq!(table.expand("Dr. 3").as_deref(), Some("Dreef 3"));
+        assert_eq!(table.expand("St. Jansstraat 4"), None);
+    }
+}
@@ -0,0 +1,722 @@
+// src/batch.rs
+// Batch conversion (command line)
+// -------------------------------
+//
+// Converts every XLSX matching one or more glob patterns, e.g.
+// "exports/**/*.xlsx". The directories below the pattern's fixed part are
+// mirrored under the output directory:
+//
+//   exports/2025/Q1/alken.xlsx  ->  converted/2025/Q1/alken.csv
+//
+// Every file gets its own HTML report next to the CSV; the batch as a whole
+// is summarized in one JSON report. Files open in Excel, or an output another
+// export is writing (see lock.rs), are retried a few times before they count
+// as failed.
+//
+// `BatchLimits` sets how many files convert in parallel and how long one file
+// may take: a file over the timeout is reported as failed and the batch goes
+// on. A stuck conversion cannot be interrupted, but it is cancelled: it stops
+// at its next stage, and as every file is written to a private temporary
+// folder first and only moved into place as the last step, one over its time
+// never leaves files (or a history run) behind.
+//
+// Unattended runs (schedule.rs) also set `retries`: a file failing with a
+// transient I/O error (a network share dropping out) is tried again after
+// `retry_delay`, doubling the wait each time. The retries are counted in the
+// file's result.
+//
+// `run_staged` makes a batch all or nothing: the files are written to a
+// hidden staging folder in the output directory and moved into place (a
+// rename on the same disk) only when every input converted. When one fails,
+// the staged files are removed and their runs taken out of the history, so
+// the output directory never holds half a set that passes for the whole. A
+// move that fails halfway is undone, the files it replaced put back. The
+// reports cite the files next to them by name, so they hold no staging paths.
+
+use crate::convert::{check_not_input, prepare_xlsx_export, write_export};
+use crate::dedup;
+use crate::error::{ConvertError, ExitKind, Result};
+#[cfg(feature = "native")]
+use crate::history::History;
+use crate::lock::OutputLock;
+use crate::profile::Profile;
+use crate::report::{ConvertSummary, RunInfo, TOOL_VERSION};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Extra attempts for a file that is open in another program (or being
+/// written by another export).
+const LOCK_RETRIES: usize = 3;
+const LOCK_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// How often a timed-out batch checks again on a file that was just moving
+/// its output into place when the time ran out.
+const COMMIT_POLL: Duration = Duration::from_millis(100);
+
+/// Numbers the temporary folders of this process.
+static WORK_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// Parallel conversions and per-file timeout of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchLimits {
+    /// Files converted at the same time (at least 1).
+    pub jobs: usize,
+    /// Time one file may take, retries included; None: no limit.
+    pub timeout: Option<Duration>,
+    /// Extra attempts for a transient I/O error (0: none).
+    pub retries: usize,
+    /// Wait before the first retry; doubled for every next one.
+    pub retry_delay: Duration,
+}
+
+impl Default for BatchLimits {
+    fn default() -> Self {
+        BatchLimits {
+            jobs: 1,
+            timeout: None,
+            retries: 0,
+            retry_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// One matched input file.
+#[derive(Debug, Clone)]
+pub struct BatchInput {
+    pub path: PathBuf,
+    /// Path below the pattern's fixed directory, mirrored in the output directory.
+    pub relative: PathBuf,
+}
+
+/// Outcome of one file, as written to the JSON report.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub rows: usize,
+    pub unknown_phones: usize,
+    pub rejected: usize,
+    pub warnings: Vec<String>,
+    pub report: Option<PathBuf>,
+    /// Change since the last run of the same file (see history.rs).
+    pub delta: Option<String>,
+    /// Attempts repeated after a transient I/O error.
+    pub retries: usize,
+    /// Set when the file could not be converted (nothing written).
+    pub error: Option<String>,
+    /// Kind of `error`, as the command line's exit code.
+    pub error_kind: Option<ExitKind>,
+    /// Run stored in the history for this file.
+    #[serde(skip)]
+    pub history_run: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    /// Convertor version (report::TOOL_VERSION).
+    pub version: String,
+    pub profile: String,
+    /// See Profile::settings_hash.
+    pub profile_hash: String,
+    pub operator: String,
+    pub remark: String,
+    pub files: Vec<FileResult>,
+    pub converted: usize,
+    pub failed: usize,
+    pub rows: usize,
+    /// Staged batch (`run_staged`): whether the files were moved into the
+    /// output directory. None for a batch written in place.
+    pub committed: Option<bool>,
+}
+
+fn has_wildcard(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+/// Directory part of a pattern before the first wildcard component
+/// ("exports/**/*.xlsx" -> "exports"). For a plain file, its directory.
+fn pattern_base(pattern: &str) -> PathBuf {
+    let path = Path::new(pattern);
+    if !has_wildcard(pattern) {
+        return path.parent().map(Path::to_path_buf).unwrap_or_default();
+    }
+    path.components()
+        .take_while(|c| !has_wildcard(&c.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// Files matching the patterns, in pattern order, each file once.
+pub fn expand_inputs(
+    patterns: &[String],
+) -> std::result::Result<Vec<BatchInput>, glob::PatternError> {
+    let mut inputs: Vec<BatchInput> = Vec::new();
+    for pattern in patterns {
+        let base = pattern_base(pattern);
+        for path in glob::glob(pattern)?.filter_map(|p| p.ok()) {
+            if !path.is_file() || inputs.iter().any(|i| i.path == path) {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(&base)
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|_| path.file_name().map(PathBuf::from).unwrap_or_default());
+            inputs.push(BatchInput { path, relative });
+        }
+    }
+    Ok(inputs)
+}
+
+/// Output CSV for an input: its relative path under `out_dir`, extension .csv.
+/// Components that would leave `out_dir` ("..") are dropped.
+pub fn output_path(out_dir: &Path, input: &BatchInput) -> PathBuf {
+    let relative: PathBuf = input
+        .relative
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect();
+    out_dir.join(relative).with_extension("csv")
+}
+
+/// Whether a conversion may still put its files in place. The batch cancels
+/// a file over its timeout; the conversion checks between its stages and
+/// claims the commit before moving its files, so only one of the two wins.
+#[derive(Debug, Default)]
+struct Job(AtomicU8);
+
+const RUNNING: u8 = 0;
+const COMMITTING: u8 = 1;
+const CANCELLED: u8 = 2;
+
+impl Job {
+    fn cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst) == CANCELLED
+    }
+
+    /// Stop the conversion; false while it is moving its files into place.
+    fn cancel(&self) -> bool {
+        self.0
+            .compare_exchange(RUNNING, CANCELLED, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
+    /// Claim the move into place; false once cancelled.
+    fn commit(&self) -> bool {
+        self.0
+            .compare_exchange(RUNNING, COMMITTING, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
+    /// Move failed: the batch may cancel again (before a retry).
+    fn release(&self) {
+        self.0.store(RUNNING, Ordering::SeqCst);
+    }
+}
+
+/// Private folder a conversion writes to before its files are moved in place.
+fn work_dir() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "be-alert-{}-{}",
+        std::process::id(),
+        WORK_DIRS.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Rename, or copy when the temporary folder is on another disk.
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    fs::rename(from, to).or_else(|_| {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    })
+}
+
+fn convert_file(
+    input: &Path,
+    output: &Path,
+    profile: &Profile,
+    run: &RunInfo,
+    job: &Job,
+) -> Result<ConvertSummary> {
+    if job.cancelled() {
+        return Err(ConvertError::Cancelled);
+    }
+    let mut prepared = prepare_xlsx_export(&input.to_string_lossy(), profile)?;
+    prepared.summary.run = run.clone();
+    // No one to review duplicates here: all rows are kept, the count is reported
+    let groups = dedup::find_groups(&prepared.records);
+    if !groups.is_empty() {
+        prepared.summary.warnings.push(format!(
+            "{} Tel/Ref. numbers on more than one row (all rows kept)",
+            groups.len()
+        ));
+    }
+    check_not_input(output, &prepared.inputs)?;
+    if job.cancelled() {
+        return Err(ConvertError::Cancelled);
+    }
+
+    let work = work_dir();
+    fs::create_dir_all(&work).map_err(|e| ConvertError::io_at(&work, e))?;
+    let staged = work.join(output.file_name().unwrap_or_default());
+    let mut summary = match write_export(prepared, &staged.to_string_lossy()) {
+        Ok(summary) => summary,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&work);
+            return Err(e);
+        }
+    };
+    let placed = if job.commit() {
+        let placed = place(&work, output, &mut summary, run);
+        if placed.is_err() {
+            job.release();
+        }
+        placed
+    } else {
+        Err(ConvertError::Cancelled)
+    };
+    // Empty unless the files could not be placed
+    let _ = fs::remove_dir_all(&work);
+    #[cfg(feature = "native")]
+    if let (Err(_), Some(run_id)) = (&placed, summary.history_run) {
+        // History is a convenience: a failure here leaves a stale entry
+        let _ = History::open().and_then(|mut h| h.forget(run_id));
+    }
+    placed.map(|()| summary)
+}
+
+/// Move the files written to `work` next to `output`, the CSV last (so no CSV
+/// is in place without its report), under the output's lock, and point the
+/// summary and its history run to them. What was moved is removed again when
+/// a file cannot be.
+fn place(work: &Path, output: &Path, summary: &mut ConvertSummary, run: &RunInfo) -> Result<()> {
+    let dir = output.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).map_err(|e| ConvertError::io_at(dir, e))?;
+    let _lock = OutputLock::acquire(output, run)?;
+    let csv = work.join(output.file_name().unwrap_or_default());
+    let mut files = fs::read_dir(work)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    files.retain(|file| *file != csv);
+    files.push(csv);
+    let mut moved = Vec::new();
+    for file in files {
+        let target = dir.join(file.file_name().unwrap_or_default());
+        if let Err(e) = move_file(&file, &target) {
+            for done in moved {
+                let _ = fs::remove_file(done);
+            }
+            return Err(ConvertError::io_at(&target, e));
+        }
+        moved.push(target);
+    }
+
+    let in_place = |path: &mut PathBuf| *path = dir.join(path.file_name().unwrap_or_default());
+    for path in [
+        &mut summary.report,
+        &mut summary.review_file,
+        &mut summary.rejected_file,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        in_place(path);
+    }
+    if let Some((path, _)) = &mut summary.delete_file {
+        in_place(path);
+    }
+    #[cfg(feature = "native")]
+    if let (Some(run_id), Ok(history)) = (summary.history_run, History::open()) {
+        let _ = history.set_output(run_id, output);
+    }
+    Ok(())
+}
+
+fn empty_result(input: &BatchInput, output: &Path) -> FileResult {
+    FileResult {
+        input: input.path.clone(),
+        output: output.to_path_buf(),
+        rows: 0,
+        unknown_phones: 0,
+        rejected: 0,
+        warnings: Vec::new(),
+        report: None,
+        delta: None,
+        retries: 0,
+        error: None,
+        error_kind: None,
+        history_run: None,
+    }
+}
+
+/// Convert one file, retrying while it is locked (and after transient I/O
+/// errors, up to `limits.retries`), within `limits.timeout` when given.
+/// Never fails: errors (and time-outs) end up in the result.
+pub fn convert_one(
+    input: &BatchInput,
+    output: &Path,
+    profile: &Profile,
+    run: &RunInfo,
+    limits: &BatchLimits,
+) -> FileResult {
+    let Some(timeout) = limits.timeout else {
+        return convert_retrying(input, output, profile, run, limits, &Job::default());
+    };
+    let (tx, rx) = mpsc::channel();
+    let job = Arc::new(Job::default());
+    let work = (
+        input.clone(),
+        output.to_path_buf(),
+        profile.clone(),
+        run.clone(),
+        *limits,
+        Arc::clone(&job),
+    );
+    thread::spawn(move || {
+        let (input, output, profile, run, limits, job) = work;
+        let _ = tx.send(convert_retrying(
+            &input, &output, &profile, &run, &limits, &job,
+        ));
+    });
+    let deadline = Instant::now() + timeout;
+    let error = loop {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(result) => return result,
+            // Just moving its files into place: wait for that to finish
+            Err(RecvTimeoutError::Timeout) if !job.cancel() => {
+                if let Ok(result) = rx.recv_timeout(COMMIT_POLL) {
+                    return result;
+                }
+            }
+            Err(e) => break e,
+        }
+    };
+    let mut result = empty_result(input, output);
+    let (error, kind) = match error {
+        RecvTimeoutError::Timeout => (
+            format!(
+                "timed out after {} s; the file was skipped",
+                timeout.as_secs()
+            ),
+            ExitKind::Cancelled,
+        ),
+        RecvTimeoutError::Disconnected => ("the conversion crashed".to_string(), ExitKind::Io),
+    };
+    result.error = Some(error);
+    result.error_kind = Some(kind);
+    result
+}
+
+fn convert_retrying(
+    input: &BatchInput,
+    output: &Path,
+    profile: &Profile,
+    run: &RunInfo,
+    limits: &BatchLimits,
+    job: &Job,
+) -> FileResult {
+    let mut result = empty_result(input, output);
+    let mut locked = 0;
+    let mut delay = limits.retry_delay;
+    let mut converted = convert_file(&input.path, output, profile, run, job);
+    loop {
+        match &converted {
+            // A file open in Excel is usually closed again soon, another
+            // export done: try a few more times
+            Err(ConvertError::FileLocked { .. } | ConvertError::OutputLocked { .. })
+                if locked < LOCK_RETRIES =>
+            {
+                locked += 1;
+                thread::sleep(LOCK_RETRY_DELAY);
+            }
+            Err(e) if e.is_transient() && result.retries < limits.retries => {
+                result.retries += 1;
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+            }
+            _ => break,
+        }
+        converted = convert_file(&input.path, output, profile, run, job);
+    }
+    match converted {
+        Ok(summary) => {
+            result.rows = summary.rows;
+            result.unknown_phones = summary.unknown_phones;
+            result.rejected = summary.rejected.len();
+            result.warnings = summary.warnings;
+            result.report = summary.report;
+            result.delta = summary.delta.map(|d| d.text());
+            result.history_run = summary.history_run;
+        }
+        Err(e) => {
+            result.error = Some(e.to_string());
+            result.error_kind = Some(e.exit_kind());
+        }
+    }
+    result
+}
+
+/// Convert all inputs with one profile, `limits.jobs` at a time. `progress`
+/// is called after each file, in the order they finish; the report lists the
+/// files in input order.
+pub fn run(
+    inputs: &[BatchInput],
+    out_dir: &Path,
+    profile: &Profile,
+    run: &RunInfo,
+    limits: &BatchLimits,
+    mut progress: impl FnMut(&FileResult),
+) -> BatchReport {
+    let mut report = BatchReport {
+        version: TOOL_VERSION.to_string(),
+        profile: profile.name.clone(),
+        profile_hash: profile.settings_hash(),
+        operator: run.operator.clone(),
+        remark: run.remark.clone(),
+        files: Vec::new(),
+        converted: 0,
+        failed: 0,
+        rows: 0,
+        committed: None,
+    };
+    let mut results: Vec<Option<FileResult>> = inputs.iter().map(|_| None).collect();
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..limits.jobs.clamp(1, inputs.len().max(1)) {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(i) else {
+                    break;
+                };
+                let output = output_path(out_dir, input);
+                let result = convert_one(input, &output, profile, run, limits);
+                if tx.send((i, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+        for (i, result) in rx {
+            progress(&result);
+            results[i] = Some(result);
+        }
+    });
+
+    for result in results.into_iter().flatten() {
+        if result.error.is_some() {
+            report.failed += 1;
+        } else {
+            report.converted += 1;
+            report.rows += result.rows;
+        }
+        report.files.push(result);
+    }
+    report
+}
+
+/// Hidden folder in `out_dir` a staged batch writes to.
+pub fn staging_dir(out_dir: &Path) -> PathBuf {
+    out_dir.join(format!(".staging-{}", std::process::id()))
+}
+
+/// As `run`, all or nothing: the files are written to `staging_dir` and
+/// moved into `out_dir` only when every input converted; otherwise they are
+/// removed again and `out_dir` is left as it was. The paths in the report
+/// (and given to `progress`) are those in `out_dir`. Fails only when the
+/// staged files cannot be moved (`out_dir` is then put back as it was) or
+/// removed.
+pub fn run_staged(
+    inputs: &[BatchInput],
+    out_dir: &Path,
+    profile: &Profile,
+    run_info: &RunInfo,
+    limits: &BatchLimits,
+    mut progress: impl FnMut(&FileResult),
+) -> io::Result<BatchReport> {
+    let staging = staging_dir(out_dir);
+    let mut report = run(inputs, &staging, profile, run_info, limits, |result| {
+        let mut shown = result.clone();
+        relocate(&mut shown, &staging, out_dir);
+        progress(&shown)
+    });
+    let moved = if report.failed == 0 {
+        commit_staged(&staging, out_dir)
+    } else {
+        Ok(())
+    };
+    let committed = report.failed == 0 && moved.is_ok();
+    let removed = if staging.exists() {
+        fs::remove_dir_all(&staging)
+    } else {
+        Ok(())
+    };
+
+    for result in &mut report.files {
+        relocate(result, &staging, out_dir);
+    }
+    #[cfg(feature = "native")]
+    if let Ok(mut history) = History::open() {
+        for result in &report.files {
+            let Some(run_id) = result.history_run else {
+                continue;
+            };
+            // History is a convenience: a failure here leaves a stale entry
+            let _ = if committed {
+                history.set_output(run_id, &result.output)
+            } else {
+                history.forget(run_id)
+            };
+        }
+    }
+    moved?;
+    removed?;
+    report.committed = Some(committed);
+    Ok(report)
+}
+
+/// Move every staged file to its place in `out_dir`. A file it replaces is
+/// kept aside in the staging folder until all are moved: when one cannot be,
+/// the moved files go back and the replaced ones are restored.
+fn commit_staged(staging: &Path, out_dir: &Path) -> io::Result<()> {
+    let files = staged_files(staging)?;
+    let kept_dir = staging.join(".replaced");
+    // (staged file, its place in out_dir, the file it replaced)
+    let mut moved: Vec<(PathBuf, PathBuf, Option<PathBuf>)> = Vec::new();
+    let mut failed = None;
+    for file in files {
+        let relative = staged_relative(&file, staging).to_path_buf();
+        let target = out_dir.join(&relative);
+        if let Some(dir) = target.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                failed = Some(e);
+                break;
+            }
+        }
+        let kept = if target.exists() {
+            let kept = kept_dir.join(&relative);
+            let aside = kept
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::rename(&target, &kept));
+            if let Err(e) = aside {
+                failed = Some(e);
+                break;
+            }
+            Some(kept)
+        } else {
+            None
+        };
+        let result = fs::rename(&file, &target);
+        moved.push((file, target, kept));
+        if let Err(e) = result {
+            failed = Some(e);
+            break;
+        }
+    }
+    let Some(e) = failed else {
+        return Ok(());
+    };
+    for (file, target, kept) in moved.into_iter().rev() {
+        // Fails harmlessly for the file that was not moved
+        let _ = fs::rename(&target, &file);
+        if let Some(kept) = kept {
+            let _ = fs::rename(&kept, &target);
+        }
+    }
+    Err(e)
+}
+
+/// Path below `staging` of a staged output.
+fn staged_relative<'a>(path: &'a Path, staging: &Path) -> &'a Path {
+    path.strip_prefix(staging).unwrap_or(path)
+}
+
+/// Point the paths of a staged file's result to `out_dir`.
+fn relocate(result: &mut FileResult, staging: &Path, out_dir: &Path) {
+    result.output = out_dir.join(staged_relative(&result.output, staging));
+    if let Some(report) = &mut result.report {
+        *report = out_dir.join(staged_relative(report, staging));
+    }
+}
+
+/// Every file below `dir`.
+fn staged_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(staged_files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+pub fn write_json(path: &Path, report: &BatchReport) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(report).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh empty folder under the system's temporary folder.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("be-alert-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn failed_commit_puts_the_output_directory_back() {
+        let root = scratch("commit");
+        let (staging, out_dir) = (root.join("staging"), root.join("out"));
+        fs::create_dir_all(staging.join("sub")).unwrap();
+        fs::create_dir_all(&out_dir).unwrap();
+        fs::write(staging.join("a.csv"), "new").unwrap();
+        fs::write(staging.join("sub").join("b.csv"), "new").unwrap();
+        fs::write(out_dir.join("a.csv"), "old").unwrap();
+        // A file where the staged folder has to go: that move fails
+        fs::write(out_dir.join("sub"), "not a folder").unwrap();
+
+        assert!(commit_staged(&staging, &out_dir).is_err());
+        assert_eq!(fs::read_to_string(out_dir.join("a.csv")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(staging.join("a.csv")).unwrap(), "new");
+        assert!(out_dir.join("sub").is_file());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn commit_replaces_the_previous_files() {
+        let root = scratch("replace");
+        let (staging, out_dir) = (root.join("staging"), root.join("out"));
+        fs::create_dir_all(staging.join("sub")).unwrap();
+        fs::create_dir_all(&out_dir).unwrap();
+        fs::write(staging.join("a.csv"), "new").unwrap();
+        fs::write(staging.join("sub").join("b.csv"), "new").unwrap();
+        fs::write(out_dir.join("a.csv"), "old").unwrap();
+
+        commit_staged(&staging, &out_dir).unwrap();
+        assert_eq!(
+            fs::read_to_string(out_dir.join("sub").join("b.csv")).unwrap(),
+            "new"
+        );
+        assert_eq!(fs::read_to_string(out_dir.join("a.csv")).unwrap(), "new");
+        assert!(!out_dir.join(".replaced").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
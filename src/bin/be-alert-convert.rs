@@ -0,0 +1,690 @@
+// src/bin/be-alert-convert.rs
+// Command line convertor
+// ----------------------
+//
+//   be-alert-convert "exports/**/*.xlsx" --out-dir converted/ --profile Alken
+//
+// Converts every matching XLSX with the same rules as the GUI, mirroring the
+// subdirectories under --out-dir, and writes an aggregate JSON report
+// (default <out-dir>/batch_report.json); --zip bundles the CSVs, reports and
+// batch report for the upload (see package.rs); --staged writes nothing to
+// --out-dir unless every file converts (see batch.rs). --expected 12345 (one input)
+// refuses the export when the number of contacts is further from the
+// register's count than the profile's expected_tolerance_percent.
+//
+//   be-alert-convert --completions bash > /etc/bash_completion.d/be-alert-convert
+//   be-alert-convert --man > be-alert-convert.1
+//
+//   be-alert-convert --verify "uploads/*.csv"
+//
+// checks existing BE-Alert CSVs without converting anything (see verify.rs)
+// and exits with 1 if any file has problems.
+//
+//   be-alert-convert --peek "//fileserver/bevolking/*.xlsx"
+//
+// shows the header and first rows of each XLSX, reading only the start of
+// the file, and exits with 2 if a required column is missing (see peek.rs).
+//
+//   be-alert-convert --to-xlsx "uploads/*.csv" --out-dir lists/
+//
+// turns BE-Alert CSVs back into XLSX contact lists (see reverse.rs).
+//
+//   be-alert-convert --from xlsx --to csv < input.xlsx > output.csv
+//
+// converts one file in a shell pipeline, without temporary files: the
+// XLSX (or a CSV contact list, --from csv) is read from stdin and the
+// BE-Alert CSV written to stdout. Messages go to stderr; --report-file writes
+// the JSON summary. Nothing is kept in the history. The separator, quote
+// and header row of a CSV list are detected (see dialect.rs) and shown on
+// stderr; --delimiter, --quote and --no-header override them.
+//
+// With --schedule it runs the scheduled export of settings.toml (see
+// schedule.rs) until stopped, e.g. as a service on the file server.
+//
+// Exit codes (error::ExitKind): 0 success, 1 validation errors (rows, profile,
+// arguments), 2 missing columns, 3 I/O, 4 cancelled (timed out). When files
+// fail for different reasons the highest code wins. The last line on stderr
+// is a status for scripts:
+//
+//   status=missing_columns code=2 converted=4 failed=1 rows=12031
+//
+// The summary lines and reports are in the language of --lang (en, nl, fr),
+// else of the locale variables (LANG, see i18n.rs); the status line is always
+// English.
+
+use be_alert_convert::batch::{self, BatchLimits};
+use be_alert_convert::convert::{check_expected, convert_csv_bytes, convert_xlsx_bytes, csv_text};
+use be_alert_convert::dialect::{self, CsvDialect};
+use be_alert_convert::error::{ConvertError, ExitKind};
+use be_alert_convert::i18n::{translate, trf, Lang};
+use be_alert_convert::package;
+use be_alert_convert::peek;
+use be_alert_convert::profile::{self, find_profile, Profile};
+use be_alert_convert::report::RunInfo;
+use be_alert_convert::reverse;
+use be_alert_convert::schedule::{self, Cron};
+use be_alert_convert::settings::Settings;
+use be_alert_convert::verify;
+use chrono::Local;
+use clap::{CommandFactory, Parser, ValueEnum};
+use clap_complete::Shell;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Input format of pipe mode.
+#[derive(Clone, Copy, ValueEnum)]
+enum InputFormat {
+    Xlsx,
+    /// Contact list saved as CSV UTF-8, same columns as the XLSX
+    Csv,
+}
+
+/// Output format of pipe mode.
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Csv,
+}
+
+#[derive(Parser)]
+#[command(
+    name = "be-alert-convert",
+    version,
+    about = "Convert BIN XLSX files to BE-Alert CSV"
+)]
+struct Cli {
+    /// XLSX files or glob patterns (quote them: "exports/**/*.xlsx"); CSV files with --verify
+    #[arg(required_unless_present_any = ["completions", "man", "schedule", "from"])]
+    inputs: Vec<String>,
+
+    /// Output directory; subdirectories below each pattern are mirrored
+    #[arg(short, long, required_unless_present_any = ["completions", "man", "schedule", "verify", "peek", "from"])]
+    out_dir: Option<PathBuf>,
+
+    /// Profile name (default: the first profile, Alken)
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// Aggregate JSON report (default: <out-dir>/batch_report.json)
+    #[arg(long)]
+    json_report: Option<PathBuf>,
+
+    /// Name recorded in the history and reports (default: the logged-in user)
+    #[arg(long)]
+    operator: Option<String>,
+
+    /// Remark recorded in the history and reports, e.g. the reason for the upload
+    #[arg(long, default_value = "")]
+    remark: String,
+
+    /// Number of residents in the register report; the export is refused when the
+    /// contacts converted differ more than the profile's expected_tolerance_percent (one input)
+    #[arg(long, value_name = "COUNT")]
+    expected: Option<usize>,
+
+    /// Language of the summary lines and reports: en, nl or fr (default: from LANG, else English)
+    #[arg(long, value_name = "CODE", value_parser = parse_lang)]
+    lang: Option<Lang>,
+
+    /// Also bundle the CSVs, their reports and the batch report with a manifest into
+    /// <out-dir>/batch_<timestamp>.zip, for handing over to whoever uploads them
+    #[arg(long)]
+    zip: bool,
+
+    /// All or nothing: write to a staging folder and move the files into --out-dir only
+    /// when every input converted; otherwise only the JSON report is written
+    #[arg(long)]
+    staged: bool,
+
+    /// Files converted in parallel
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: u16,
+
+    /// Seconds one file may take; slower files are reported as failed
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Print a shell completion script (bash, zsh, powershell, fish, elvish) and exit
+    #[arg(long, value_name = "SHELL", exclusive = true)]
+    completions: Option<Shell>,
+
+    /// Print the man page (roff) and exit
+    #[arg(long, exclusive = true)]
+    man: bool,
+
+    /// Check existing BE-Alert CSV files instead of converting (nothing is written)
+    #[arg(long, conflicts_with_all = ["out_dir", "profile", "json_report", "operator", "remark", "expected", "jobs", "timeout", "zip", "staged"])]
+    verify: bool,
+
+    /// Show the header and first rows of each XLSX without converting, reading only
+    /// the start of the file (quick on slow network shares)
+    #[arg(long, conflicts_with_all = ["out_dir", "json_report", "operator", "remark", "expected", "jobs", "timeout", "verify", "zip", "staged"])]
+    peek: bool,
+
+    /// Convert BE-Alert CSV files back to XLSX contact lists in --out-dir
+    #[arg(long, conflicts_with_all = ["profile", "json_report", "operator", "remark", "expected", "jobs", "timeout", "verify", "peek", "zip", "staged"])]
+    to_xlsx: bool,
+
+    /// Pipe mode: convert one file read from stdin, write the CSV to stdout
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["inputs", "out_dir", "json_report", "zip", "staged", "jobs", "timeout", "verify", "to_xlsx"])]
+    from: Option<InputFormat>,
+
+    /// Output format of pipe mode
+    #[arg(long, value_enum, value_name = "FORMAT", requires = "from")]
+    to: Option<OutputFormat>,
+
+    /// JSON summary of pipe mode (rows, warnings, rejected rows, ...)
+    #[arg(long, requires = "from")]
+    report_file: Option<PathBuf>,
+
+    /// Separator of a CSV list in pipe mode (';', ',', tab, ...) instead of the detected one
+    #[arg(long, value_name = "CHAR", value_parser = parse_csv_char, requires = "from")]
+    delimiter: Option<u8>,
+
+    /// Quote character of a CSV list in pipe mode instead of the detected one
+    #[arg(long, value_name = "CHAR", value_parser = parse_csv_char, requires = "from")]
+    quote: Option<u8>,
+
+    /// The CSV list in pipe mode has no header row (columns from the profile or guessed)
+    #[arg(long, requires = "from")]
+    no_header: bool,
+
+    /// Run the scheduled export from settings.toml until stopped
+    #[arg(long, conflicts_with_all = ["inputs", "out_dir", "json_report", "operator", "remark", "expected", "jobs", "timeout", "completions", "man", "from", "zip", "staged"])]
+    schedule: bool,
+}
+
+/// Print the status line (see the top of this file) and exit with its code.
+fn finish(kind: ExitKind, converted: usize, failed: usize, rows: usize) -> ExitCode {
+    eprintln!(
+        "status={} code={} converted={} failed={} rows={}",
+        kind.name(),
+        kind.code(),
+        converted,
+        failed,
+        rows
+    );
+    ExitCode::from(kind.code())
+}
+
+/// `finish` for a run that stopped before converting anything.
+fn fail(kind: ExitKind) -> ExitCode {
+    finish(kind, 0, 0, 0)
+}
+
+fn run_verify(patterns: &[String], lang: Lang) -> ExitCode {
+    let inputs = match batch::expand_inputs(patterns) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("error: invalid pattern: {}", e);
+            return fail(ExitKind::Validation);
+        }
+    };
+    if inputs.is_empty() {
+        eprintln!(
+            "{}",
+            trf(lang, "error: no files match {}", &[&patterns.join(" ")])
+        );
+        return fail(ExitKind::Io);
+    }
+    let (mut failed, mut rows, mut worst) = (0, 0, ExitKind::Success);
+    for input in &inputs {
+        match verify::verify_csv(&input.path) {
+            Ok(result) if result.is_ok() => {
+                rows += result.rows;
+                println!("ok    {} ({})", input.path.display(), result.summary())
+            }
+            Ok(result) => {
+                failed += 1;
+                worst = worst.max(ExitKind::Validation);
+                println!("FAIL  {}: {}", input.path.display(), result.summary());
+                for problem in &result.problems {
+                    println!("      {}", problem.text());
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                worst = worst.max(e.exit_kind());
+                println!("FAIL  {}: {}", input.path.display(), e);
+            }
+        }
+    }
+    println!(
+        "{}",
+        trf(
+            lang,
+            "{} verified, {} with problems",
+            &[&inputs.len(), &failed]
+        )
+    );
+    finish(worst, inputs.len() - failed, failed, rows)
+}
+
+fn run_peek(patterns: &[String], profile: &Profile, lang: Lang) -> ExitCode {
+    let inputs = match batch::expand_inputs(patterns) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("error: invalid pattern: {}", e);
+            return fail(ExitKind::Validation);
+        }
+    };
+    if inputs.is_empty() {
+        eprintln!(
+            "{}",
+            trf(lang, "error: no files match {}", &[&patterns.join(" ")])
+        );
+        return fail(ExitKind::Io);
+    }
+    let (mut failed, mut worst) = (0, ExitKind::Success);
+    for input in &inputs {
+        match peek::peek_xlsx(&input.path, profile, peek::PEEK_ROWS) {
+            Ok(peek) => {
+                if peek.problem.is_some() {
+                    failed += 1;
+                    worst = worst.max(ExitKind::MissingColumns);
+                    println!("FAIL  {}: {}", input.path.display(), peek.text());
+                } else {
+                    println!("ok    {}: {}", input.path.display(), peek.text());
+                }
+                for row in std::iter::once(&peek.header).chain(&peek.rows) {
+                    println!("      {}", row.join("\t"));
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                worst = worst.max(e.exit_kind());
+                println!("FAIL  {}: {}", input.path.display(), e);
+            }
+        }
+    }
+    println!(
+        "{}",
+        trf(
+            lang,
+            "{} peeked at, {} with problems",
+            &[&inputs.len(), &failed]
+        )
+    );
+    finish(worst, inputs.len() - failed, failed, 0)
+}
+
+fn run_to_xlsx(patterns: &[String], out_dir: &Path, lang: Lang) -> ExitCode {
+    let inputs = match batch::expand_inputs(patterns) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("error: invalid pattern: {}", e);
+            return fail(ExitKind::Validation);
+        }
+    };
+    if inputs.is_empty() {
+        eprintln!(
+            "{}",
+            trf(lang, "error: no files match {}", &[&patterns.join(" ")])
+        );
+        return fail(ExitKind::Io);
+    }
+    let (mut failed, mut rows, mut worst) = (0, 0, ExitKind::Success);
+    for input in &inputs {
+        let output = batch::output_path(out_dir, input).with_extension("xlsx");
+        let converted = match output.parent() {
+            Some(dir) => fs::create_dir_all(dir).map_err(|e| ConvertError::io_at(dir, e)),
+            None => Ok(()),
+        }
+        .and_then(|()| reverse::csv_to_xlsx(&input.path, &output));
+        match converted {
+            Ok(written) => {
+                rows += written;
+                println!(
+                    "ok    {} -> {} ({} rows)",
+                    input.path.display(),
+                    output.display(),
+                    written
+                )
+            }
+            Err(e) => {
+                failed += 1;
+                worst = worst.max(e.exit_kind());
+                println!("FAIL  {}: {}", input.path.display(), e);
+            }
+        }
+    }
+    println!(
+        "{}",
+        trf(
+            lang,
+            "{} converted, {} failed",
+            &[&(inputs.len() - failed), &failed]
+        )
+    );
+    finish(worst, inputs.len() - failed, failed, rows)
+}
+
+/// A language code: "nl", "fr-BE", ...
+fn parse_lang(value: &str) -> Result<Lang, String> {
+    Lang::parse(value).ok_or_else(|| "expected en, nl or fr".to_string())
+}
+
+/// A single ASCII character, or "tab".
+fn parse_csv_char(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err("expected one character or \"tab\"".to_string()),
+    }
+}
+
+/// CSV dialect given on the command line, over the detected one.
+struct DialectOverride {
+    delimiter: Option<u8>,
+    quote: Option<u8>,
+    no_header: bool,
+}
+
+impl DialectOverride {
+    fn apply(&self, mut dialect: CsvDialect) -> CsvDialect {
+        dialect.delimiter = self.delimiter.unwrap_or(dialect.delimiter);
+        dialect.quote = self.quote.unwrap_or(dialect.quote);
+        dialect.has_header &= !self.no_header;
+        dialect
+    }
+}
+
+fn run_pipe(
+    from: InputFormat,
+    to: OutputFormat,
+    profile: &Profile,
+    run: RunInfo,
+    report_file: Option<&Path>,
+    dialect_override: &DialectOverride,
+) -> ExitCode {
+    let mut input = Vec::new();
+    if let Err(e) = io::stdin().lock().read_to_end(&mut input) {
+        eprintln!("error: cannot read stdin: {}", e);
+        return finish(ExitKind::Io, 0, 1, 0);
+    }
+    let converted = match from {
+        InputFormat::Xlsx => convert_xlsx_bytes("stdin", &input, profile),
+        InputFormat::Csv => {
+            let detected = csv_text("stdin", &input)
+                .map(dialect::sniff)
+                .unwrap_or_default();
+            let dialect = dialect_override.apply(detected);
+            eprintln!("CSV list: {}", dialect.text());
+            convert_csv_bytes("stdin", &input, profile, &dialect)
+        }
+    };
+    let mut export = match converted {
+        Ok(export) => export,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return finish(e.exit_kind(), 0, 1, 0);
+        }
+    };
+    export.summary.run = run;
+    if let Err(e) = check_expected(
+        export.summary.rows,
+        export.summary.run.expected_contacts,
+        profile.expected_tolerance_percent,
+    ) {
+        eprintln!(
+            "error: {}",
+            translate(export.summary.run.lang, &e.to_string())
+        );
+        return finish(e.exit_kind(), 0, 1, 0);
+    }
+
+    let output = match to {
+        OutputFormat::Csv => &export.csv,
+    };
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = stdout.write_all(output).and_then(|()| stdout.flush()) {
+        eprintln!("error: cannot write stdout: {}", e);
+        return finish(ExitKind::Io, 0, 1, 0);
+    }
+    if let Some(path) = report_file {
+        let json = serde_json::to_string_pretty(&export.summary.to_json())
+            .map_err(io::Error::other)
+            .and_then(|json| fs::write(path, json));
+        if let Err(e) = json {
+            eprintln!("error: cannot write {}: {}", path.display(), e);
+            return finish(ExitKind::Io, 0, 1, 0);
+        }
+    }
+    eprintln!(
+        "{}{}",
+        trf(export.summary.run.lang, "{} rows.", &[&export.summary.rows]),
+        export.summary.warning_text()
+    );
+    finish(ExitKind::Success, 1, 0, export.summary.rows)
+}
+
+fn run_schedule(profiles: &[Profile], profile_arg: Option<&str>) -> ExitCode {
+    let Some(config) = Settings::load().schedule else {
+        eprintln!("error: no [schedule] in settings.toml");
+        return fail(ExitKind::Validation);
+    };
+    let cron = match Cron::parse(&config.cron) {
+        Ok(cron) => cron,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return fail(ExitKind::Validation);
+        }
+    };
+    let profile = match find_profile(profiles, profile_arg.or(config.profile.as_deref())) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return fail(ExitKind::Validation);
+        }
+    };
+    if let Some(next) = schedule::next_run_text(&cron) {
+        println!("Schedule \"{}\": next run {}", config.cron, next);
+    }
+    schedule::run_forever(&config, &cron, &profile, |outcome| {
+        println!("{}", outcome.text())
+    });
+    eprintln!("error: the cron expression never matches");
+    fail(ExitKind::Validation)
+}
+
+fn main() -> ExitCode {
+    // clap exits with 2 on a usage error, which here means missing columns
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return if e.use_stderr() {
+                fail(ExitKind::Validation)
+            } else {
+                // --help, --version
+                ExitCode::SUCCESS
+            };
+        }
+    };
+
+    if let Some(shell) = cli.completions {
+        clap_complete::generate(
+            shell,
+            &mut Cli::command(),
+            "be-alert-convert",
+            &mut io::stdout(),
+        );
+        return ExitCode::SUCCESS;
+    }
+    if cli.man {
+        if let Err(e) = clap_mangen::Man::new(Cli::command()).render(&mut io::stdout()) {
+            eprintln!("error: {}", e);
+            return ExitCode::from(ExitKind::Io.code());
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let lang = cli.lang.unwrap_or_else(Lang::from_env);
+    if cli.verify {
+        return run_verify(&cli.inputs, lang);
+    }
+
+    let (profiles, problems) = profile::load_profiles();
+    for problem in &problems {
+        eprintln!("warning: skipped profile {}", problem);
+    }
+    if cli.schedule {
+        return run_schedule(&profiles, cli.profile.as_deref());
+    }
+    if cli.peek {
+        return match find_profile(&profiles, cli.profile.as_deref()) {
+            Ok(profile) => run_peek(&cli.inputs, &profile, lang),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                fail(ExitKind::Validation)
+            }
+        };
+    }
+    let os = RunInfo::from_os();
+    let run = RunInfo {
+        operator: cli.operator.unwrap_or(os.operator),
+        remark: cli.remark,
+        expected_contacts: cli.expected,
+        lang,
+    };
+    if let Some(from) = cli.from {
+        let profile = match find_profile(&profiles, cli.profile.as_deref()) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                return fail(ExitKind::Validation);
+            }
+        };
+        let to = cli.to.unwrap_or(OutputFormat::Csv);
+        let dialect_override = DialectOverride {
+            delimiter: cli.delimiter,
+            quote: cli.quote,
+            no_header: cli.no_header,
+        };
+        return run_pipe(
+            from,
+            to,
+            &profile,
+            run,
+            cli.report_file.as_deref(),
+            &dialect_override,
+        );
+    }
+    // Required by clap unless --completions / --man / --schedule / --from is given
+    let Some(out_dir) = cli.out_dir else {
+        return fail(ExitKind::Validation);
+    };
+    if cli.to_xlsx {
+        return run_to_xlsx(&cli.inputs, &out_dir, lang);
+    }
+    let profile = match find_profile(&profiles, cli.profile.as_deref()) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return fail(ExitKind::Validation);
+        }
+    };
+
+    let inputs = match batch::expand_inputs(&cli.inputs) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("error: invalid pattern: {}", e);
+            return fail(ExitKind::Validation);
+        }
+    };
+    if inputs.is_empty() {
+        eprintln!(
+            "{}",
+            trf(lang, "error: no files match {}", &[&cli.inputs.join(" ")])
+        );
+        return fail(ExitKind::Io);
+    }
+    if run.expected_contacts.is_some() && inputs.len() > 1 {
+        eprintln!("error: --expected is the count of one register export; give one input file");
+        return fail(ExitKind::Validation);
+    }
+
+    let limits = BatchLimits {
+        jobs: cli.jobs.into(),
+        timeout: cli.timeout.map(Duration::from_secs),
+        ..Default::default()
+    };
+    let show = |result: &batch::FileResult| match &result.error {
+        None => println!(
+            "ok    {} -> {} ({} rows)",
+            result.input.display(),
+            result.output.display(),
+            result.rows
+        ),
+        Some(e) => println!("FAIL  {}: {}", result.input.display(), translate(lang, e)),
+    };
+    let report = if cli.staged {
+        match batch::run_staged(&inputs, &out_dir, &profile, &run, &limits, show) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!(
+                    "error: staged files in {} could not be moved or removed: {}",
+                    batch::staging_dir(&out_dir).display(),
+                    e
+                );
+                return fail(ExitKind::Io);
+            }
+        }
+    } else {
+        batch::run(&inputs, &out_dir, &profile, &run, &limits, show)
+    };
+    if report.committed == Some(false) {
+        println!(
+            "no files moved to {}: {} of {} failed",
+            out_dir.display(),
+            report.failed,
+            report.files.len()
+        );
+    }
+
+    let json_path = cli
+        .json_report
+        .unwrap_or_else(|| out_dir.join("batch_report.json"));
+    if let Err(e) = batch::write_json(&json_path, &report) {
+        eprintln!("error: cannot write {}: {}", json_path.display(), e);
+        return finish(ExitKind::Io, report.converted, report.failed, report.rows);
+    }
+    if cli.zip && report.committed != Some(false) {
+        let files: Vec<PathBuf> = report
+            .files
+            .iter()
+            .filter(|file| file.error.is_none())
+            .flat_map(|file| std::iter::once(file.output.clone()).chain(file.report.clone()))
+            .chain([json_path.clone()])
+            .collect();
+        let zip = package::package_path(&out_dir, "batch", Local::now());
+        if let Err(e) = package::write_package(&zip, &out_dir, &files, &run) {
+            eprintln!("error: {}", e);
+            return finish(ExitKind::Io, report.converted, report.failed, report.rows);
+        }
+        println!("packaged {} files in {}", files.len(), zip.display());
+    }
+    println!(
+        "{}",
+        trf(
+            lang,
+            "{} converted, {} failed, {} rows. Report: {}",
+            &[
+                &report.converted,
+                &report.failed,
+                &report.rows,
+                &json_path.display()
+            ]
+        )
+    );
+
+    let worst = report
+        .files
+        .iter()
+        .filter_map(|file| file.error_kind)
+        .max()
+        .unwrap_or(ExitKind::Success);
+    finish(worst, report.converted, report.failed, report.rows)
+}
@@ -0,0 +1,60 @@
+// src/clipboard.rs
+// "Paste from Excel"
+// ------------------
+//
+// Rows copied from an open workbook land on the clipboard as tab-separated
+// text (cells with tabs/newlines quoted). They are parsed into the same cell
+// type calamine produces, so the pasted rows go through the normal header
+// detection and conversion. The first copied row must be the header row.
+
+use crate::error::{ConvertError, Result};
+use calamine::Data;
+
+/// Name used for the pasted data in messages and the report.
+pub const SOURCE_NAME: &str = "clipboard";
+
+pub fn parse_tsv(text: &str) -> Vec<Vec<Data>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    reader
+        .records()
+        .filter_map(|r| r.ok())
+        .map(|record| {
+            record
+                .iter()
+                .map(|cell| {
+                    if cell.is_empty() {
+                        Data::Empty
+                    } else {
+                        Data::String(cell.to_string())
+                    }
+                })
+                .collect::<Vec<Data>>()
+        })
+        .filter(|row| row.iter().any(|c| *c != Data::Empty))
+        .collect()
+}
+
+/// Pasted rows from the system clipboard.
+pub fn read_rows() -> Result<Vec<Vec<Data>>> {
+    let unsupported = |reason: String| ConvertError::UnsupportedFormat {
+        path: SOURCE_NAME.into(),
+        reason,
+    };
+    let text = arboard::Clipboard::new()
+        .and_then(|mut c| c.get_text())
+        .map_err(|e| unsupported(e.to_string()))?;
+
+    let rows = parse_tsv(&text);
+    if rows.is_empty() {
+        return Err(unsupported(
+            "no rows copied (select the header row and the contacts in Excel, then copy)"
+                .to_string(),
+        ));
+    }
+    Ok(rows)
+}
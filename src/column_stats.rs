@@ -0,0 +1,122 @@
+// src/column_stats.rs
+// Column statistics ("Profile data")
+// ----------------------------------
+//
+// Quick look at what is in each input column before mapping it: how many
+// cells are filled, how many distinct values, shortest/longest value and the
+// ten most frequent values. Columns that are mostly empty are highlighted.
+
+use crate::convert::cell_to_string;
+use crate::report::escape;
+use calamine::Data;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const TOP_VALUES: usize = 10;
+
+pub struct ColumnStats {
+    pub name: String,
+    pub non_empty: usize,
+    pub distinct: usize,
+    /// Length in characters of the shortest / longest non-empty value.
+    pub min_len: usize,
+    pub max_len: usize,
+    /// Most frequent values with their count, most frequent first.
+    pub top: Vec<(String, usize)>,
+}
+
+/// Statistics per header column and the number of data rows.
+/// `rows` is header + data rows.
+pub fn column_stats<'a>(mut rows: impl Iterator<Item = &'a [Data]>) -> (Vec<ColumnStats>, usize) {
+    let header: Vec<String> = rows
+        .next()
+        .map(|h| {
+            h.iter()
+                .map(|c| cell_to_string(c).trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut counts: Vec<HashMap<String, usize>> = vec![HashMap::new(); header.len()];
+    let mut data_rows = 0;
+    for row in rows {
+        data_rows += 1;
+        for (i, cell) in row.iter().enumerate().take(header.len()) {
+            let value = cell_to_string(cell).trim().to_string();
+            if !value.is_empty() {
+                *counts[i].entry(value).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let stats = header
+        .into_iter()
+        .zip(counts)
+        .enumerate()
+        .map(|(i, (name, values))| {
+            let lengths = values.keys().map(|v| v.chars().count());
+            let mut top: Vec<(String, usize)> =
+                values.iter().map(|(v, n)| (v.clone(), *n)).collect();
+            // Most frequent first, ties alphabetically so the report is stable
+            top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            top.truncate(TOP_VALUES);
+            ColumnStats {
+                name: if name.is_empty() {
+                    format!("(column {})", i + 1)
+                } else {
+                    name
+                },
+                non_empty: values.values().sum(),
+                distinct: values.len(),
+                min_len: lengths.clone().min().unwrap_or(0),
+                max_len: lengths.max().unwrap_or(0),
+                top,
+            }
+        })
+        .collect();
+    (stats, data_rows)
+}
+
+pub fn write_html(path: &Path, input: &str, stats: &[ColumnStats], rows: usize) -> io::Result<()> {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\n");
+    html.push_str("<title>Column profile</title>\n");
+    html.push_str(
+        "<style>body{font-family:sans-serif}table{border-collapse:collapse}\
+         td,th{border:1px solid #ccc;padding:2px 6px;vertical-align:top}.sparse{background:#fde2e2}</style>\n",
+    );
+    html.push_str("</head><body>\n<h1>Column profile</h1>\n");
+    html.push_str(&format!(
+        "<p>Input: {}<br>Data rows: {}</p>\n",
+        escape(input),
+        rows
+    ));
+    html.push_str(
+        "<table>\n<tr><th>Column</th><th>Filled</th><th>Distinct</th><th>Min length</th><th>Max length</th><th>Top values</th></tr>\n",
+    );
+    for c in stats {
+        let top: Vec<String> = c
+            .top
+            .iter()
+            .map(|(v, n)| format!("{} ({})", escape(v), n))
+            .collect();
+        html.push_str(&format!(
+            "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            // Less than half filled
+            if c.non_empty * 2 < rows {
+                " class=\"sparse\""
+            } else {
+                ""
+            },
+            escape(&c.name),
+            c.non_empty,
+            c.distinct,
+            c.min_len,
+            c.max_len,
+            top.join("<br>")
+        ));
+    }
+    html.push_str("</table>\n</body></html>\n");
+    fs::write(path, html)
+}
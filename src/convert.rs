@@ -0,0 +1,2744 @@
+// src/convert.rs
+// XLSX rows -> BE-Alert BIN CSV
+// -----------------------------
+//
+// The conversion itself (column checks, sorting, writing the CSV and report),
+// shared by the GUI and the be-alert-convert command line. The per-row work
+// (output record, phone normalization, ...) is done by pipeline.rs.
+
+use crate::dedup::{self, DuplicateAction, DuplicateGroup, NumberIndex, Record};
+use crate::dialect::{self, CsvDialect};
+use crate::error::{ConvertError, Result, RowErrorKind};
+use crate::gemeente;
+use crate::headerless;
+#[cfg(feature = "native")]
+use crate::history::{self, History};
+use crate::lock::OutputLock;
+use crate::pipeline::{field_problem, Pipeline};
+use crate::profile::{
+    HeaderRows, LineEnding, MissingStreetPolicy, Profile, RijksregisterPolicy, RowHash, SortOrder,
+    StrictMode,
+};
+use crate::report::{self, CellWarning, ConvertSummary, EmptyColumn, ProfileStamp, RunInfo};
+use crate::rijksregister;
+use crate::template::{self, OutputTemplate};
+use calamine::{Cell, Data, DataRef, Range, Reader, Xlsx};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use csv::{Terminator, Writer, WriterBuilder};
+use memmap2::Mmap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// Share of filled "Mobiel nummer" cells with an '@' above which the phone
+/// and e-mail columns are taken to be swapped (see check_swapped).
+const SWAPPED_PHONE_SHARE: f64 = 0.3;
+
+/// Share of empty cells from which a required column counts as empty (see
+/// empty_columns).
+const EMPTY_COLUMN_SHARE: f64 = 0.95;
+
+/// Output buffer of the CSV writer; large enough that a million-row export
+/// does not spend its time in small writes.
+const CSV_BUFFER: usize = 1 << 20;
+
+pub const REQUIRED_COLUMNS: [&str; 6] = [
+    "Voornaam",
+    "Naam",
+    "Straat",
+    "Huisnummer",
+    "Mobiel nummer",
+    "E-mailadres",
+];
+
+// Optional input columns used to split a regional file over several profiles
+pub const POSTCODE_COLUMN: &str = "Postcode";
+pub const GEMEENTE_COLUMN: &str = "Gemeente";
+
+// Optional input column for sources that already aggregate per address
+pub const INWONERS_COLUMN: &str = "Aantal inwoners";
+
+// Optional input column for the landline channel (see profile `phone_channels`)
+pub const LANDLINE_COLUMN: &str = "Vast nummer";
+
+// Optional input column for the age filter (profile `min_age`)
+pub const GEBOORTEDATUM_COLUMN: &str = "Geboortedatum";
+
+// Optional input column with street and house number together ("Dorpstraat 12 bus 3"),
+// instead of Straat and Huisnummer, as in several population register exports
+pub const ADDRESS_COLUMN: &str = "Adres";
+
+// Optional input column with a free-text note on the contact ("verhuisd per 1/6"):
+// shown in the report and kept in the history, never written to the CSV
+pub const OPMERKING_COLUMN: &str = "Opmerking";
+
+// Optional input column with the resident's channel choice (J/N): a mobile
+// number marked for SMS goes to the SMS field instead of Tel/Ref. (see
+// profile `sms_preference`)
+pub const SMS_VOORKEUR_COLUMN: &str = "SMS voorkeur";
+
+/// Input columns the conversion reads when present.
+pub const OPTIONAL_COLUMNS: [&str; 7] = [
+    POSTCODE_COLUMN,
+    GEMEENTE_COLUMN,
+    INWONERS_COLUMN,
+    LANDLINE_COLUMN,
+    ADDRESS_COLUMN,
+    OPMERKING_COLUMN,
+    SMS_VOORKEUR_COLUMN,
+];
+
+/// Data row with its 1-based Excel row number (header = row 1).
+pub type SheetRow<'a> = (usize, &'a [Data]);
+
+/// Numbers from this size on have lost digits in Excel (15 significant
+/// digits), e.g. a 16-digit card number typed in a number cell.
+const EXACT_NUMBER_LIMIT: f64 = 1e15;
+
+/// Text of a cell as written to the CSV. Whole numbers without decimals,
+/// dates as dd/mm/yyyy (with the time when there is one), durations as
+/// h:mm:ss; error cells are empty. See `is_lossy` for what cannot be shown
+/// exactly.
+pub fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::String(s) => s.clone(),
+        Data::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => (*f as i64).to_string(),
+        // Too large for i64 (`as` would saturate)
+        Data::Float(f) if f.fract() == 0.0 => format!("{:.0}", f),
+        Data::Float(f) => f.to_string(),
+        Data::Int(i) => i.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(dt) => {
+            if dt.is_duration() {
+                duration_text((dt.as_f64() * 86_400.0).round() as i64)
+            } else {
+                match dt.as_datetime() {
+                    Some(d) => datetime_text(d),
+                    None => dt.as_f64().to_string(),
+                }
+            }
+        }
+        // Written by some tools (and ODS) instead of a serial number; kept
+        // as it is when it cannot be read
+        Data::DateTimeIso(s) => iso_datetime(s).unwrap_or_else(|| s.clone()),
+        Data::DurationIso(s) => iso_duration(s).map_or_else(|| s.clone(), duration_text),
+        // #N/A, #VALUE!, ... carry no data; reported as cell warnings
+        Data::Error(_) => String::new(),
+        Data::Empty => String::new(),
+    }
+}
+
+/// Whether cell_to_string cannot show the cell's value exactly: numbers
+/// Excel already cut to 15 digits, or not finite; dates and durations that
+/// could not be read (written as a serial number or the raw text). Errors
+/// are counted on their own.
+pub(crate) fn is_lossy(cell: &Data) -> bool {
+    match cell {
+        Data::Float(f) => !f.is_finite() || f.abs() >= EXACT_NUMBER_LIMIT,
+        Data::DateTime(dt) => !dt.is_duration() && dt.as_datetime().is_none(),
+        Data::DateTimeIso(s) => iso_datetime(s).is_none(),
+        Data::DurationIso(s) => iso_duration(s).is_none(),
+        Data::String(_) | Data::Int(_) | Data::Bool(_) | Data::Error(_) | Data::Empty => false,
+    }
+}
+
+/// dd/mm/yyyy, with hh:mm for a time after midnight (as before ISO dates were
+/// read: seconds are not written).
+fn datetime_text(d: NaiveDateTime) -> String {
+    if d.time() == NaiveTime::MIN {
+        d.format("%d/%m/%Y").to_string()
+    } else {
+        d.format("%d/%m/%Y %H:%M").to_string()
+    }
+}
+
+/// Seconds as h:mm:ss.
+fn duration_text(secs: i64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// "2025-03-01T14:30:00", "2025-03-01" or "14:30:00" in our date format.
+fn iso_datetime(s: &str) -> Option<String> {
+    let s = s.trim();
+    if let Ok(d) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(datetime_text(d));
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(d.format("%d/%m/%Y").to_string());
+    }
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .ok()
+        .map(|t| t.format("%H:%M:%S").to_string())
+}
+
+/// Seconds of an ISO 8601 duration like "PT1H30M" or "P1DT2H15M30S"
+/// (days and the time part; years and months have no fixed length).
+fn iso_duration(s: &str) -> Option<i64> {
+    let rest = s.trim().strip_prefix('P')?;
+    let (days, time) = rest.split_once('T').unwrap_or((rest, ""));
+    let mut secs = 0.0;
+    let mut parse = |part: &str, units: &[(char, f64)]| -> Option<()> {
+        let mut number = String::new();
+        for c in part.chars() {
+            match units.iter().find(|(unit, _)| *unit == c) {
+                Some((_, factor)) => {
+                    secs += number.parse::<f64>().ok()? * factor;
+                    number.clear();
+                }
+                None => number.push(c),
+            }
+        }
+        number.is_empty().then_some(())
+    };
+    parse(days, &[('W', 604_800.0), ('D', 86_400.0)])?;
+    parse(time, &[('H', 3600.0), ('M', 60.0), ('S', 1.0)])?;
+    (rest != "T" && !rest.is_empty()).then_some(secs.round() as i64)
+}
+
+pub(crate) fn get(cols: &HashMap<String, usize>, row: &[Data], name: &str) -> String {
+    cols.get(name)
+        .and_then(|&i| row.get(i))
+        .map(cell_to_string)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Value of the `slot`-th column in a profile column list (empty when unmapped).
+pub(crate) fn get_mapped(
+    cols: &HashMap<String, usize>,
+    row: &[Data],
+    columns: &[String],
+    slot: usize,
+) -> String {
+    columns
+        .get(slot)
+        .map(|name| get(cols, row, name))
+        .unwrap_or_default()
+}
+
+/// Keep only leading digits; stop at first non-digit.
+/// Examples:
+/// - "11A" -> "11"
+/// - "12 Bus 3" -> "12"
+pub fn extract_house_number(input: &str) -> String {
+    let mut digits = String::new();
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else {
+            break;
+        }
+    }
+    digits
+}
+
+/// "Dorpstraat 12 bus 3" -> ("Dorpstraat", "12 bus 3"): the house number
+/// starts at the first word after the street that starts with a digit.
+pub fn split_address(adres: &str) -> (String, String) {
+    let adres = adres.trim();
+    let number_at = adres
+        .char_indices()
+        .skip(1)
+        .find(|&(i, c)| c.is_ascii_digit() && adres[..i].ends_with(' '))
+        .map(|(i, _)| i);
+    match number_at {
+        Some(i) => (adres[..i].trim_end().to_string(), adres[i..].to_string()),
+        None => (adres.to_string(), String::new()),
+    }
+}
+
+/// Straat and Huisnummer of a row: from their own columns, or split from the
+/// combined "Adres" column when the source has no separate ones.
+pub(crate) fn street_and_number(cols: &HashMap<String, usize>, row: &[Data]) -> (String, String) {
+    if cols.contains_key("Straat") && cols.contains_key("Huisnummer") {
+        (get(cols, row, "Straat"), get(cols, row, "Huisnummer"))
+    } else {
+        split_address(&get(cols, row, ADDRESS_COLUMN))
+    }
+}
+
+/// Reproducible order (profile `reproducible`): the output records sorted on
+/// their fields from Tel/Ref. on, so the order of the source rows does not matter.
+fn sort_reproducible(records: &mut [Record]) {
+    records.sort_by(|(_, a), (_, b)| a.cmp(b));
+}
+
+/// Stable sort of the data rows; rows with equal keys keep their input order.
+fn sort_rows(cols: &HashMap<String, usize>, rows: &mut [SheetRow], order: SortOrder) {
+    match order {
+        SortOrder::Input => {}
+        SortOrder::Address => rows.sort_by_cached_key(|&(_, row)| {
+            let (straat, huisnr) = street_and_number(cols, row);
+            (
+                straat.to_lowercase(),
+                extract_house_number(&huisnr)
+                    .parse::<u64>()
+                    .unwrap_or(u64::MAX),
+                huisnr.to_lowercase(),
+            )
+        }),
+        SortOrder::Name => rows.sort_by_cached_key(|&(_, row)| {
+            (
+                get(cols, row, "Naam").to_lowercase(),
+                get(cols, row, "Voornaam").to_lowercase(),
+            )
+        }),
+    }
+}
+
+/// Phone cell without annotations in brackets: "0475 12 34 56 (dochter)" ->
+/// "0475 12 34 56 ". An unclosed bracket drops the rest of the cell.
+pub(crate) fn strip_annotations(input: &str) -> String {
+    let mut depth = 0usize;
+    input
+        .chars()
+        .filter(|&c| match c {
+            '(' | '[' | '{' => {
+                depth += 1;
+                false
+            }
+            ')' | ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                false
+            }
+            _ => depth == 0,
+        })
+        .collect()
+}
+
+/// Belgium-style normalization:
+/// - "+32..." -> "0032..."
+/// - "0032..." stays, so normalizing twice changes nothing
+/// - "0..."   -> "0032..." (drop leading 0)
+/// - drops annotations in brackets ("(dochter)"), then all punctuation and
+///   grouping ("0475/12.34.56", "0475-123.456"); only a leading '+' is kept
+pub fn normalize_be_phone(input: &str) -> String {
+    let cleaned = strip_annotations(input);
+    let cleaned = cleaned.trim();
+    let plus = cleaned.starts_with('+');
+    let mut s: String = cleaned.chars().filter(char::is_ascii_digit).collect();
+    if plus && !s.is_empty() {
+        s.insert(0, '+');
+    }
+
+    if s.is_empty() {
+        return String::new();
+    }
+
+    if let Some(rest) = s.strip_prefix("+32") {
+        return format!("0032{}", rest);
+    }
+
+    if s.starts_with("0032") {
+        return s;
+    }
+
+    if s.starts_with('0') {
+        s.remove(0);
+        return format!("0032{}", s);
+    }
+
+    if s.starts_with('4') {
+        return format!("0032{}", s);
+    }
+
+    s
+}
+
+/// E-mail address as pasted into registers, often from Outlook:
+/// - "mailto:jan@telenet.be" -> "jan@telenet.be"
+/// - "Jan Peeters <jan@telenet.be>", "<jan@telenet.be>" -> "jan@telenet.be"
+/// - quotes around the address are dropped
+/// - lower case ("Jan.Peeters@Telenet.BE" -> "jan.peeters@telenet.be")
+pub fn normalize_email(input: &str) -> String {
+    let mut email = input.trim();
+    if let Some((_, address)) = email.strip_suffix('>').and_then(|e| e.rsplit_once('<')) {
+        email = address;
+    }
+    loop {
+        let trimmed = email
+            .trim()
+            .trim_matches(|c| matches!(c, '"' | '\'' | '<' | '>'));
+        let stripped = match trimmed.get(..7) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("mailto:") => &trimmed[7..],
+            _ => trimmed,
+        };
+        if stripped == email {
+            break;
+        }
+        email = stripped;
+    }
+    email.to_lowercase()
+}
+
+/// Workbook extensions the convertor reads. Macro-enabled workbooks (.xlsm)
+/// are the same format plus a macro part, which is never read or run.
+pub const EXCEL_EXTENSIONS: [&str; 2] = ["xlsx", "xlsm"];
+
+pub fn is_macro_workbook(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xlsm"))
+}
+
+/// Warn that an .xlsm is read as data only: formulas and values are as last
+/// saved by Excel, whatever the macros would do.
+fn note_macros(input_xlsx: &str, summary: &mut ConvertSummary) {
+    if is_macro_workbook(input_xlsx) {
+        summary.warnings.push(format!(
+            "{}: macros are not run, values are as last saved in Excel",
+            file_stem(input_xlsx)
+        ));
+    }
+}
+
+/// Name the column read for each header name chosen in the profile's
+/// `[duplicate_columns]`, so the report shows which "Naam" was exported.
+fn note_duplicate_columns(
+    input_xlsx: &str,
+    header: Option<&[Data]>,
+    profile: &Profile,
+    summary: &mut ConvertSummary,
+) {
+    for (name, columns) in duplicate_headers(header.unwrap_or_default()) {
+        if let Some(&n) = profile.duplicate_columns.get(&name) {
+            summary.warnings.push(format!(
+                "{}: column \"{}\" appears {} times, read from column {}",
+                file_stem(input_xlsx),
+                name,
+                columns.len(),
+                report::column_name(columns[n - 1])
+            ));
+        }
+    }
+}
+
+/// Bytes of an input workbook. The file is memory-mapped, so the zip reader
+/// seeks around a 500 MB regional extract in place instead of through small
+/// buffered reads; read into memory where mapping is not possible.
+enum InputBytes {
+    Mapped(Mmap),
+    Read(Vec<u8>),
+}
+
+impl AsRef<[u8]> for InputBytes {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            InputBytes::Mapped(map) => map,
+            InputBytes::Read(bytes) => bytes,
+        }
+    }
+}
+
+fn read_input(input_xlsx: &str) -> Result<InputBytes> {
+    let file = File::open(input_xlsx).map_err(|e| ConvertError::io_at(input_xlsx, e))?;
+    // Safety: the map is only read while converting, and a workbook open in
+    // Excel cannot be opened at all (FileLocked). Another program rewriting
+    // the file meanwhile would be a corrupt read, as with buffered reading.
+    match unsafe { Mmap::map(&file) } {
+        Ok(map) => Ok(InputBytes::Mapped(map)),
+        Err(_) => fs::read(input_xlsx)
+            .map(InputBytes::Read)
+            .map_err(|e| ConvertError::io_at(input_xlsx, e)),
+    }
+}
+
+fn open_workbook<'a>(input_xlsx: &str, bytes: &'a InputBytes) -> Result<Xlsx<Cursor<&'a [u8]>>> {
+    Xlsx::new(Cursor::new(bytes.as_ref())).map_err(|e| ConvertError::from_xlsx(input_xlsx, e))
+}
+
+pub fn open_first_sheet(input_xlsx: &str) -> Result<Range<Data>> {
+    let bytes = read_input(input_xlsx)?;
+    first_sheet(input_xlsx, &mut open_workbook(input_xlsx, &bytes)?)
+}
+
+/// First sheet for a conversion: its values plus a cell warning for each
+/// column with formulas without a saved result (see formula_warnings).
+fn read_first_sheet(
+    input_xlsx: &str,
+    profile: &Profile,
+) -> Result<(Range<Data>, Vec<FormulaColumn>)> {
+    let bytes = read_input(input_xlsx)?;
+    let mut workbook = open_workbook(input_xlsx, &bytes)?;
+    sheet_with_formulas(input_xlsx, &mut workbook, profile)
+}
+
+/// The first sheet's values (as first_sheet) and its formula warnings. The
+/// values are read cell by cell, as calamine's worksheet_range does, noting
+/// the cells without a value: a formula saved without its result is one of
+/// them (as is a formatted empty cell). The formulas are only read when such
+/// a cell is in a data row.
+fn sheet_with_formulas<RS: Read + Seek>(
+    input_xlsx: &str,
+    workbook: &mut Xlsx<RS>,
+    profile: &Profile,
+) -> Result<(Range<Data>, Vec<FormulaColumn>)> {
+    let name =
+        workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or_else(|| ConvertError::UnsupportedFormat {
+                path: input_xlsx.into(),
+                reason: "no sheet found in XLSX".to_string(),
+            })?;
+    let mut cells = Vec::new();
+    let mut empty = Vec::new();
+    let mut reader = workbook
+        .worksheet_cells_reader(&name)
+        .map_err(|e| ConvertError::from_xlsx(input_xlsx, e))?;
+    while let Some(cell) = reader
+        .next_cell()
+        .map_err(|e| ConvertError::from_xlsx(input_xlsx, e))?
+    {
+        match cell.get_value() {
+            DataRef::Empty => empty.push(cell.get_position()),
+            value => cells.push(Cell::new(cell.get_position(), Data::from(value.clone()))),
+        }
+    }
+    drop(reader);
+    let range = from_column_a(Range::from_sparse(cells));
+    let warnings = formula_warnings(workbook, &name, &range, &empty, profile);
+    Ok((range, warnings))
+}
+
+fn first_sheet<RS: Read + Seek>(input_xlsx: &str, workbook: &mut Xlsx<RS>) -> Result<Range<Data>> {
+    workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| ConvertError::UnsupportedFormat {
+            path: input_xlsx.into(),
+            reason: "no sheet found in XLSX".to_string(),
+        })?
+        .map(from_column_a)
+        .map_err(|e| ConvertError::from_xlsx(input_xlsx, e))
+}
+
+/// The sheet's cells from column A. calamine starts a range at its first
+/// filled column; moved back to A, a column index is the Excel column, so
+/// the report's "C12" is cell C12 also when columns A and B are empty.
+fn from_column_a(range: Range<Data>) -> Range<Data> {
+    let (Some((top, left)), Some((bottom, right))) = (range.start(), range.end()) else {
+        return range;
+    };
+    if left == 0 {
+        return range;
+    }
+    let mut moved = Range::new((top, 0), (bottom, right));
+    for (row, column, value) in range.used_cells() {
+        moved.set_value((top + row as u32, left + column as u32), value.clone());
+    }
+    moved
+}
+
+/// 0-based sheet row of the range's first row: calamine starts a range at
+/// its first filled row, the row numbers in the report count from row 1.
+pub fn sheet_top(range: &Range<Data>) -> usize {
+    range.start().map_or(0, |(row, _)| row as usize)
+}
+
+/// Formulas without a saved result in one column: the warning for the first
+/// cell (its message naming the others) and the number of cells.
+type FormulaColumn = (CellWarning, usize);
+
+/// Formula cells are read as the result Excel saved with them (e.g. the
+/// number behind `=TEXT(A2;"0")`). Files written by other tools sometimes
+/// save the formula only; those cells read as empty and are listed per
+/// column. Only data rows count, with the header and boilerplate rows of
+/// `profile`; `empty` are the sheet positions of cells read without a value.
+fn formula_warnings<RS: Read + Seek>(
+    workbook: &mut Xlsx<RS>,
+    sheet: &str,
+    range: &Range<Data>,
+    empty: &[(u32, u32)],
+    profile: &Profile,
+) -> Vec<FormulaColumn> {
+    let mut rows = skip_boilerplate(range.rows(), profile);
+    let assumed = assumed_header(profile);
+    let (header, first_row) = split_header(
+        &mut rows,
+        &assumed,
+        profile.skip_leading_rows,
+        sheet_top(range),
+    );
+    let header = header.unwrap_or_default();
+    // 0-based sheet rows of the data, as split_header counts them
+    let data = first_row - 1..first_row - 1 + rows.len();
+    if !empty.iter().any(|&(r, _)| data.contains(&(r as usize))) {
+        return Vec::new();
+    }
+    let Ok(formulas) = workbook.worksheet_formula(sheet) else {
+        return Vec::new();
+    };
+
+    // Column -> (row, formula) of its cells, top to bottom
+    let mut columns: BTreeMap<usize, Vec<(usize, &str)>> = BTreeMap::new();
+    for &(r, c) in empty {
+        let formula = formulas.get_value((r, c)).map_or("", String::as_str);
+        if data.contains(&(r as usize)) && !formula.is_empty() {
+            columns
+                .entry(c as usize)
+                .or_default()
+                .push((r as usize + 1, formula));
+        }
+    }
+    columns
+        .into_iter()
+        .map(|(c, cells)| {
+            let (row, formula) = cells[0];
+            let (last, _) = cells[cells.len() - 1];
+            let message = if cells.len() == 1 {
+                format!("formula ={} has no saved result, exported empty", formula)
+            } else {
+                format!(
+                    "{} formulas in {}:{} (such as ={}) have no saved result, exported empty",
+                    cells.len(),
+                    report::cell_ref(row, Some(c)),
+                    report::cell_ref(last, Some(c)),
+                    formula
+                )
+            };
+            let warning = CellWarning {
+                row,
+                column: Some(c),
+                field: header
+                    .get(c)
+                    .map(cell_to_string)
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("column {}", c + 1)),
+                value: format!("={}", formula),
+                message,
+                fix: "open and save the file in Excel".to_string(),
+            };
+            (warning, cells.len())
+        })
+        .collect()
+}
+
+/// Count of formula-only cells per column in the summary warnings, one cell
+/// warning per column in the report.
+fn note_formulas(input_xlsx: &str, columns: Vec<FormulaColumn>, summary: &mut ConvertSummary) {
+    if columns.is_empty() {
+        return;
+    }
+    let counts: Vec<String> = columns
+        .iter()
+        .map(|(warning, count)| format!("{} {}", count, warning.field))
+        .collect();
+    summary.warnings.push(format!(
+        "{}: formula cells without a saved result (exported empty): {}",
+        file_stem(input_xlsx),
+        counts.join(", ")
+    ));
+    for (warning, _) in columns {
+        summary.warn_cell(warning);
+    }
+}
+
+/// The profile's `input_columns` as a header row, for lists without one.
+pub(crate) fn assumed_header(profile: &Profile) -> Option<Vec<Data>> {
+    (!profile.input_columns.is_empty()).then(|| {
+        profile
+            .input_columns
+            .iter()
+            .map(|name| Data::String(name.clone()))
+            .collect()
+    })
+}
+
+/// The sheet rows without the profile's `skip_leading_rows` and
+/// `skip_trailing_rows`: the fixed title and footer lines some exports put
+/// around the list, left out before the header row is looked for.
+pub fn skip_boilerplate<'a>(
+    rows: impl Iterator<Item = &'a [Data]>,
+    profile: &Profile,
+) -> std::vec::IntoIter<&'a [Data]> {
+    let mut rows: Vec<&[Data]> = rows.skip(profile.skip_leading_rows).collect();
+    rows.truncate(rows.len().saturating_sub(profile.skip_trailing_rows));
+    rows.into_iter()
+}
+
+/// Take the header off the sheet rows: the first row, or `assumed` (see
+/// assumed_header) when the list has none. Also returns the Excel row number
+/// of the first data row, counting the `skipped` leading rows and the empty
+/// rows above the range (`top`, see sheet_top).
+fn split_header<'a: 'b, 'b>(
+    rows: &mut impl Iterator<Item = &'a [Data]>,
+    assumed: &'b Option<Vec<Data>>,
+    skipped: usize,
+    top: usize,
+) -> (Option<&'b [Data]>, usize) {
+    match assumed {
+        Some(header) => (Some(header.as_slice()), top + skipped + 1),
+        None => (rows.next(), top + skipped + 2),
+    }
+}
+
+/// Header names that appear more than once, with their columns (0-based,
+/// left to right), in the order of the header row.
+pub fn duplicate_headers(header: &[Data]) -> Vec<(String, Vec<usize>)> {
+    let mut seen: Vec<(String, Vec<usize>)> = Vec::new();
+    for (i, cell) in header.iter().enumerate() {
+        let name = cell_to_string(cell).trim().to_string();
+        if name.is_empty() {
+            continue;
+        }
+        match seen.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, columns)) => columns.push(i),
+            None => seen.push((name, vec![i])),
+        }
+    }
+    seen.retain(|(_, columns)| columns.len() > 1);
+    seen
+}
+
+/// Header row -> column name -> index, checked against REQUIRED_COLUMNS.
+/// Columns renamed in the profile's `[columns]` table are also found under
+/// their expected name. An "Adres" column stands in for Straat and Huisnummer.
+/// A name that appears more than once is read from the column the profile's
+/// `[duplicate_columns]` chooses; a column the conversion reads may not be
+/// left to chance.
+pub fn header_columns(
+    input_xlsx: &str,
+    header: Option<&[Data]>,
+    profile: &Profile,
+) -> Result<HashMap<String, usize>> {
+    let header = header.ok_or_else(|| ConvertError::UnsupportedFormat {
+        path: input_xlsx.into(),
+        reason: "empty sheet (no header row)".to_string(),
+    })?;
+
+    let mut cols: HashMap<String, usize> = HashMap::new();
+    let mut found: Vec<String> = Vec::new();
+    for (i, cell) in header.iter().enumerate() {
+        let name = cell_to_string(cell).trim().to_string();
+        if !name.is_empty() {
+            found.push(name.clone());
+            cols.insert(name, i);
+        }
+    }
+
+    for (name, columns) in duplicate_headers(header) {
+        match profile.duplicate_columns.get(&name) {
+            Some(&n) if (1..=columns.len()).contains(&n) => {
+                cols.insert(name, columns[n - 1]);
+            }
+            Some(&n) => {
+                return Err(ConvertError::InvalidProfileValue {
+                    field: format!("duplicate_columns.{}", name),
+                    value: n.to_string(),
+                    expected: format!("1 to {}", columns.len()),
+                })
+            }
+            None if reads_column(profile, &name) => {
+                return Err(ConvertError::AmbiguousColumn { name, columns })
+            }
+            None => {}
+        }
+    }
+
+    for (expected, source) in &profile.columns {
+        if let Some(&i) = cols.get(source) {
+            cols.insert(expected.clone(), i);
+        }
+    }
+
+    let combined_address = cols.contains_key(ADDRESS_COLUMN);
+    for required in REQUIRED_COLUMNS {
+        if combined_address && (required == "Straat" || required == "Huisnummer") {
+            continue;
+        }
+        if !cols.contains_key(required) {
+            return Err(ConvertError::MissingColumn {
+                name: required.to_string(),
+                found,
+            });
+        }
+    }
+
+    Ok(cols)
+}
+
+/// Does the conversion read the header `name` (required, optional, mapped
+/// or named in `[columns]`)? A required name renamed in `[columns]` is read
+/// from the other header instead.
+fn reads_column(profile: &Profile, name: &str) -> bool {
+    let known = REQUIRED_COLUMNS.contains(&name)
+        || OPTIONAL_COLUMNS.contains(&name)
+        || (profile.uses_birth_date() && name == GEBOORTEDATUM_COLUMN);
+    (known && !profile.columns.contains_key(name))
+        || profile.mapped_columns().any(|c| c == name)
+        || profile.columns.values().any(|c| c == name)
+}
+
+fn missing_column(cols: &HashMap<String, usize>, name: &str) -> ConvertError {
+    let mut found: Vec<String> = cols.keys().cloned().collect();
+    found.sort();
+    ConvertError::MissingColumn {
+        name: name.to_string(),
+        found,
+    }
+}
+
+/// Columns mapped by the profile must exist too, otherwise they would silently export blanks.
+/// The same goes for the birth date when the profile filters or brackets on age.
+fn check_profile_columns(cols: &HashMap<String, usize>, profile: &Profile) -> Result<()> {
+    for name in profile.mapped_columns() {
+        if !cols.contains_key(name) {
+            return Err(missing_column(cols, name));
+        }
+    }
+    if profile.uses_birth_date() && !cols.contains_key(GEBOORTEDATUM_COLUMN) {
+        return Err(missing_column(cols, GEBOORTEDATUM_COLUMN));
+    }
+    Ok(())
+}
+
+/// Fixed values of the profile must be accepted by BE-Alert: a typo like
+/// `taal = "NLD"` blocks the export instead of the upload. Zone libre 3 can
+/// hold a mapped column or the row hash, not both (nor the group's Zone
+/// libre); the placeholder policy
+/// needs a placeholder street and a template must name known fields.
+fn check_fixed_values(profile: &Profile) -> Result<()> {
+    if profile.phone_channels.is_empty() {
+        // No channel would fill Tel/Ref.: every row would be rejected
+        return Err(ConvertError::InvalidProfileValue {
+            field: "phone_channels".to_string(),
+            value: "[]".to_string(),
+            expected: "at least one of \"mobile\" and \"landline\"".to_string(),
+        });
+    }
+    if profile.preset.is_some() {
+        // A preset has no fixed values of its own: never write Alken's
+        for (field, value) in [
+            ("postcode", &profile.postcode),
+            ("gemeente", &profile.gemeente),
+        ] {
+            if value.trim().is_empty() {
+                return Err(ConvertError::InvalidProfileValue {
+                    field: field.to_string(),
+                    value: String::new(),
+                    expected: "the gemeente's own value (a preset has none)".to_string(),
+                });
+            }
+        }
+    }
+    let fixed = [
+        ("Postcode", &profile.postcode),
+        ("Taal", &profile.taal),
+        ("Land", &profile.land),
+    ];
+    for (field, value) in fixed {
+        if let Some(expected) = field_problem(field, value) {
+            return Err(ConvertError::InvalidProfileValue {
+                field: field.to_string(),
+                value: value.clone(),
+                expected: expected.to_string(),
+            });
+        }
+    }
+    if profile.row_hash != RowHash::Off {
+        if let Some(column) = profile.zone_libre_columns.get(2) {
+            return Err(ConvertError::InvalidProfileValue {
+                field: "zone_libre_columns".to_string(),
+                value: column.clone(),
+                expected: "at most two columns, row_hash fills Zone libre 3".to_string(),
+            });
+        }
+    }
+    if profile.group_column.is_some() || !profile.group.is_empty() {
+        let zone = profile.group_zone;
+        let problem = if !(1..=3).contains(&zone) {
+            Some("1, 2 or 3")
+        } else if profile.zone_libre_columns.get(zone - 1).is_some() {
+            Some("a Zone libre not filled by zone_libre_columns")
+        } else if zone == 3 && profile.row_hash != RowHash::Off {
+            Some("1 or 2, row_hash fills Zone libre 3")
+        } else {
+            None
+        };
+        if let Some(expected) = problem {
+            return Err(ConvertError::InvalidProfileValue {
+                field: "group_zone".to_string(),
+                value: zone.to_string(),
+                expected: expected.to_string(),
+            });
+        }
+    }
+    if let Some(zone) = profile.age_bracket_zone {
+        let group_zone = (profile.group_column.is_some() || !profile.group.is_empty())
+            .then_some(profile.group_zone);
+        let problem = if !(1..=3).contains(&zone) {
+            Some("1, 2 or 3")
+        } else if profile.zone_libre_columns.get(zone - 1).is_some() {
+            Some("a Zone libre not filled by zone_libre_columns")
+        } else if group_zone == Some(zone) {
+            Some("a Zone libre other than group_zone")
+        } else if zone == 3 && profile.row_hash != RowHash::Off {
+            Some("1 or 2, row_hash fills Zone libre 3")
+        } else if profile.age_brackets.is_empty() {
+            Some("a zone with age_brackets to write, e.g. [65, 80]")
+        } else {
+            None
+        };
+        if let Some(expected) = problem {
+            return Err(ConvertError::InvalidProfileValue {
+                field: "age_bracket_zone".to_string(),
+                value: zone.to_string(),
+                expected: expected.to_string(),
+            });
+        }
+    }
+    if let Some(problem) = profile.template.as_ref().and_then(OutputTemplate::problem) {
+        return Err(ConvertError::InvalidProfileValue {
+            field: "template".to_string(),
+            value: problem,
+            expected: "a template made with \"CSV template\" (see template.rs)".to_string(),
+        });
+    }
+    if profile.missing_street == MissingStreetPolicy::Placeholder
+        && profile.street_placeholder.trim().is_empty()
+    {
+        return Err(ConvertError::InvalidProfileValue {
+            field: "street_placeholder".to_string(),
+            value: profile.street_placeholder.clone(),
+            expected: "a street name for missing_street = \"placeholder\"".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Strict mode: header columns that are neither required, mapped nor allowed.
+/// Blocks with an error, or adds a warning to the summary.
+fn check_strict(
+    cols: &HashMap<String, usize>,
+    profile: &Profile,
+    summary: &mut ConvertSummary,
+) -> Result<()> {
+    if profile.strict == StrictMode::Off {
+        return Ok(());
+    }
+
+    let mut unexpected: Vec<String> = cols
+        .keys()
+        .filter(|name| {
+            !REQUIRED_COLUMNS.contains(&name.as_str())
+                && !OPTIONAL_COLUMNS.contains(&name.as_str())
+                && (!profile.uses_birth_date() || name.as_str() != GEBOORTEDATUM_COLUMN)
+                && !profile.mapped_columns().any(|c| c == name.as_str())
+                && !profile.allowed_columns.contains(name)
+                && !profile.columns.contains_key(name.as_str())
+                && !profile.columns.values().any(|c| c == name.as_str())
+        })
+        .cloned()
+        .collect();
+    if unexpected.is_empty() {
+        return Ok(());
+    }
+    unexpected.sort();
+
+    if profile.strict == StrictMode::Block {
+        return Err(ConvertError::UnexpectedColumns { names: unexpected });
+    }
+    summary.warnings.push(format!(
+        "profile {}: unexpected columns {}",
+        profile.name,
+        unexpected.join(", ")
+    ));
+    Ok(())
+}
+
+/// GDPR guard: no cell, exported or not, may hold a rijksregisternummer.
+/// Refuses with the first offending row, or counts the cells that will be
+/// masked in the output.
+fn check_rijksregister(
+    cols: &HashMap<String, usize>,
+    rows: &[SheetRow],
+    profile: &Profile,
+    summary: &mut ConvertSummary,
+) -> Result<()> {
+    let mut masked = 0;
+    for &(row_nr, row) in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if !rijksregister::contains(&cell_to_string(cell)) {
+                continue;
+            }
+            if profile.rijksregister == RijksregisterPolicy::Mask {
+                masked += 1;
+                continue;
+            }
+            let field = cols
+                .iter()
+                .find(|&(_, &idx)| idx == i)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_else(|| format!("column {}", i + 1));
+            return Err(ConvertError::RowError {
+                row: row_nr,
+                column: i,
+                field,
+                kind: RowErrorKind::NationalRegisterNumber,
+            });
+        }
+    }
+    if masked > 0 {
+        summary.warnings.push(format!(
+            "{} cells look like a rijksregisternummer (GDPR); masked in the output",
+            masked
+        ));
+    }
+    Ok(())
+}
+
+/// A cell that is a phone number rather than an e-mail address: no '@' and
+/// at least 8 digits among phone punctuation.
+pub(crate) fn looks_like_phone(value: &str) -> bool {
+    !value.contains('@')
+        && value.chars().filter(char::is_ascii_digit).count() >= 8
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || " +./-()".contains(c))
+}
+
+/// Swapped source columns: refuse when over 30% of the filled "Mobiel
+/// nummer" cells hold an '@', or most filled "E-mailadres" cells a phone
+/// number, instead of exporting thousands of invalid rows.
+fn check_swapped(cols: &HashMap<String, usize>, rows: &[SheetRow]) -> Result<()> {
+    let filled = |name: &str| -> Vec<String> {
+        rows.iter()
+            .map(|(_, row)| get(cols, row, name))
+            .filter(|value| !value.is_empty())
+            .collect()
+    };
+    let phones = filled("Mobiel nummer");
+    let with_at = phones.iter().filter(|v| v.contains('@')).count();
+    if !phones.is_empty() && with_at as f64 > phones.len() as f64 * SWAPPED_PHONE_SHARE {
+        return Err(ConvertError::SwappedColumns {
+            detail: format!(
+                "{} of {} \"Mobiel nummer\" cells hold an e-mail address",
+                with_at,
+                phones.len()
+            ),
+        });
+    }
+    let emails = filled("E-mailadres");
+    let numbers = emails.iter().filter(|v| looks_like_phone(v)).count();
+    if numbers * 2 > emails.len() {
+        return Err(ConvertError::SwappedColumns {
+            detail: format!(
+                "{} of {} \"E-mailadres\" cells hold a phone number",
+                numbers,
+                emails.len()
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Partial-failure policy: refuse the export when more than the profile's
+/// `max_rejected_percent` of `rows` was rejected; otherwise the good rows go
+/// out and the rejected ones to the rejected-rows file.
+fn check_rejected(rows: usize, rejected: usize, profile: &Profile) -> Result<()> {
+    match profile.max_rejected_percent {
+        Some(limit) if rejected * 100 > rows * limit as usize => {
+            Err(ConvertError::TooManyRejected {
+                rejected,
+                rows,
+                limit,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reconciliation with the register: refuse the export when the number of
+/// contacts converted is more than `tolerance` percent away from the number
+/// the operator expected (a truncated or wrong export). No expectation, no check.
+pub fn check_expected(rows: usize, expected: Option<usize>, tolerance: u32) -> Result<()> {
+    match expected {
+        Some(expected) if rows.abs_diff(expected) * 100 > expected * tolerance as usize => {
+            Err(ConvertError::UnexpectedCount {
+                rows,
+                expected,
+                tolerance,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Pre-flight check against the profile's BE-Alert quota: BE-Alert drops
+/// the contacts over it, so warn with the number of rows too many.
+fn check_quota(rows: usize, profile: &Profile, summary: &mut ConvertSummary) {
+    if let Some(quota) = profile.quota.filter(|&quota| rows > quota) {
+        summary.warnings.push(format!(
+            "{} rows exceed the BE-Alert quota of {} contacts for {}: {} rows over the limit would be dropped on upload",
+            rows,
+            quota,
+            profile.name,
+            rows - quota
+        ));
+    }
+}
+
+/// Gemeente names: the profile's own must be the official spelling (it is
+/// what the output gets), and rows of the input "Gemeente" column that are
+/// written differently ("Alken (Limburg)", "Bruxelles/Brussel") or name
+/// another gemeente are counted, with the first example.
+fn check_gemeente(
+    cols: &HashMap<String, usize>,
+    rows: &[SheetRow],
+    profile: &Profile,
+    summary: &mut ConvertSummary,
+) {
+    let official = profile.official_gemeente();
+    if official != profile.gemeente.trim() {
+        summary.warnings.push(format!(
+            "profile gemeente \"{}\" exported as the official \"{}\"",
+            profile.gemeente, official
+        ));
+    }
+    if !cols.contains_key(GEMEENTE_COLUMN) {
+        return;
+    }
+
+    let mut respelled: Vec<String> = Vec::new();
+    let mut other: Vec<String> = Vec::new();
+    for &(_, row) in rows {
+        let value = get(cols, row, GEMEENTE_COLUMN);
+        if value.is_empty() {
+            continue;
+        }
+        if !gemeente::same(&value, &profile.gemeente, &profile.gemeente_aliases) {
+            other.push(value);
+        } else if gemeente::normalize(&value, &profile.taal, &profile.gemeente_aliases) != value {
+            respelled.push(value);
+        }
+    }
+    if let Some(first) = respelled.first() {
+        summary.warnings.push(format!(
+            "{} rows write the gemeente unofficially (e.g. \"{}\"), exported as \"{}\"",
+            respelled.len(),
+            first,
+            official
+        ));
+    }
+    if let Some(first) = other.first() {
+        summary.warnings.push(format!(
+            "{} rows name another gemeente than {} (e.g. \"{}\")",
+            other.len(),
+            official,
+            first
+        ));
+    }
+}
+
+/// Required columns in the header with EMPTY_COLUMN_SHARE or more of their
+/// cells empty: the header is right, the content is not (a register export
+/// made without phone numbers, or with a shifted layout). E-mailadres is not
+/// checked, most registers hold few addresses.
+pub fn empty_columns(cols: &HashMap<String, usize>, rows: &[SheetRow]) -> Vec<EmptyColumn> {
+    if rows.is_empty() {
+        return Vec::new();
+    }
+    REQUIRED_COLUMNS
+        .iter()
+        .filter(|&&name| name != "E-mailadres" && cols.contains_key(name))
+        .filter_map(|&name| {
+            let filled = rows
+                .iter()
+                .filter(|(_, row)| !get(cols, row, name).is_empty())
+                .count();
+            let empty = (rows.len() - filled) as f64;
+            (empty >= rows.len() as f64 * EMPTY_COLUMN_SHARE).then(|| EmptyColumn {
+                column: name.to_string(),
+                filled,
+                rows: rows.len(),
+            })
+        })
+        .collect()
+}
+
+/// Check the header row of `input_xlsx`; returns the number of data rows and
+/// the required columns that are (almost) empty.
+pub fn validate_xlsx_columns(
+    input_xlsx: &str,
+    profile: &Profile,
+) -> Result<(usize, Vec<EmptyColumn>)> {
+    let range = open_first_sheet(input_xlsx)?;
+    let assumed = assumed_header(profile);
+    let mut rows = skip_boilerplate(range.rows(), profile);
+    let (header, first_row) = split_header(
+        &mut rows,
+        &assumed,
+        profile.skip_leading_rows,
+        sheet_top(&range),
+    );
+    let cols = header_columns(input_xlsx, header, profile)?;
+    let rows: Vec<SheetRow> = rows
+        .enumerate()
+        .map(|(i, row)| (i + first_row, row))
+        .collect();
+    let empty = empty_columns(&cols, &rows);
+    Ok((rows.len(), empty))
+}
+
+// NEW output CSV header (33 columns)
+pub const OUTPUT_HEADER: [&str; 33] = [
+    "Tel/Ref.",
+    "Civilité",
+    "Naam",
+    "Voornaam",
+    "Adres incl huisnummer",
+    "Bijkomend adres",
+    "Postcode",
+    "Gemeente",
+    "Geboortedatum",
+    "Email",
+    "FAX",
+    "FAX2",
+    "FAX3",
+    "Verdieping",
+    "Aantal inwoners",
+    "Telefoon 2",
+    "Telefoon 3",
+    "Telefoon 4",
+    "Telefoon 5",
+    "Telefoone 6",
+    "Telefoon 7",
+    "SMS",
+    "SMS 2",
+    "SMS 3",
+    "Pager",
+    "Zone libre 1",
+    "Zone libre 2",
+    "Zone libre 3",
+    "Taal",
+    "Land",
+    "Rode lijst",
+    "Type Contact",
+    "GPS coördinaten",
+];
+
+// Second header line for `header = "described"` profiles, same order as OUTPUT_HEADER
+pub(crate) const OUTPUT_DESCRIPTIONS: [&str; 33] = [
+    "Mobile number (0032...), contact key",
+    "Title",
+    "First name (provider swap)",
+    "Last name (provider swap)",
+    "Street and house number",
+    "Additional address",
+    "Postcode",
+    "Municipality",
+    "Date of birth",
+    "E-mail address",
+    "Fax / extra number",
+    "Fax / extra number 2",
+    "Fax / extra number 3",
+    "Floor",
+    "Number of residents",
+    "Phone 2",
+    "Phone 3",
+    "Phone 4",
+    "Phone 5",
+    "Phone 6",
+    "Phone 7",
+    "SMS number",
+    "SMS number 2",
+    "SMS number 3",
+    "Pager",
+    "Free field 1",
+    "Free field 2",
+    "Free field 3",
+    "Language",
+    "Country",
+    "Red list (0/1)",
+    "Contact type",
+    "GPS coordinates",
+];
+
+pub fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output")
+        .to_string()
+}
+
+/// Fail early with `FileLocked` when an existing output file is open in
+/// another program, before any work is done.
+pub fn check_writable(path: &Path) -> Result<()> {
+    if path.exists() {
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| ConvertError::io_at(path, e))?;
+    }
+    Ok(())
+}
+
+/// Writer of output records: in OUTPUT_HEADER order, or in the order of the
+/// profile's template (see template.rs).
+struct CsvOut<W: Write> {
+    writer: Writer<W>,
+    /// The template's fields; None for the standard layout.
+    fields: Option<Vec<Option<usize>>>,
+}
+
+impl<W: Write> CsvOut<W> {
+    fn write_record<S: AsRef<str> + AsRef<[u8]>>(&mut self, record: &[S]) -> Result<()> {
+        match &self.fields {
+            None => self.writer.write_record(record)?,
+            Some(fields) => self
+                .writer
+                .write_record(template::reorder(fields, record))?,
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "native")]
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CsvOut<File> {
+    /// Flush and make sure the file is on disk, not only in the cache of
+    /// the system or the share, before it is read back (see check_written).
+    fn finish(self, path: &Path) -> Result<()> {
+        let file = self
+            .writer
+            .into_inner()
+            .map_err(|e| ConvertError::io_at(path, e.into_error()))?;
+        file.sync_all().map_err(|e| ConvertError::io_at(path, e))
+    }
+}
+
+/// Read a CSV back after writing: UTF-8, `rows` records after the header
+/// line(s), every line with the layout's number of fields, the last one
+/// ended. A write cut short (disk full, share gone) has gone unnoticed
+/// until BE-Alert refused the upload; such a file is removed.
+fn check_written(
+    path: &Path,
+    rows: usize,
+    header: HeaderRows,
+    template: Option<&OutputTemplate>,
+) -> Result<()> {
+    let incomplete = |reason: String| {
+        let _ = fs::remove_file(path);
+        ConvertError::OutputIncomplete {
+            path: path.to_path_buf(),
+            reason,
+        }
+    };
+    let bytes = fs::read(path).map_err(|e| ConvertError::io_at(path, e))?;
+    if std::str::from_utf8(&bytes).is_err() {
+        return Err(incomplete("not valid UTF-8".to_string()));
+    }
+    let header_lines = header_lines(header);
+    if header_lines + rows > 0 && !bytes.ends_with(b"\n") {
+        return Err(incomplete("the last line is cut off".to_string()));
+    }
+    let (delimiter, columns) = match template {
+        Some(t) => (t.delimiter as u8, t.columns.len()),
+        None => (b';', OUTPUT_HEADER.len()),
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes.as_slice());
+    let mut lines = 0;
+    for record in reader.records() {
+        lines += 1;
+        let record = record.map_err(|e| incomplete(format!("line {}: {}", lines, e)))?;
+        if record.len() != columns {
+            return Err(incomplete(format!(
+                "line {} has {} fields instead of {}",
+                lines,
+                record.len(),
+                columns
+            )));
+        }
+    }
+    if lines != header_lines + rows {
+        return Err(incomplete(format!(
+            "{} lines read back instead of {}",
+            lines,
+            header_lines + rows
+        )));
+    }
+    Ok(())
+}
+
+/// Lines above the first record.
+fn header_lines(header: HeaderRows) -> usize {
+    match header {
+        HeaderRows::Standard => 1,
+        HeaderRows::None => 0,
+        HeaderRows::Described => 2,
+    }
+}
+
+fn create_csv_writer(
+    output_csv: &Path,
+    header: HeaderRows,
+    line_ending: LineEnding,
+    template: Option<&OutputTemplate>,
+) -> Result<CsvOut<File>> {
+    let file = File::create(output_csv).map_err(|e| ConvertError::io_at(output_csv, e))?;
+    csv_writer(file, header, line_ending, template)
+}
+
+/// Line terminator of the CSVs a profile writes.
+fn terminator(line_ending: LineEnding) -> Terminator {
+    match line_ending {
+        LineEnding::Crlf => Terminator::CRLF,
+        LineEnding::Lf => Terminator::Any(b'\n'),
+    }
+}
+
+fn csv_writer<W: Write>(
+    out: W,
+    header: HeaderRows,
+    line_ending: LineEnding,
+    template: Option<&OutputTemplate>,
+) -> Result<CsvOut<W>> {
+    let delimiter = template.map_or(b';', |t| t.delimiter as u8);
+    let writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .terminator(terminator(line_ending))
+        .buffer_capacity(CSV_BUFFER)
+        .from_writer(out);
+    let mut out = CsvOut {
+        writer,
+        fields: template.map(OutputTemplate::fields),
+    };
+    let names = match template {
+        Some(t) => t.header(),
+        None => OUTPUT_HEADER.to_vec(),
+    };
+    match header {
+        HeaderRows::Standard => out.writer.write_record(names)?,
+        HeaderRows::None => {}
+        HeaderRows::Described => {
+            out.writer.write_record(names)?;
+            out.write_record(&OUTPUT_DESCRIPTIONS)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Convert header + data rows from any source (XLSX sheet, pasted rows).
+/// `source` names the input in messages and the report.
+pub fn convert_rows_to_csv<'a>(
+    source: &str,
+    rows: impl Iterator<Item = &'a [Data]>,
+    output_csv: &str,
+    profile: &Profile,
+) -> Result<ConvertSummary> {
+    write_export(prepare_export(source, rows, profile)?, output_csv)
+}
+
+/// Records of a single-CSV export, read and checked but not written yet
+/// (so duplicates can be reviewed first).
+pub struct PreparedExport {
+    pub source: String,
+    /// Files read for this export, never overwritten by the output.
+    pub inputs: Vec<PathBuf>,
+    /// Profile name, for the history.
+    pub profile: String,
+    pub header: HeaderRows,
+    pub line_ending: LineEnding,
+    /// Column layout of the profile, None for the standard one.
+    pub template: Option<OutputTemplate>,
+    /// Header row of the input, for the rejected-rows file.
+    pub input_header: Vec<String>,
+    /// Write the contacts gone since the last run (profile `delete_file`).
+    pub delete_file: bool,
+    /// Profile `expected_tolerance_percent`, for `check_expected` when written.
+    pub expected_tolerance: u32,
+    pub records: Vec<Record>,
+    pub summary: ConvertSummary,
+}
+
+pub fn prepare_xlsx_export(input_xlsx: &str, profile: &Profile) -> Result<PreparedExport> {
+    prepare_xlsx_export_with_progress(input_xlsx, profile, &|_, _| {})
+}
+
+/// As prepare_xlsx_export, calling `progress` with (rows done, data rows)
+/// while the rows go through the pipeline (see `Pipeline::run_with_progress`).
+pub fn prepare_xlsx_export_with_progress(
+    input_xlsx: &str,
+    profile: &Profile,
+    progress: &dyn Fn(usize, usize),
+) -> Result<PreparedExport> {
+    let (range, formulas) = read_first_sheet(input_xlsx, profile)?;
+    let top = sheet_top(&range);
+    let mut prepared = prepare_rows(input_xlsx, range.rows(), top, profile, progress)?;
+    note_macros(input_xlsx, &mut prepared.summary);
+    note_formulas(input_xlsx, formulas, &mut prepared.summary);
+    Ok(prepared)
+}
+
+pub fn prepare_export<'a>(
+    source: &str,
+    rows: impl Iterator<Item = &'a [Data]>,
+    profile: &Profile,
+) -> Result<PreparedExport> {
+    prepare_rows(source, rows, 0, profile, &|_, _| {})
+}
+
+/// `top`: sheet row of the first of `rows` (see sheet_top).
+fn prepare_rows<'a>(
+    source: &str,
+    rows: impl Iterator<Item = &'a [Data]>,
+    top: usize,
+    profile: &Profile,
+    progress: &dyn Fn(usize, usize),
+) -> Result<PreparedExport> {
+    // Header row -> column name -> index (required columns checked)
+    let assumed = assumed_header(profile);
+    let mut rows = skip_boilerplate(rows, profile);
+    let (header, first_row) = split_header(&mut rows, &assumed, profile.skip_leading_rows, top);
+    let cols = header_columns(source, header, profile)?;
+    check_profile_columns(&cols, profile)?;
+    check_fixed_values(profile)?;
+
+    let mut summary = ConvertSummary {
+        profiles: vec![ProfileStamp::of(profile)],
+        ..Default::default()
+    };
+    check_strict(&cols, profile, &mut summary)?;
+    note_duplicate_columns(source, header, profile, &mut summary);
+
+    let mut rows: Vec<SheetRow> = rows
+        .enumerate()
+        .map(|(i, row)| (i + first_row, row))
+        .collect();
+    check_swapped(&cols, &rows)?;
+    summary.empty_columns = empty_columns(&cols, &rows);
+    check_rijksregister(&cols, &rows, profile, &mut summary)?;
+    check_gemeente(&cols, &rows, profile, &mut summary);
+    sort_rows(&cols, &mut rows, profile.sort);
+
+    let mut records =
+        Pipeline::for_profile(&cols, profile)?.run_with_progress(&rows, &mut summary, progress);
+    if profile.reproducible {
+        sort_reproducible(&mut records);
+    }
+    check_rejected(rows.len(), summary.rejected.len(), profile)?;
+    check_quota(records.len(), profile, &mut summary);
+    Ok(PreparedExport {
+        source: source.to_string(),
+        inputs: vec![PathBuf::from(source)],
+        profile: profile.name.clone(),
+        header: profile.header,
+        line_ending: profile.line_ending,
+        template: profile.template.clone(),
+        input_header: header.map(input_header).unwrap_or_default(),
+        delete_file: profile.delete_file,
+        expected_tolerance: profile.expected_tolerance_percent,
+        records,
+        summary,
+    })
+}
+
+fn input_header(header: &[Data]) -> Vec<String> {
+    header
+        .iter()
+        .map(|cell| cell_to_string(cell).trim().to_string())
+        .collect()
+}
+
+/// `<output stem>_rejected.csv` next to the output CSV.
+fn rejected_path(output_csv: &Path) -> PathBuf {
+    let stem = file_stem(&output_csv.to_string_lossy());
+    output_csv.with_file_name(format!("{}_rejected.csv", stem))
+}
+
+/// Rejected rows with the reason and their input cells, so they can be fixed
+/// in the source and converted again.
+fn write_rejected(
+    path: &Path,
+    input_header: &[String],
+    summary: &ConvertSummary,
+    line_ending: LineEnding,
+) -> Result<()> {
+    let file = File::create(path).map_err(|e| ConvertError::io_at(path, e))?;
+    // Rows of a merge come from two sources of different widths
+    let mut writer = WriterBuilder::new()
+        .delimiter(b';')
+        .terminator(terminator(line_ending))
+        .flexible(true)
+        .from_writer(file);
+    let mut header = vec![
+        "Row".to_string(),
+        "Cell".to_string(),
+        "Field".to_string(),
+        "Problem".to_string(),
+    ];
+    header.extend(input_header.iter().cloned());
+    writer.write_record(&header)?;
+    for rejected in &summary.rejected {
+        let mut record = vec![
+            rejected.row.to_string(),
+            rejected
+                .column
+                .map_or_else(String::new, |c| report::cell_ref(rejected.row, Some(c))),
+            rejected.field.clone(),
+            rejected.kind.to_string(),
+        ];
+        record.extend(rejected.cells.iter().cloned());
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// `<output stem>_delete.csv` next to the output CSV.
+#[cfg(feature = "native")]
+fn delete_path(output_csv: &Path) -> PathBuf {
+    let stem = file_stem(&output_csv.to_string_lossy());
+    output_csv.with_file_name(format!("{}_delete.csv", stem))
+}
+
+/// Contacts to remove from BE-Alert, in the layout of the contact CSV: the
+/// removal upload matches on Tel/Ref., the name and address are there for
+/// whoever checks the file.
+#[cfg(feature = "native")]
+fn write_removed(
+    path: &Path,
+    removed: &[history::RemovedContact],
+    header: HeaderRows,
+    line_ending: LineEnding,
+    template: Option<&OutputTemplate>,
+) -> Result<()> {
+    let mut writer = create_csv_writer(path, header, line_ending, template)?;
+    for contact in removed {
+        let mut record = vec![String::new(); OUTPUT_HEADER.len()];
+        // Tel/Ref., Naam, Voornaam, Adres incl huisnummer, Email
+        record[0] = contact.tel_ref.clone();
+        record[2] = contact.naam.clone();
+        record[3] = contact.voornaam.clone();
+        record[4] = contact.adres.clone();
+        record[9] = contact.email.clone();
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// The HTML report and the review CSV next to it (see report::review_lines).
+fn write_reports(
+    report: &Path,
+    source: &str,
+    outputs: &[PathBuf],
+    summary: &mut ConvertSummary,
+    line_ending: LineEnding,
+) -> Result<()> {
+    report::write_html(report, source, outputs, summary)
+        .map_err(|e| ConvertError::io_at(report, e))?;
+    summary.report = Some(report.to_path_buf());
+
+    // Like the rejected-rows file, only written when there is something in it
+    let lines = report::review_lines(summary);
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let path = report::review_path(report);
+    let file = File::create(&path).map_err(|e| ConvertError::io_at(&path, e))?;
+    // The BOM makes Excel read the accents as UTF-8
+    let mut out = io::BufWriter::new(file);
+    out.write_all("\u{feff}".as_bytes())
+        .map_err(|e| ConvertError::io_at(&path, e))?;
+    let mut writer = WriterBuilder::new()
+        .delimiter(b';')
+        .terminator(terminator(line_ending))
+        .from_writer(out);
+    writer.write_record([
+        "Row",
+        "Cell",
+        "Column",
+        "Original value",
+        "Problem",
+        "Suggested fix",
+    ])?;
+    for line in lines {
+        writer.write_record([
+            line.row.map(|r| r.to_string()).unwrap_or_default(),
+            line.cell,
+            line.column,
+            line.value,
+            line.problem,
+            line.fix,
+        ])?;
+    }
+    writer.flush()?;
+    summary.review_file = Some(path);
+    Ok(())
+}
+
+/// Refuse an output path that is one of `inputs`, compared after resolving
+/// `..`, links and (on Windows) letter case. A path that does not exist yet
+/// cannot be an input; sources that are not files (clipboard) never match.
+pub fn check_not_input(output: &Path, inputs: &[PathBuf]) -> Result<()> {
+    let Ok(output) = fs::canonicalize(output) else {
+        return Ok(());
+    };
+    if inputs
+        .iter()
+        .any(|input| fs::canonicalize(input).is_ok_and(|input| input == output))
+    {
+        return Err(ConvertError::OutputIsInput { path: output });
+    }
+    Ok(())
+}
+
+/// What an export changes in the CSV it would overwrite. Rows are matched on
+/// Tel/Ref. (the n-th row with a number to the n-th with it in the other
+/// file), rows without one on all their fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputDiff {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+impl OutputDiff {
+    /// "12 rows added, 3 removed, 40 changed, 9000 unchanged"
+    pub fn text(&self) -> String {
+        format!(
+            "{} rows added, {} removed, {} changed, {} unchanged",
+            self.added, self.removed, self.changed, self.unchanged
+        )
+    }
+}
+
+/// Records of a CSV after its header line(s), by (Tel/Ref. in column `key`,
+/// occurrence); rows without a Tel/Ref. by all their fields.
+fn keyed_records(
+    bytes: &[u8],
+    delimiter: u8,
+    skip: usize,
+    key: Option<usize>,
+) -> Result<HashMap<(String, usize), Vec<String>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes);
+    let mut records = HashMap::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for record in reader.records().skip(skip) {
+        let fields: Vec<String> = record?.iter().map(str::to_string).collect();
+        let id = match key.and_then(|k| fields.get(k)).filter(|v| !v.is_empty()) {
+            Some(number) => number.clone(),
+            None => fields.join("\n"),
+        };
+        let occurrence = seen.entry(id.clone()).or_default();
+        *occurrence += 1;
+        records.insert((id, *occurrence), fields);
+    }
+    Ok(records)
+}
+
+/// Compare a prepared export with the CSV at `output` before overwriting it,
+/// as the export would write it (same layout and header lines). None when
+/// there is no file yet.
+pub fn diff_existing(prepared: &PreparedExport, output: &Path) -> Result<Option<OutputDiff>> {
+    let existing = match fs::read(output) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(ConvertError::io_at(output, e)),
+    };
+    let template = prepared.template.as_ref();
+    let mut writer = csv_writer(Vec::new(), prepared.header, prepared.line_ending, template)?;
+    for (_, record) in &prepared.records {
+        writer.write_record(record)?;
+    }
+    let written = writer
+        .writer
+        .into_inner()
+        .map_err(|e| ConvertError::IoError(e.into_error()))?;
+
+    let delimiter = template.map_or(b';', |t| t.delimiter as u8);
+    let key = match template {
+        Some(t) => t.columns.iter().position(|c| c.field == OUTPUT_HEADER[0]),
+        None => Some(0),
+    };
+    let skip = header_lines(prepared.header);
+    let before = keyed_records(&existing, delimiter, skip, key)?;
+    let after = keyed_records(&written, delimiter, skip, key)?;
+    let mut diff = OutputDiff {
+        removed: before.keys().filter(|id| !after.contains_key(*id)).count(),
+        ..Default::default()
+    };
+    for (id, fields) in &after {
+        match before.get(id) {
+            None => diff.added += 1,
+            Some(old) if old == fields => diff.unchanged += 1,
+            Some(_) => diff.changed += 1,
+        }
+    }
+    Ok(Some(diff))
+}
+
+/// Write the CSV and its report (`<source stem>_report.html` next to the CSV).
+pub fn write_export(prepared: PreparedExport, output_csv: &str) -> Result<ConvertSummary> {
+    let output = Path::new(output_csv);
+    let report = report::report_path(
+        output.parent().unwrap_or(Path::new(".")),
+        &file_stem(&prepared.source),
+    );
+    write_export_with_report(prepared, output_csv, &report)
+}
+
+/// History of a source that is a file, with the comparison to its last run
+/// stored in the summary.
+#[cfg(feature = "native")]
+fn open_history(
+    source: &str,
+    records: &[Record],
+    summary: &mut ConvertSummary,
+) -> Option<(History, String)> {
+    let hash = history::file_hash(source)?;
+    let opened = History::open().and_then(|h| {
+        let delta = h.compare(source, &hash, records, summary)?;
+        Ok((h, delta))
+    });
+    match opened {
+        Ok((h, delta)) => {
+            summary.delta = delta;
+            Some((h, hash))
+        }
+        Err(e) => {
+            summary.warnings.push(format!("history unavailable: {}", e));
+            None
+        }
+    }
+}
+
+// Without history (browser build) the profile name is not needed
+#[cfg_attr(not(feature = "native"), allow(unused_variables))]
+fn write_export_with_report(
+    prepared: PreparedExport,
+    output_csv: &str,
+    report: &Path,
+) -> Result<ConvertSummary> {
+    let PreparedExport {
+        source,
+        inputs,
+        profile,
+        header,
+        line_ending,
+        template,
+        input_header,
+        delete_file,
+        expected_tolerance,
+        records,
+        mut summary,
+    } = prepared;
+
+    // History is a convenience: problems with it never block the export
+    #[cfg(feature = "native")]
+    let mut history = open_history(&source, &records, &mut summary);
+
+    let output_csv = Path::new(output_csv);
+    check_not_input(output_csv, &inputs)?;
+    check_expected(
+        records.len(),
+        summary.run.expected_contacts,
+        expected_tolerance,
+    )?;
+    // Held until the report and history are written too
+    let _lock = OutputLock::acquire(output_csv, &summary.run)?;
+    let mut writer = create_csv_writer(output_csv, header, line_ending, template.as_ref())?;
+    for (_, record) in &records {
+        writer.write_record(record)?;
+    }
+    writer.finish(output_csv)?;
+    check_written(output_csv, records.len(), header, template.as_ref())?;
+    summary.rows = records.len();
+    summary.count_streets(&records);
+    if !summary.rejected.is_empty() {
+        let path = rejected_path(output_csv);
+        write_rejected(&path, &input_header, &summary, line_ending)?;
+        summary.rejected_file = Some(path);
+    }
+
+    #[cfg(feature = "native")]
+    if delete_file {
+        match history.as_ref() {
+            Some((h, hash)) => match h.removed_contacts(&source, hash, &records) {
+                Ok(removed) => {
+                    let path = delete_path(output_csv);
+                    write_removed(&path, &removed, header, line_ending, template.as_ref())?;
+                    summary.delete_file = Some((path, removed.len()));
+                }
+                Err(e) => summary
+                    .warnings
+                    .push(format!("no delete file, history unavailable: {}", e)),
+            },
+            None => summary
+                .warnings
+                .push("no delete file: there is no history of this source".to_string()),
+        }
+    }
+
+    write_reports(
+        report,
+        &source,
+        &[output_csv.to_path_buf()],
+        &mut summary,
+        line_ending,
+    )?;
+
+    #[cfg(feature = "native")]
+    if let Some((h, hash)) = history.as_mut() {
+        match h.record(&source, hash, output_csv, &profile, &records, &summary) {
+            Ok(run_id) => summary.history_run = Some(run_id),
+            Err(e) => summary
+                .warnings
+                .push(format!("run not saved in history: {}", e)),
+        }
+    }
+
+    Ok(summary)
+}
+
+/// CSV and report of a workbook converted in memory.
+pub struct BufferExport {
+    pub csv: Vec<u8>,
+    pub report_html: String,
+    pub summary: ConvertSummary,
+}
+
+/// Convert XLSX bytes without touching the file system (browser build,
+/// library callers, the command line's pipe mode): nothing is written and
+/// there is no history. `name` is used in messages and the report.
+pub fn convert_xlsx_bytes(name: &str, xlsx: &[u8], profile: &Profile) -> Result<BufferExport> {
+    let mut workbook =
+        Xlsx::new(Cursor::new(xlsx)).map_err(|e| ConvertError::from_xlsx(name, e))?;
+    let (range, formulas) = sheet_with_formulas(name, &mut workbook, profile)?;
+    let mut prepared = prepare_rows(name, range.rows(), sheet_top(&range), profile, &|_, _| {})?;
+    note_macros(name, &mut prepared.summary);
+    note_formulas(name, formulas, &mut prepared.summary);
+    buffer_export(name, prepared)
+}
+
+/// As convert_xlsx_bytes, for a contact list saved as CSV (UTF-8, the same
+/// columns as the XLSX), written as `dialect` (see dialect.rs). Every cell is
+/// text, so leading zeros of phone numbers survive. A list without a header
+/// row uses the profile's `input_columns`, or columns guessed from the data.
+pub fn convert_csv_bytes(
+    name: &str,
+    csv: &[u8],
+    profile: &Profile,
+    dialect: &CsvDialect,
+) -> Result<BufferExport> {
+    let text = csv_text(name, csv)?;
+    let rows = dialect::read_rows(text, dialect)?;
+    let mut profile = profile.clone();
+    if dialect.has_header {
+        profile.input_columns.clear();
+    } else if profile.input_columns.is_empty() {
+        profile.input_columns = headerless::guess_columns(rows.iter().map(Vec::as_slice));
+    }
+    let prepared = prepare_export(name, rows.iter().map(Vec::as_slice), &profile)?;
+    buffer_export(name, prepared)
+}
+
+/// Text of a CSV file, without a byte order mark.
+pub fn csv_text<'a>(name: &str, csv: &'a [u8]) -> Result<&'a str> {
+    let text = std::str::from_utf8(csv).map_err(|_| ConvertError::UnsupportedFormat {
+        path: PathBuf::from(name),
+        reason: "not UTF-8; save the list as \"CSV UTF-8\"".to_string(),
+    })?;
+    Ok(text.strip_prefix('\u{feff}').unwrap_or(text))
+}
+
+/// CSV bytes and HTML report of a prepared export.
+fn buffer_export(name: &str, prepared: PreparedExport) -> Result<BufferExport> {
+    let mut writer = csv_writer(
+        Vec::new(),
+        prepared.header,
+        prepared.line_ending,
+        prepared.template.as_ref(),
+    )?;
+    for (_, record) in &prepared.records {
+        writer.write_record(record)?;
+    }
+    let csv = writer
+        .writer
+        .into_inner()
+        .map_err(|e| ConvertError::io_at(name, e.into_error()))?;
+    let mut summary = prepared.summary;
+    summary.rows = prepared.records.len();
+    summary.count_streets(&prepared.records);
+    Ok(BufferExport {
+        csv,
+        report_html: report::html(name, &[], &summary),
+        summary,
+    })
+}
+
+/// Apply the duplicate decisions to a prepared export.
+pub fn resolve_duplicates(
+    prepared: &mut PreparedExport,
+    groups: &[DuplicateGroup],
+    actions: &[DuplicateAction],
+) {
+    let records = std::mem::take(&mut prepared.records);
+    let (kept, removed) = dedup::apply(records, groups, actions);
+    prepared.records = kept;
+    prepared.summary.warnings.push(format!(
+        "{} duplicate Tel/Ref. groups reviewed, {} rows removed",
+        groups.len(),
+        removed
+    ));
+}
+
+/// Result of one profile in a multi-profile export.
+pub struct ProfileExport {
+    pub profile: String,
+    pub path: PathBuf,
+    pub rows: usize,
+}
+
+/// Split one regional XLSX over several profiles: every row goes to the first
+/// profile whose postcode (or gemeente) matches the row's "Postcode" /
+/// "Gemeente" input column, one CSV per profile in `output_dir`.
+/// Returns the per-profile results; the summary counts the rows no profile matched.
+pub fn convert_xlsx_per_profile(
+    input_xlsx: &str,
+    output_dir: &Path,
+    profiles: &[Profile],
+    run: &RunInfo,
+) -> Result<(Vec<ProfileExport>, ConvertSummary)> {
+    let (range, formulas) = read_first_sheet(input_xlsx, &profiles[0])?;
+    // One file, so one header mapping: the first profile's [columns] table
+    // and boilerplate rows
+    let mut rows = skip_boilerplate(range.rows(), &profiles[0]);
+    let assumed = assumed_header(&profiles[0]);
+    let (header, first_row) = split_header(
+        &mut rows,
+        &assumed,
+        profiles[0].skip_leading_rows,
+        sheet_top(&range),
+    );
+    let cols = header_columns(input_xlsx, header, &profiles[0])?;
+    let mut summary = ConvertSummary {
+        run: run.clone(),
+        profiles: profiles.iter().map(ProfileStamp::of).collect(),
+        ..Default::default()
+    };
+    note_macros(input_xlsx, &mut summary);
+    note_formulas(input_xlsx, formulas, &mut summary);
+    note_duplicate_columns(input_xlsx, header, &profiles[0], &mut summary);
+    for profile in profiles {
+        check_profile_columns(&cols, profile)?;
+        check_fixed_values(profile)?;
+        check_strict(&cols, profile, &mut summary)?;
+    }
+
+    if !cols.contains_key(POSTCODE_COLUMN) && !cols.contains_key(GEMEENTE_COLUMN) {
+        return Err(missing_column(
+            &cols,
+            &format!("{} or {}", POSTCODE_COLUMN, GEMEENTE_COLUMN),
+        ));
+    }
+
+    let stem = file_stem(input_xlsx);
+
+    let mut partitions: Vec<Vec<SheetRow>> = vec![Vec::new(); profiles.len()];
+    for (i, row) in rows.enumerate() {
+        let postcode = get(&cols, row, POSTCODE_COLUMN);
+        let gemeente = get(&cols, row, GEMEENTE_COLUMN);
+        match profiles
+            .iter()
+            .position(|p| p.matches(&postcode, &gemeente))
+        {
+            Some(p) => partitions[p].push((i + first_row, row)),
+            None => summary.unmatched += 1,
+        }
+    }
+    summary.empty_columns = empty_columns(&cols, &partitions.concat());
+    for (profile, part) in profiles.iter().zip(&partitions) {
+        check_swapped(&cols, part)?;
+        check_rijksregister(&cols, part, profile, &mut summary)?;
+        check_gemeente(&cols, part, profile, &mut summary);
+    }
+
+    // All profiles are converted before anything is written, so a profile
+    // over its rejected-rows limit refuses the whole export
+    let mut converted = Vec::new();
+    for (profile, mut part) in profiles.iter().zip(partitions) {
+        sort_rows(&cols, &mut part, profile.sort);
+        let rejected = summary.rejected.len();
+        let mut records = Pipeline::for_profile(&cols, profile)?.run(&part, &mut summary);
+        if profile.reproducible {
+            sort_reproducible(&mut records);
+        }
+        check_rejected(part.len(), summary.rejected.len() - rejected, profile)?;
+        check_quota(records.len(), profile, &mut summary);
+        converted.push((profile, records));
+    }
+    // The register report counts the whole file, not one gemeente
+    let total = converted.iter().map(|(_, records)| records.len()).sum();
+    check_expected(
+        total,
+        run.expected_contacts,
+        profiles[0].expected_tolerance_percent,
+    )?;
+
+    let paths: Vec<PathBuf> = converted
+        .iter()
+        .map(|(profile, _)| output_dir.join(format!("{}_{}.csv", stem, profile.file_tag())))
+        .collect();
+    // Every file locked before the first is written: all or nothing
+    let _locks = paths
+        .iter()
+        .map(|path| OutputLock::acquire(path, run))
+        .collect::<Result<Vec<_>>>()?;
+    let mut exports = Vec::new();
+    for ((profile, records), path) in converted.into_iter().zip(paths) {
+        let mut writer = create_csv_writer(
+            &path,
+            profile.header,
+            profile.line_ending,
+            profile.template.as_ref(),
+        )?;
+        for (_, record) in &records {
+            writer.write_record(record)?;
+        }
+        writer.finish(&path)?;
+        check_written(
+            &path,
+            records.len(),
+            profile.header,
+            profile.template.as_ref(),
+        )?;
+        summary.rows += records.len();
+        summary.count_streets(&records);
+        exports.push(ProfileExport {
+            profile: profile.name.clone(),
+            path,
+            rows: records.len(),
+        });
+    }
+
+    if !summary.rejected.is_empty() {
+        let path = output_dir.join(format!("{}_rejected.csv", stem));
+        let header = header.map(input_header).unwrap_or_default();
+        write_rejected(&path, &header, &summary, profiles[0].line_ending)?;
+        summary.rejected_file = Some(path);
+    }
+
+    let report = report::report_path(output_dir, &stem);
+    let outputs: Vec<PathBuf> = exports.iter().map(|e| e.path.clone()).collect();
+    write_reports(
+        &report,
+        input_xlsx,
+        &outputs,
+        &mut summary,
+        profiles[0].line_ending,
+    )?;
+
+    Ok((exports, summary))
+}
+
+/// Merge two differently structured XLSX files (e.g. population export and a
+/// volunteer list), each read with its own profile, into one CSV.
+/// Contacts are matched on Tel/Ref.: the first file's rows come first, a
+/// contact also found in the second file is kept once, taking the version of
+/// the file that wins conflicts (`second_wins`).
+pub fn convert_merge_to_csv(
+    first: (&str, &Profile),
+    second: (&str, &Profile),
+    output_csv: &str,
+    second_wins: bool,
+    run: &RunInfo,
+) -> Result<ConvertSummary> {
+    let mut summary = ConvertSummary {
+        run: run.clone(),
+        profiles: vec![ProfileStamp::of(first.1), ProfileStamp::of(second.1)],
+        ..Default::default()
+    };
+    let mut sources = Vec::new();
+    for (input_xlsx, profile) in [first, second] {
+        let (range, formulas) = read_first_sheet(input_xlsx, profile)?;
+        let mut rows = skip_boilerplate(range.rows(), profile);
+        let assumed = assumed_header(profile);
+        let (header, first_row) = split_header(
+            &mut rows,
+            &assumed,
+            profile.skip_leading_rows,
+            sheet_top(&range),
+        );
+        let cols = header_columns(input_xlsx, header, profile)?;
+        check_profile_columns(&cols, profile)?;
+        check_fixed_values(profile)?;
+        check_strict(&cols, profile, &mut summary)?;
+        note_duplicate_columns(input_xlsx, header, profile, &mut summary);
+        note_macros(input_xlsx, &mut summary);
+        note_formulas(input_xlsx, formulas, &mut summary);
+
+        let mut rows: Vec<SheetRow> = rows
+            .enumerate()
+            .map(|(i, row)| (i + first_row, row))
+            .collect();
+        check_swapped(&cols, &rows)?;
+        summary.empty_columns.extend(empty_columns(&cols, &rows));
+        check_rijksregister(&cols, &rows, profile, &mut summary)?;
+        check_gemeente(&cols, &rows, profile, &mut summary);
+        sort_rows(&cols, &mut rows, profile.sort);
+        let rejected = summary.rejected.len();
+        sources.push(Pipeline::for_profile(&cols, profile)?.run(&rows, &mut summary));
+        check_rejected(rows.len(), summary.rejected.len() - rejected, profile)?;
+    }
+    let second_records = sources.pop().unwrap_or_default();
+    let mut records = sources.pop().unwrap_or_default();
+
+    let by_tel_ref = NumberIndex::new(&records);
+
+    let (mut duplicates, mut conflicts) = (0, 0);
+    for record in second_records {
+        let number = dedup::contact_number(&record.1);
+        match by_tel_ref.get(&records, number) {
+            Some(i) => {
+                duplicates += 1;
+                if records[i].1 != record.1 {
+                    conflicts += 1;
+                    if second_wins {
+                        records[i] = record;
+                    }
+                }
+            }
+            _ => records.push(record),
+        }
+    }
+    if duplicates > 0 {
+        summary.warnings.push(if second_wins {
+            format!(
+                "{} contacts in both files, {} with different details (kept the second file's version)",
+                duplicates, conflicts
+            )
+        } else {
+            format!(
+                "{} contacts in both files, {} with different details (kept the first file's version)",
+                duplicates, conflicts
+            )
+        });
+    }
+
+    if first.1.reproducible {
+        sort_reproducible(&mut records);
+    }
+    check_quota(records.len(), first.1, &mut summary);
+
+    // The report is named after the first file
+    let prepared = PreparedExport {
+        source: format!("{} + {}", first.0, second.0),
+        inputs: vec![PathBuf::from(first.0), PathBuf::from(second.0)],
+        profile: first.1.name.clone(),
+        header: first.1.header,
+        line_ending: first.1.line_ending,
+        template: first.1.template.clone(),
+        // Two sources with different columns: the rejected rows carry their cells only
+        input_header: Vec::new(),
+        // A merge has no previous run to compare with
+        delete_file: false,
+        expected_tolerance: first.1.expected_tolerance_percent,
+        records,
+        summary,
+    };
+    let output = Path::new(output_csv);
+    let report = report::report_path(
+        output.parent().unwrap_or(Path::new(".")),
+        &file_stem(first.0),
+    );
+    write_export_with_report(prepared, output_csv, &report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{PhoneChannel, UnknownPhonePolicy};
+    use rust_xlsxwriter::Workbook;
+
+    fn header(names: &[&str]) -> Vec<Data> {
+        names
+            .iter()
+            .map(|name| match *name {
+                "" => Data::Empty,
+                name => Data::String(name.to_string()),
+            })
+            .collect()
+    }
+
+    const LIST: [&str; 6] = REQUIRED_COLUMNS;
+
+    #[test]
+    fn duplicate_headers_lists_repeated_names_in_header_order() {
+        let row = header(&["Naam", "GSM", " Naam ", "", "", "GSM", "Naam"]);
+        assert_eq!(
+            duplicate_headers(&row),
+            vec![
+                ("Naam".to_string(), vec![0, 2, 6]),
+                ("GSM".to_string(), vec![1, 5]),
+            ]
+        );
+        assert!(duplicate_headers(&header(&LIST)).is_empty());
+    }
+
+    #[test]
+    fn reads_column_follows_the_profile() {
+        let mut profile = Profile::default();
+        assert!(reads_column(&profile, "Naam"));
+        assert!(reads_column(&profile, LANDLINE_COLUMN));
+        assert!(!reads_column(&profile, "Geslacht"));
+        assert!(!reads_column(&profile, GEBOORTEDATUM_COLUMN));
+
+        profile.min_age = Some(18);
+        profile.zone_libre_columns = vec!["Wijk".to_string()];
+        profile
+            .columns
+            .insert("Naam".to_string(), "Familienaam".to_string());
+        assert!(reads_column(&profile, GEBOORTEDATUM_COLUMN));
+        assert!(reads_column(&profile, "Wijk"));
+        // Renamed: read from the other header, not from "Naam"
+        assert!(reads_column(&profile, "Familienaam"));
+        assert!(!reads_column(&profile, "Naam"));
+    }
+
+    #[test]
+    fn header_columns_needs_a_choice_for_a_duplicate_it_reads() {
+        let mut names = LIST.to_vec();
+        names.push("Naam");
+        let row = header(&names);
+        let mut profile = Profile::default();
+        match header_columns("dup.xlsx", Some(&row), &profile) {
+            Err(ConvertError::AmbiguousColumn { name, columns }) => {
+                assert_eq!((name.as_str(), columns), ("Naam", vec![1, 6]));
+            }
+            other => panic!("expected an ambiguous column, got {:?}", other),
+        }
+
+        profile.duplicate_columns.insert("Naam".to_string(), 2);
+        let cols = header_columns("dup.xlsx", Some(&row), &profile).unwrap();
+        assert_eq!(cols["Naam"], 6);
+
+        // Out of range
+        profile.duplicate_columns.insert("Naam".to_string(), 3);
+        match header_columns("dup.xlsx", Some(&row), &profile) {
+            Err(ConvertError::InvalidProfileValue {
+                field,
+                value,
+                expected,
+            }) => {
+                assert_eq!(field, "duplicate_columns.Naam");
+                assert_eq!((value.as_str(), expected.as_str()), ("3", "1 to 2"));
+            }
+            other => panic!("expected a refused profile, got {:?}", other),
+        }
+        profile.duplicate_columns.insert("Naam".to_string(), 0);
+        assert!(header_columns("dup.xlsx", Some(&row), &profile).is_err());
+    }
+
+    #[test]
+    fn a_duplicate_the_profile_renames_away_is_not_ambiguous() {
+        let mut names = LIST.to_vec();
+        names.extend(["Naam", "Familienaam", "Geslacht", "Geslacht"]);
+        let row = header(&names);
+        let mut profile = Profile::default();
+        profile
+            .columns
+            .insert("Naam".to_string(), "Familienaam".to_string());
+        let cols = header_columns("dup.xlsx", Some(&row), &profile).unwrap();
+        // Naam is read from Familienaam; the two Naam and Geslacht columns are not read
+        assert_eq!(cols["Naam"], 7);
+    }
+
+    /// A workbook whose list starts at `(row, column)` (0-based), not at A1.
+    fn xlsx_at(row: u32, column: u16, rows: &[&[&str]]) -> Vec<u8> {
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        for (r, cells) in rows.iter().enumerate() {
+            for (c, value) in cells.iter().enumerate() {
+                if !value.is_empty() {
+                    sheet
+                        .write_string(row + r as u32, column + c as u16, *value)
+                        .unwrap();
+                }
+            }
+        }
+        workbook.save_to_buffer().unwrap()
+    }
+
+    #[test]
+    fn datetime_text_keeps_the_date_and_minutes_format() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        assert_eq!(
+            datetime_text(date.and_hms_opt(0, 0, 0).unwrap()),
+            "01/03/2025"
+        );
+        assert_eq!(
+            datetime_text(date.and_hms_opt(14, 30, 0).unwrap()),
+            "01/03/2025 14:30"
+        );
+        assert_eq!(
+            datetime_text(date.and_hms_opt(14, 30, 59).unwrap()),
+            "01/03/2025 14:30"
+        );
+    }
+
+    #[test]
+    fn iso_datetime_reads_dates_times_and_both() {
+        assert_eq!(
+            iso_datetime("2025-03-01T14:30:00").as_deref(),
+            Some("01/03/2025 14:30")
+        );
+        assert_eq!(
+            iso_datetime("2025-03-01T00:00:00.000").as_deref(),
+            Some("01/03/2025")
+        );
+        assert_eq!(iso_datetime(" 2025-03-01 ").as_deref(), Some("01/03/2025"));
+        assert_eq!(iso_datetime("14:30:05").as_deref(), Some("14:30:05"));
+        assert_eq!(iso_datetime("2025-02-30"), None);
+        assert_eq!(iso_datetime("01/03/2025"), None);
+    }
+
+    #[test]
+    fn iso_duration_counts_days_and_time() {
+        assert_eq!(iso_duration("PT1H30M"), Some(5400));
+        assert_eq!(iso_duration("P1DT2H15M30S"), Some(94_530));
+        assert_eq!(iso_duration("P1W"), Some(604_800));
+        assert_eq!(iso_duration("PT0.5S"), Some(1));
+        assert_eq!(
+            iso_duration("PT90M").map(duration_text).as_deref(),
+            Some("1:30:00")
+        );
+        // No part, a year or month, a unit without number, no P
+        for bad in ["P", "PT", "P1Y", "P2M", "PTH", "1H30M", "PT1H30"] {
+            assert_eq!(iso_duration(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn no_review_file_without_problems() {
+        let dir = std::env::temp_dir().join(format!("be-alert-review-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("alken_report.html");
+        let mut summary = ConvertSummary::default();
+        write_reports(&report, "alken.xlsx", &[], &mut summary, LineEnding::Crlf).unwrap();
+        assert_eq!(summary.report.as_deref(), Some(report.as_path()));
+        assert_eq!(summary.review_file, None);
+        assert!(!report::review_path(&report).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_rejected_number_is_labelled_with_its_channel() {
+        let xlsx = xlsx_at(
+            0,
+            0,
+            &[
+                &[
+                    "Voornaam",
+                    "Naam",
+                    "Straat",
+                    "Huisnummer",
+                    "Mobiel nummer",
+                    "E-mailadres",
+                    LANDLINE_COLUMN,
+                ],
+                &["Jan", "Peeters", "Kerkstraat", "12", "", "", "999"],
+                &["An", "Smets", "Kerkstraat", "14", "999", "", ""],
+            ],
+        );
+        let mut profile = Profile {
+            phone_channels: vec![PhoneChannel::Landline, PhoneChannel::Mobile],
+            unknown_phone: UnknownPhonePolicy::Reject,
+            ..Profile::default()
+        };
+        let export = convert_xlsx_bytes("channels.xlsx", &xlsx, &profile).unwrap();
+        let fields: Vec<&str> = export
+            .summary
+            .rejected
+            .iter()
+            .map(|r| r.field.as_str())
+            .collect();
+        assert_eq!(fields, [LANDLINE_COLUMN, "Mobiel nummer"]);
+
+        profile.phone_channels.clear();
+        match convert_xlsx_bytes("channels.xlsx", &xlsx, &profile) {
+            Err(ConvertError::InvalidProfileValue { field, .. }) => {
+                assert_eq!(field, "phone_channels")
+            }
+            other => panic!("expected a refused profile, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn the_review_and_audit_name_the_channel_of_an_unknown_number() {
+        let xlsx = xlsx_at(
+            0,
+            0,
+            &[
+                &[
+                    "Voornaam",
+                    "Naam",
+                    "Straat",
+                    "Huisnummer",
+                    "Mobiel nummer",
+                    "E-mailadres",
+                    LANDLINE_COLUMN,
+                ],
+                &["Jan", "Peeters", "Kerkstraat", "12", "", "", "999"],
+            ],
+        );
+        let profile = Profile {
+            phone_channels: vec![PhoneChannel::Landline, PhoneChannel::Mobile],
+            ..Profile::default()
+        };
+        let export = convert_xlsx_bytes("channels.xlsx", &xlsx, &profile).unwrap();
+        assert_eq!(export.summary.phone_audit[0].channel, LANDLINE_COLUMN);
+        let lines = report::review_lines(&export.summary);
+        let columns: Vec<&str> = lines.iter().map(|l| l.column.as_str()).collect();
+        assert_eq!(columns, [LANDLINE_COLUMN]);
+        let html = report::html("channels.xlsx", &[], &export.summary);
+        assert!(html.contains(&format!("<td>{}</td><td>999</td>", LANDLINE_COLUMN)));
+    }
+
+    #[test]
+    fn the_phone_audit_is_masked_like_the_csv() {
+        let xlsx = xlsx_at(
+            0,
+            0,
+            &[
+                &[
+                    "Voornaam",
+                    "Naam",
+                    "Straat",
+                    "Huisnummer",
+                    "Mobiel nummer",
+                    "E-mailadres",
+                ],
+                &[
+                    "Jan",
+                    "Peeters 85.07.30-033.28",
+                    "Kerkstraat",
+                    "12",
+                    "85.07.30-033.28",
+                    "",
+                ],
+            ],
+        );
+        let profile = Profile {
+            rijksregister: RijksregisterPolicy::Mask,
+            ..Profile::default()
+        };
+        let export = convert_xlsx_bytes("masked.xlsx", &xlsx, &profile).unwrap();
+        let audit = &export.summary.phone_audit[0];
+        for text in [&audit.name, &audit.original, &audit.normalized] {
+            assert!(!rijksregister::contains(text), "{}", text);
+            assert!(!text.contains("033"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn cell_warnings_are_masked_in_every_report() {
+        let xlsx = xlsx_at(
+            0,
+            0,
+            &[
+                &[
+                    "Voornaam",
+                    "Naam",
+                    "Straat",
+                    "Huisnummer",
+                    "Mobiel nummer",
+                    "E-mailadres",
+                    "Aantal inwoners",
+                ],
+                &[
+                    "Jan",
+                    "Peeters",
+                    "Kerkstraat",
+                    "12",
+                    "0475 12 34 56",
+                    "",
+                    "85.07.30-033.28",
+                ],
+            ],
+        );
+        let profile = Profile {
+            rijksregister: RijksregisterPolicy::Mask,
+            ..Profile::default()
+        };
+        let export = convert_xlsx_bytes("masked.xlsx", &xlsx, &profile).unwrap();
+        let summary = &export.summary;
+        assert_eq!(summary.cell_warnings[0].field, "Aantal inwoners");
+        let review: String = report::review_lines(summary)
+            .iter()
+            .map(|l| format!("{};{};{};{}", l.column, l.value, l.problem, l.fix))
+            .collect();
+        let outputs = [
+            report::html("masked.xlsx", &[], summary),
+            review,
+            summary.to_json().to_string(),
+        ];
+        for text in outputs {
+            assert!(!text.contains("033"), "{}", text);
+            assert!(!text.contains("8507"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn a_preset_profile_without_fixed_values_is_refused() {
+        let xlsx = xlsx_at(
+            0,
+            0,
+            &[
+                &["Voornaam", "Familienaam", "Adres", "GSM", "E-mail"],
+                &["Jan", "Peeters", "Kerkstraat 12", "0475 12 34 56", ""],
+            ],
+        );
+        let profile = crate::presets::PRESETS[0].profile();
+        match convert_xlsx_bytes("cevi.xlsx", &xlsx, &profile) {
+            Err(ConvertError::InvalidProfileValue { field, .. }) => assert_eq!(field, "postcode"),
+            other => panic!("expected a refused profile, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    /// As xlsx_at from A1, with formulas at (row, column) saved without a
+    /// result, as some tools do (rust_xlsxwriter saves 0).
+    fn xlsx_with_formulas(rows: &[&[&str]], formulas: &[(u32, u16, &str)]) -> Vec<u8> {
+        use std::io::Read as _;
+        use zip::write::SimpleFileOptions;
+
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        for (r, cells) in rows.iter().enumerate() {
+            for (c, value) in cells.iter().enumerate() {
+                if !value.is_empty() {
+                    sheet.write_string(r as u32, c as u16, *value).unwrap();
+                }
+            }
+        }
+        for &(row, column, formula) in formulas {
+            sheet.write_formula(row, column, formula).unwrap();
+        }
+        let xlsx = workbook.save_to_buffer().unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(xlsx)).unwrap();
+        let mut out = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).unwrap();
+            let mut text = String::new();
+            file.read_to_string(&mut text).unwrap();
+            out.start_file(file.name(), SimpleFileOptions::default())
+                .unwrap();
+            out.write_all(text.replace("</f><v>0</v>", "</f>").as_bytes())
+                .unwrap();
+        }
+        out.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn formula_warnings_skip_the_boilerplate_rows_and_group_per_column() {
+        let xlsx = xlsx_with_formulas(
+            &[
+                &["Bevolkingsregister Alken"],
+                &[
+                    "Voornaam",
+                    "Naam",
+                    "Straat",
+                    "Huisnummer",
+                    "Mobiel nummer",
+                    "E-mailadres",
+                ],
+                &["Jan", "Peeters", "Kerkstraat", "12", "", ""],
+                &["An", "Smets", "Kerkstraat", "14", "", ""],
+                &["Totaal"],
+            ],
+            &[
+                (0, 4, "TODAY()"),
+                (2, 4, "\"0475\"&\"123456\""),
+                (3, 4, "\"0475\"&\"654321\""),
+                (3, 5, "LOWER(B4)"),
+                (4, 4, "COUNTA(E3:E4)"),
+            ],
+        );
+        let profile = Profile {
+            skip_leading_rows: 1,
+            skip_trailing_rows: 1,
+            ..Profile::default()
+        };
+        let export = convert_xlsx_bytes("title.xlsx", &xlsx, &profile).unwrap();
+        let warnings: Vec<_> = export
+            .summary
+            .cell_warnings
+            .iter()
+            .filter(|w| w.value.starts_with('='))
+            .collect();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!((warnings[0].row, warnings[0].column), (3, Some(4)));
+        assert_eq!(warnings[0].field, "Mobiel nummer");
+        assert!(
+            warnings[0].message.starts_with("2 formulas in E3:E4"),
+            "{}",
+            warnings[0].message
+        );
+        assert_eq!((warnings[1].row, warnings[1].column), (4, Some(5)));
+        assert!(export
+            .summary
+            .warnings
+            .iter()
+            .any(|w| w.ends_with(": 2 Mobiel nummer, 1 E-mailadres")));
+    }
+
+    #[test]
+    fn sheet_with_formulas_reads_the_values_as_first_sheet() {
+        let xlsx = xlsx_at(2, 1, &[&["Naam", "", "GSM"], &["Peeters", "", "0475"]]);
+        let mut workbook = Xlsx::new(Cursor::new(xlsx.as_slice())).unwrap();
+        let (range, formulas) =
+            sheet_with_formulas("plain.xlsx", &mut workbook, &Profile::default()).unwrap();
+        assert!(formulas.is_empty());
+        let mut workbook = Xlsx::new(Cursor::new(xlsx.as_slice())).unwrap();
+        let expected = first_sheet("plain.xlsx", &mut workbook).unwrap();
+        assert_eq!(
+            (range.start(), range.end()),
+            (expected.start(), expected.end())
+        );
+        assert!(range.cells().eq(expected.cells()));
+    }
+
+    #[test]
+    fn cells_are_named_from_a1_when_the_list_starts_lower() {
+        let xlsx = xlsx_at(
+            2,
+            2,
+            &[
+                &[
+                    "Voornaam",
+                    "Naam",
+                    "Straat",
+                    "Huisnummer",
+                    "Mobiel nummer",
+                    "E-mailadres",
+                ],
+                &["Jan", "Peeters", "Kerkstraat", "12", "0475 12 34 56", ""],
+                &["An", "Smets", "", "3", "0475 99 88 77", ""],
+            ],
+        );
+        let export = convert_xlsx_bytes("offset.xlsx", &xlsx, &Profile::default()).unwrap();
+        // No Straat: the cell warning names the empty cell
+        let warning = &export.summary.cell_warnings[0];
+        assert_eq!(warning.row, 5);
+        assert_eq!(report::cell_ref(warning.row, warning.column), "E5");
+    }
+}
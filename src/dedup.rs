@@ -0,0 +1,187 @@
+// src/dedup.rs
+// Duplicate contacts within one export
+// ------------------------------------
+//
+// BE-Alert identifies a contact by its Tel/Ref., so rows sharing a Tel/Ref.
+// (household members on one number, rows entered twice) form a duplicate
+// group. The user decides per group what to keep before the CSV is written.
+// A contact preferring SMS has its number in the SMS field instead (see
+// `contact_number`).
+//
+// Province files run to a million rows, so numbers are indexed by a 64-bit
+// hash instead of a copy of the text (see NumberIndex); only numbers seen
+// more than once are compared as text. The records themselves are in memory
+// anyway, so the index is not moved to disk.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Output record with its 1-based Excel row number.
+pub type Record = (usize, Vec<String>);
+
+/// Number identifying a contact: Tel/Ref., or SMS for contacts that chose
+/// SMS ("SMS voorkeur") and have no Tel/Ref. Empty for e-mail only contacts.
+pub fn contact_number(record: &[String]) -> &str {
+    // Output fields: Tel/Ref., SMS
+    if record[0].is_empty() {
+        &record[21]
+    } else {
+        &record[0]
+    }
+}
+
+fn number_hash(number: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    number.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// First record per contact number, keyed by the number's hash: 12 bytes
+/// per contact instead of the number's text. Numbers whose hash is taken by
+/// another number go to `collided`.
+#[derive(Default)]
+pub struct NumberIndex {
+    by_hash: HashMap<u64, u32>,
+    collided: HashMap<String, u32>,
+}
+
+impl NumberIndex {
+    /// Index of the records with a (non-empty) contact number.
+    pub fn new(records: &[Record]) -> Self {
+        let mut index = NumberIndex::default();
+        for i in 0..records.len() {
+            index.insert(records, i);
+        }
+        index
+    }
+
+    /// Add `records[i]`, unless its number is already indexed.
+    pub fn insert(&mut self, records: &[Record], i: usize) {
+        let number = contact_number(&records[i].1);
+        if number.is_empty() {
+            return;
+        }
+        let first = *self.by_hash.entry(number_hash(number)).or_insert(i as u32);
+        if contact_number(&records[first as usize].1) != number {
+            self.collided.entry(number.to_string()).or_insert(i as u32);
+        }
+    }
+
+    /// First indexed record with this (non-empty) contact number.
+    pub fn get(&self, records: &[Record], number: &str) -> Option<usize> {
+        if number.is_empty() {
+            return None;
+        }
+        match self.by_hash.get(&number_hash(number)) {
+            Some(&i) if contact_number(&records[i as usize].1) == number => Some(i as usize),
+            Some(_) => self.collided.get(number).map(|&i| i as usize),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAction {
+    KeepFirst,
+    /// Keep only the n-th member of the group.
+    Keep(usize),
+    /// Keep the first member, filling its empty fields from the others.
+    Merge,
+    KeepAll,
+}
+
+pub struct DuplicateGroup {
+    pub tel_ref: String,
+    /// Indexes into the record list, in output order.
+    pub members: Vec<usize>,
+}
+
+/// Groups of two or more records with the same (non-empty) Tel/Ref.,
+/// in order of first appearance.
+pub fn find_groups(records: &[Record]) -> Vec<DuplicateGroup> {
+    // First pass: the hashes seen more than once
+    let mut seen: HashMap<u64, bool> = HashMap::new();
+    for (_, record) in records {
+        let tel_ref = contact_number(record);
+        if !tel_ref.is_empty() {
+            seen.entry(number_hash(tel_ref))
+                .and_modify(|again| *again = true)
+                .or_insert(false);
+        }
+    }
+    seen.retain(|_, again| *again);
+
+    // Second pass: group the numbers that may occur twice by their text
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    let mut by_tel_ref: HashMap<&str, usize> = HashMap::new();
+    for (i, (_, record)) in records.iter().enumerate() {
+        let tel_ref = contact_number(record);
+        if tel_ref.is_empty() || !seen.contains_key(&number_hash(tel_ref)) {
+            continue;
+        }
+        match by_tel_ref.get(tel_ref) {
+            Some(&g) => groups[g].members.push(i),
+            None => {
+                by_tel_ref.insert(tel_ref, groups.len());
+                groups.push(DuplicateGroup {
+                    tel_ref: tel_ref.to_string(),
+                    members: vec![i],
+                });
+            }
+        }
+    }
+    groups.retain(|g| g.members.len() > 1);
+    groups
+}
+
+fn merged(records: &[Record], members: &[usize]) -> Record {
+    let (row, mut fields) = records[members[0]].clone();
+    for (j, field) in fields.iter_mut().enumerate() {
+        if field.is_empty() {
+            if let Some(value) = members[1..]
+                .iter()
+                .map(|&m| &records[m].1[j])
+                .find(|v| !v.is_empty())
+            {
+                *field = value.clone();
+            }
+        }
+    }
+    (row, fields)
+}
+
+/// Apply one action per group. Returns the remaining records (output order
+/// kept) and the number of records removed.
+pub fn apply(
+    records: Vec<Record>,
+    groups: &[DuplicateGroup],
+    actions: &[DuplicateAction],
+) -> (Vec<Record>, usize) {
+    let mut removed: HashSet<usize> = HashSet::new();
+    let mut replaced: HashMap<usize, Record> = HashMap::new();
+
+    for (group, action) in groups.iter().zip(actions) {
+        let members = &group.members;
+        match *action {
+            DuplicateAction::KeepFirst => removed.extend(&members[1..]),
+            DuplicateAction::Keep(n) => {
+                let keep = members.get(n).copied().unwrap_or(members[0]);
+                removed.extend(members.iter().filter(|&&m| m != keep));
+            }
+            DuplicateAction::Merge => {
+                replaced.insert(members[0], merged(&records, members));
+                removed.extend(&members[1..]);
+            }
+            DuplicateAction::KeepAll => {}
+        }
+    }
+
+    let kept = records
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !removed.contains(i))
+        .map(|(i, record)| replaced.remove(&i).unwrap_or(record))
+        .collect();
+    (kept, removed.len())
+}
@@ -0,0 +1,175 @@
+// src/dialect.rs
+// CSV input dialect
+// -----------------
+//
+// Contact lists saved as CSV (command line `--from csv`) come from Excel in
+// any locale and from register software: separated by ';', ',', tab or '|',
+// values quoted with '"' or '\'', with or without a header row. The dialect
+// is sniffed from the first SNIFF_BYTES of the file:
+//
+// - separator and quote: the combination that splits the most lines into the
+//   same number (more than one) of fields; ';' and '"' on a tie
+// - header: a first line with none of the expected column names but a phone
+//   number or e-mail address in it is data (see headerless.rs)
+//
+// Each part can be overridden (`--delimiter`, `--quote`, `--no-header`). The
+// separator of an example or exported BE-Alert CSV (template.rs, verify.rs)
+// is sniffed the same way.
+
+use crate::headerless;
+use calamine::Data;
+use std::collections::HashMap;
+
+/// Bytes of the file the dialect is sniffed from.
+const SNIFF_BYTES: usize = 16 * 1024;
+
+const DELIMITERS: [u8; 4] = [b';', b',', b'\t', b'|'];
+const QUOTES: [u8; 2] = [b'"', b'\''];
+
+/// How a CSV contact list is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    /// The first line holds the column names.
+    pub has_header: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: b';',
+            quote: b'"',
+            has_header: true,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// "separator ';', quote '"', header row", for messages.
+    pub fn text(&self) -> String {
+        let delimiter = match self.delimiter {
+            b'\t' => "tab".to_string(),
+            d => format!("'{}'", d as char),
+        };
+        format!(
+            "separator {}, quote '{}', {}",
+            delimiter,
+            self.quote as char,
+            if self.has_header {
+                "header row"
+            } else {
+                "no header row"
+            }
+        )
+    }
+}
+
+/// Fields per line of `sample` read with one dialect.
+fn field_counts(sample: &str, delimiter: u8, quote: u8) -> Vec<usize> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(sample.as_bytes())
+        .records()
+        .map_while(|record| record.ok().map(|r| r.len()))
+        .collect()
+}
+
+/// Lines sharing the most frequent field count, when that count is over 1.
+fn consistency(counts: &[usize]) -> usize {
+    let mut lines: HashMap<usize, usize> = HashMap::new();
+    for &count in counts.iter().filter(|&&count| count > 1) {
+        *lines.entry(count).or_default() += 1;
+    }
+    lines.into_values().max().unwrap_or(0)
+}
+
+/// Dialect of a CSV file's text (see the top of this file).
+pub fn sniff(text: &str) -> CsvDialect {
+    // Whole lines only: the last one may be cut off
+    let sample = if text.len() > SNIFF_BYTES {
+        let mut end = SNIFF_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        &text[..text[..end].rfind('\n').unwrap_or(end)]
+    } else {
+        text
+    };
+    let mut best = (CsvDialect::default(), 0);
+    for delimiter in DELIMITERS {
+        for quote in QUOTES {
+            let score = consistency(&field_counts(sample, delimiter, quote));
+            if score > best.1 {
+                let dialect = CsvDialect {
+                    delimiter,
+                    quote,
+                    has_header: true,
+                };
+                best = (dialect, score);
+            }
+        }
+    }
+    let mut dialect = best.0;
+    let first = read_rows(sample, &dialect)
+        .ok()
+        .and_then(|rows| rows.into_iter().next());
+    dialect.has_header = !first.is_some_and(|row| headerless::looks_headerless(&row));
+    dialect
+}
+
+/// Rows of a CSV file's text as cells, every value text.
+pub fn read_rows(text: &str, dialect: &CsvDialect) -> Result<Vec<Vec<Data>>, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let cells = record?
+            .iter()
+            .map(|value| match value.trim() {
+                "" => Data::Empty,
+                _ => Data::String(value.to_string()),
+            })
+            .collect();
+        rows.push(cells);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delimiter(text: &str) -> char {
+        sniff(text).delimiter as char
+    }
+
+    #[test]
+    fn sniff_finds_the_separator_of_a_header_line() {
+        assert_eq!(delimiter("Tel/Ref.;Naam;Voornaam"), ';');
+        assert_eq!(delimiter("Tél/Réf.,Nom,Prénom"), ',');
+        assert_eq!(delimiter("Tel/Ref.\tNaam\tVoornaam"), '\t');
+        assert_eq!(delimiter("\"a,b,c\";Naam;Voornaam"), ';');
+        // Tie and no separator at all
+        assert_eq!(delimiter("a;b,c"), ';');
+        assert_eq!(delimiter("Naam"), ';');
+    }
+
+    #[test]
+    fn sniff_reads_at_most_sniff_bytes() {
+        // Lines of 64 bytes but 33 chars: a limit counted in chars would read
+        // more ';' lines past the limit than ',' lines before it
+        let line = format!("{},é\n", "é".repeat(30));
+        let mut text = line.repeat(SNIFF_BYTES / line.len());
+        // Past the limit the lines split on ';' instead
+        text.push_str(&"a;b;c;d\n".repeat(SNIFF_BYTES));
+        assert_eq!(delimiter(&text), ',');
+    }
+}
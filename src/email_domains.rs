@@ -0,0 +1,241 @@
+// src/email_domains.rs
+// Typos in e-mail domains
+// -----------------------
+//
+// An alert mailed to "jan@gmial.com" or "an@telnet.be" never arrives, and
+// nobody notices until the day it matters. The domain of every address is
+// compared (case-insensitive) with the common Belgian mail domains below,
+// those of the neighbouring countries, and the profile's `email_domains`; one
+// that is not among them but one typo away from exactly one of them (a letter
+// wrong, missing, extra or two swapped; two for the longer names) is reported
+// with that domain as the suggestion. When the top-level domain is a real
+// one, only the name before it is compared: "outlook.fr" or "hotmail.nl" is a
+// real address abroad, never a typo of "outlook.be". A top-level domain that
+// is not ("telenet.b", "gmail.con") is compared with the rest. What happens
+// next is the profile's `email_typos` policy (see pipeline.rs).
+
+const BUILT_IN: [&str; 44] = [
+    "gmail.com",
+    "googlemail.com",
+    "hotmail.com",
+    "hotmail.be",
+    "hotmail.fr",
+    "outlook.com",
+    "outlook.be",
+    "live.com",
+    "live.be",
+    "msn.com",
+    "yahoo.com",
+    "yahoo.fr",
+    "icloud.com",
+    "me.com",
+    "telenet.be",
+    "skynet.be",
+    "proximus.be",
+    "scarlet.be",
+    "belgacom.net",
+    "pandora.be",
+    "base.be",
+    "orange.be",
+    "voo.be",
+    "edpnet.be",
+    "hotmail.nl",
+    "hotmail.de",
+    "hotmail.co.uk",
+    "outlook.fr",
+    "outlook.nl",
+    "outlook.de",
+    "live.fr",
+    "live.nl",
+    "yahoo.de",
+    "yahoo.co.uk",
+    "orange.fr",
+    "free.fr",
+    "sfr.fr",
+    "wanadoo.fr",
+    "laposte.net",
+    "ziggo.nl",
+    "kpnmail.nl",
+    "gmx.de",
+    "gmx.net",
+    "web.de",
+];
+
+/// Real top-level domains besides those of the known domains: a domain
+/// ending in one of them only has its name compared.
+const OTHER_TLDS: [&str; 11] = [
+    "eu", "lu", "uk", "org", "info", "biz", "it", "es", "ch", "at", "pl",
+];
+
+/// Names (before the top-level domain) this long may be two typos away;
+/// shorter ones only one.
+const TWO_TYPOS_FROM: usize = 8;
+
+/// Known domains of one profile.
+pub struct EmailDomains {
+    domains: Vec<String>,
+}
+
+impl EmailDomains {
+    /// The built-in domains and the profile's `email_domains`.
+    pub fn new(profile_domains: &[String]) -> Self {
+        let mut domains: Vec<String> = BUILT_IN.iter().map(|d| d.to_string()).collect();
+        for domain in profile_domains {
+            let domain = domain.trim().trim_start_matches('@').to_lowercase();
+            if !domain.is_empty() && !domains.contains(&domain) {
+                domains.push(domain);
+            }
+        }
+        EmailDomains { domains }
+    }
+
+    /// The address with its domain corrected, when the domain looks like a
+    /// typo of a known one; None for known, unrelated or ambiguous domains.
+    pub fn suggest(&self, email: &str) -> Option<String> {
+        let (local, domain) = email.trim().rsplit_once('@')?;
+        let domain = domain.to_lowercase();
+        if local.is_empty() || domain.is_empty() || self.domains.contains(&domain) {
+            return None;
+        }
+        let (name, tld) = domain.rsplit_once('.')?;
+        let real_tld = OTHER_TLDS.contains(&tld)
+            || self
+                .domains
+                .iter()
+                .any(|d| d.rsplit_once('.').is_some_and(|(_, t)| t == tld));
+        let mut best: Option<(usize, &str)> = None;
+        let mut tie = false;
+        for known in &self.domains {
+            let Some((known_name, known_tld)) = known.rsplit_once('.') else {
+                continue;
+            };
+            if real_tld && known_tld != tld {
+                continue;
+            }
+            let limit = if known_name.len() >= TWO_TYPOS_FROM {
+                2
+            } else {
+                1
+            };
+            let distance = if real_tld {
+                typo_distance(name, known_name)
+            } else {
+                typo_distance(&domain, known)
+            };
+            if distance > limit {
+                continue;
+            }
+            match best {
+                Some((d, _)) if d < distance => {}
+                Some((d, _)) if d == distance => tie = true,
+                _ => {
+                    best = Some((distance, known));
+                    tie = false;
+                }
+            }
+        }
+        match best {
+            Some((_, known)) if !tie => Some(format!("{}@{}", local, known)),
+            _ => None,
+        }
+    }
+}
+
+/// Edits (replace, insert, delete, swap two neighbours) from `a` to `b`.
+fn typo_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typo_distance_counts_one_edit_per_typo() {
+        assert_eq!(typo_distance("gmail", "gmail"), 0);
+        assert_eq!(typo_distance("gmial", "gmail"), 1);
+        assert_eq!(typo_distance("gmai", "gmail"), 1);
+        assert_eq!(typo_distance("gmaill", "gmail"), 1);
+        assert_eq!(typo_distance("gnail", "gmail"), 1);
+        assert_eq!(typo_distance("hotmial", "hotmail"), 1);
+        assert_eq!(typo_distance("hotmal", "hotmail"), 1);
+        assert_eq!(typo_distance("googlemial", "googlemail"), 1);
+        assert_eq!(typo_distance("gogglemal", "googlemail"), 2);
+        assert_eq!(typo_distance("", "voo"), 3);
+    }
+
+    #[test]
+    fn suggest_corrects_the_name_of_a_known_domain() {
+        let domains = EmailDomains::new(&[]);
+        let suggest = |email| domains.suggest(email);
+        assert_eq!(suggest("jan@gmial.com").as_deref(), Some("jan@gmail.com"));
+        assert_eq!(suggest("an@Telnet.be").as_deref(), Some("an@telenet.be"));
+        assert_eq!(suggest("an@hotmial.de").as_deref(), Some("an@hotmail.de"));
+        assert_eq!(
+            suggest("an@gogglemal.com").as_deref(),
+            Some("an@googlemail.com")
+        );
+    }
+
+    #[test]
+    fn suggest_never_swaps_one_real_top_level_domain_for_another() {
+        let domains = EmailDomains::new(&[]);
+        for email in [
+            "an@outlook.fr",
+            "an@outlook.nl",
+            "an@hotmail.de",
+            "an@hotmail.co.uk",
+            "an@outlook.it",
+            "an@telenet.nl",
+            "an@gmail.be",
+        ] {
+            assert_eq!(domains.suggest(email), None, "{}", email);
+        }
+    }
+
+    #[test]
+    fn suggest_corrects_a_top_level_domain_that_is_not_real() {
+        let domains = EmailDomains::new(&[]);
+        let suggest = |email| domains.suggest(email);
+        assert_eq!(suggest("an@telenet.b").as_deref(), Some("an@telenet.be"));
+        assert_eq!(suggest("an@gmail.con").as_deref(), Some("an@gmail.com"));
+        assert_eq!(suggest("an@skynet.bee").as_deref(), Some("an@skynet.be"));
+        // A typo in the name as well is one too many for a short name
+        assert_eq!(suggest("an@gmial.con"), None);
+    }
+
+    #[test]
+    fn suggest_leaves_known_unrelated_and_ambiguous_domains() {
+        let domains = EmailDomains::new(&["@Alken.be".to_string()]);
+        assert_eq!(domains.suggest("jan@alken.be"), None);
+        assert_eq!(
+            domains.suggest("jan@alkn.be").as_deref(),
+            Some("jan@alken.be")
+        );
+        assert_eq!(domains.suggest("jan@example.com"), None);
+        // One typo from both me.com and msn.com
+        assert_eq!(domains.suggest("jan@mn.com"), None);
+        assert_eq!(domains.suggest("no-at-sign"), None);
+        assert_eq!(domains.suggest("@gmial.com"), None);
+    }
+}
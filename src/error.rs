@@ -0,0 +1,407 @@
+// src/error.rs
+// Error type shared by the GUI and the conversion code
+// ----------------------------------------------------
+//
+// Every failure the convertor can report is one of the ConvertError variants,
+// so callers can show a targeted message (or pick an exit code) by matching on
+// the kind instead of parsing an error string.
+
+use crate::report::{cell_ref, column_name};
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, ConvertError>;
+
+#[derive(Debug)]
+pub enum ConvertError {
+    /// A required input column is not present in the header row.
+    /// `found` lists the headers that were present, for the error message.
+    MissingColumn { name: String, found: Vec<String> },
+    /// A column the conversion reads appears more than once in the header row
+    /// (e.g. two "Naam", one the maiden name) and the profile's
+    /// `[duplicate_columns]` does not say which one. `columns` are 0-based.
+    AmbiguousColumn {
+        name: String,
+        columns: Vec<usize>,
+    },
+    /// Strict mode: the header row has columns the profile does not expect.
+    UnexpectedColumns { names: Vec<String> },
+    /// The phone and e-mail columns look swapped in the source (most phone
+    /// cells hold an address, or most e-mail cells a number).
+    SwappedColumns {
+        detail: String,
+    },
+    /// More rows failed validation than the profile's `max_rejected_percent`.
+    TooManyRejected {
+        rejected: usize,
+        rows: usize,
+        limit: u32,
+    },
+    /// The number of contacts converted is further from the number the
+    /// operator expected (RunInfo::expected_contacts) than the profile's
+    /// `expected_tolerance_percent`.
+    UnexpectedCount {
+        rows: usize,
+        expected: usize,
+        tolerance: u32,
+    },
+    /// The output path chosen is one of the input files (e.g. the XLSX
+    /// picked again with a .csv name typed over it).
+    OutputIsInput {
+        path: PathBuf,
+    },
+    /// The file is opened by another program (typically Excel).
+    FileLocked { path: PathBuf },
+    /// Another export is writing the same output right now (see lock.rs).
+    /// `holder` is who, where and since when, as written in the lock file.
+    OutputLocked {
+        path: PathBuf,
+        holder: String,
+    },
+    /// The CSV read back after writing is not what was written (disk full,
+    /// share dropped out); the file has been removed.
+    OutputIncomplete {
+        path: PathBuf,
+        reason: String,
+    },
+    /// The input is not a workbook we can read (wrong extension, corrupt zip, no sheet, ...).
+    UnsupportedFormat { path: PathBuf, reason: String },
+    /// A single data row could not be converted.
+    /// `row` is the 1-based row number as shown in Excel (header = row 1),
+    /// `column` the 0-based column.
+    RowError {
+        row: usize,
+        column: usize,
+        field: String,
+        kind: RowErrorKind,
+    },
+    /// A value of the profile (or of the session) that cannot be used, e.g.
+    /// `taal = "NLD"`, which BE-Alert would refuse.
+    InvalidProfileValue {
+        field: String,
+        value: String,
+        expected: String,
+    },
+    /// The profile's script cannot be loaded (see script.rs).
+    Script {
+        path: PathBuf,
+        message: String,
+    },
+    /// The conversion was stopped before its files were moved into place (a
+    /// batch file over its timeout); nothing was written.
+    Cancelled,
+    IoError(io::Error),
+}
+
+/// Exit code of the command line, so a script or the scheduler can tell
+/// which team to alert without reading the messages. In the JSON report as
+/// `"validation"`, `"missing_columns"`, ...
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitKind {
+    /// 0: everything converted.
+    Success,
+    /// 1: rows, profile values or command line arguments that cannot be
+    /// used (the list or the profile needs fixing).
+    Validation,
+    /// 2: the header row lacks required columns, or has unexpected, swapped
+    /// or duplicated ones (the register export changed).
+    MissingColumns,
+    /// 3: a file that cannot be read or written: missing, locked, corrupt,
+    /// disk full, share gone.
+    Io,
+    /// 4: the conversion was stopped (timed out).
+    Cancelled,
+}
+
+impl ExitKind {
+    pub fn code(self) -> u8 {
+        match self {
+            ExitKind::Success => 0,
+            ExitKind::Validation => 1,
+            ExitKind::MissingColumns => 2,
+            ExitKind::Io => 3,
+            ExitKind::Cancelled => 4,
+        }
+    }
+
+    /// Name in the final status line and the JSON report.
+    pub fn name(self) -> &'static str {
+        match self {
+            ExitKind::Success => "ok",
+            ExitKind::Validation => "validation",
+            ExitKind::MissingColumns => "missing_columns",
+            ExitKind::Io => "io",
+            ExitKind::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowErrorKind {
+    /// The field is required but the cell is empty.
+    Empty,
+    /// The cell has a value that cannot be used in the output.
+    Invalid { value: String, reason: String },
+    /// The cell holds what looks like a rijksregisternummer.
+    NationalRegisterNumber,
+    /// The profile script dropped the row or failed on it.
+    Script(String),
+}
+
+impl fmt::Display for RowErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowErrorKind::Empty => write!(f, "empty value"),
+            RowErrorKind::Invalid { value, reason } => write!(f, "\"{}\" {}", value, reason),
+            RowErrorKind::NationalRegisterNumber => write!(
+                f,
+                "looks like a rijksregisternummer. This data must never be uploaded to BE-Alert (GDPR); remove it from the source"
+            ),
+            RowErrorKind::Script(message) => write!(f, "profile script: {}", message),
+        }
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::MissingColumn { name, found } => {
+                write!(f, "Missing required XLSX column: {}", name)?;
+                if found.is_empty() {
+                    write!(f, " (header row is empty)")
+                } else {
+                    write!(f, " (found: {})", found.join(", "))
+                }
+            }
+            ConvertError::AmbiguousColumn { name, columns } => write!(
+                f,
+                "XLSX column \"{}\" appears {} times (columns {}). Choose the one to read in the profile's [duplicate_columns] (\"{}\" = 1 for column {}) and try again.",
+                name,
+                columns.len(),
+                columns
+                    .iter()
+                    .map(|&c| column_name(c))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                name,
+                columns.first().map(|&c| column_name(c)).unwrap_or_default()
+            ),
+            ConvertError::UnexpectedColumns { names } => write!(
+                f,
+                "Unexpected XLSX columns (strict mode): {}. Is this the right register export?",
+                names.join(", ")
+            ),
+            ConvertError::SwappedColumns { detail } => write!(
+                f,
+                "Columns look swapped in the source: {}. Fix the XLSX (or the profile's [columns]) and try again.",
+                detail
+            ),
+            ConvertError::TooManyRejected {
+                rejected,
+                rows,
+                limit,
+            } => write!(
+                f,
+                "{} of {} rows failed validation, more than the profile allows ({}%). Nothing exported; check the source.",
+                rejected, rows, limit
+            ),
+            ConvertError::UnexpectedCount {
+                rows,
+                expected,
+                tolerance,
+            } => write!(
+                f,
+                "{} contacts converted where the register counts {}, more than {}% off. Nothing exported; is this the complete export?",
+                rows, expected, tolerance
+            ),
+            ConvertError::OutputIsInput { path } => write!(
+                f,
+                "{} is the input file. Choose another name for the output; the source is not overwritten.",
+                path.display()
+            ),
+            ConvertError::FileLocked { path } => write!(
+                f,
+                "{} is opened by another program. Close it (e.g. in Excel) and try again.",
+                path.display()
+            ),
+            ConvertError::OutputLocked { path, holder } => {
+                write!(f, "{} is being written by another export", path.display())?;
+                if !holder.is_empty() {
+                    write!(f, " ({})", holder)?;
+                }
+                write!(f, ". Wait until it is done and try again, or choose another name.")
+            }
+            ConvertError::OutputIncomplete { path, reason } => write!(
+                f,
+                "{} was not written completely ({}) and has been removed. Check the free space on the disk or share and export again.",
+                path.display(),
+                reason
+            ),
+            ConvertError::UnsupportedFormat { path, reason } => {
+                write!(f, "Cannot read {}: {}", path.display(), reason)
+            }
+            ConvertError::RowError {
+                row,
+                column,
+                field,
+                kind,
+            } => write!(
+                f,
+                "Cell {} (column \"{}\"): {}",
+                cell_ref(*row, Some(*column)),
+                field,
+                kind
+            ),
+            ConvertError::InvalidProfileValue {
+                field,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Profile value {} \"{}\" is not valid (expected {}). Fix the profile and try again.",
+                field, value, expected
+            ),
+            ConvertError::Script { path, message } => {
+                write!(f, "Profile script {}: {}", path.display(), message)
+            }
+            ConvertError::Cancelled => write!(f, "Conversion stopped; nothing was written."),
+            ConvertError::IoError(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConvertError::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConvertError {
+    fn from(e: io::Error) -> Self {
+        ConvertError::IoError(e)
+    }
+}
+
+impl From<csv::Error> for ConvertError {
+    fn from(e: csv::Error) -> Self {
+        ConvertError::IoError(e.into())
+    }
+}
+
+/// Windows reports a file held open by Excel as a sharing (32) or lock (33) violation.
+pub fn is_lock_violation(e: &io::Error) -> bool {
+    cfg!(target_os = "windows") && matches!(e.raw_os_error(), Some(32) | Some(33))
+}
+
+/// Windows reports a network share that dropped out as a missing network path
+/// (53), an unexpected network error (59) or a network name no longer
+/// available (64).
+fn is_share_lost(e: &io::Error) -> bool {
+    cfg!(target_os = "windows") && matches!(e.raw_os_error(), Some(53) | Some(59) | Some(64))
+}
+
+impl ConvertError {
+    /// Attach the path to an I/O error, turning sharing violations into `FileLocked`.
+    pub fn io_at(path: impl Into<PathBuf>, e: io::Error) -> Self {
+        if is_lock_violation(&e) {
+            ConvertError::FileLocked { path: path.into() }
+        } else {
+            ConvertError::IoError(e)
+        }
+    }
+
+    /// Exit code of a command line run that failed with this error.
+    pub fn exit_kind(&self) -> ExitKind {
+        match self {
+            ConvertError::MissingColumn { .. }
+            | ConvertError::AmbiguousColumn { .. }
+            | ConvertError::UnexpectedColumns { .. }
+            | ConvertError::SwappedColumns { .. } => ExitKind::MissingColumns,
+            ConvertError::TooManyRejected { .. }
+            | ConvertError::UnexpectedCount { .. }
+            | ConvertError::RowError { .. }
+            | ConvertError::InvalidProfileValue { .. }
+            | ConvertError::Script { .. } => ExitKind::Validation,
+            ConvertError::OutputIsInput { .. }
+            | ConvertError::FileLocked { .. }
+            | ConvertError::OutputLocked { .. }
+            | ConvertError::OutputIncomplete { .. }
+            | ConvertError::UnsupportedFormat { .. }
+            | ConvertError::IoError(_) => ExitKind::Io,
+            ConvertError::Cancelled => ExitKind::Cancelled,
+        }
+    }
+
+    /// An I/O error that may go away when tried again: a timed-out, reset or
+    /// interrupted connection, or a network share that dropped out. Anything
+    /// else (missing file, refused permission, full disk, ...) is not retried.
+    pub fn is_transient(&self) -> bool {
+        let ConvertError::IoError(e) = self else {
+            return false;
+        };
+        is_share_lost(e)
+            || matches!(
+                e.kind(),
+                io::ErrorKind::TimedOut
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::Interrupted
+                    | io::ErrorKind::WouldBlock
+            )
+    }
+
+    /// Map a calamine error for the workbook at `path`.
+    pub fn from_xlsx(path: impl Into<PathBuf>, e: calamine::XlsxError) -> Self {
+        let path = path.into();
+        match e {
+            calamine::XlsxError::Io(io) => ConvertError::io_at(path, io),
+            other => ConvertError::UnsupportedFormat {
+                path,
+                reason: other.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn io(kind: io::ErrorKind) -> ConvertError {
+        ConvertError::IoError(io::Error::from(kind))
+    }
+
+    #[test]
+    fn only_connection_problems_are_retried() {
+        for kind in [
+            io::ErrorKind::TimedOut,
+            io::ErrorKind::ConnectionReset,
+            io::ErrorKind::ConnectionAborted,
+            io::ErrorKind::Interrupted,
+            io::ErrorKind::WouldBlock,
+        ] {
+            assert!(io(kind).is_transient(), "{:?}", kind);
+        }
+        for kind in [
+            io::ErrorKind::NotFound,
+            io::ErrorKind::PermissionDenied,
+            io::ErrorKind::StorageFull,
+            io::ErrorKind::Other,
+        ] {
+            assert!(!io(kind).is_transient(), "{:?}", kind);
+        }
+        assert!(!ConvertError::Cancelled.is_transient());
+    }
+
+    #[test]
+    fn a_lost_share_is_retried_on_windows() {
+        let lost = ConvertError::IoError(io::Error::from_raw_os_error(64));
+        assert_eq!(lost.is_transient(), cfg!(target_os = "windows"));
+    }
+}
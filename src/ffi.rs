@@ -0,0 +1,154 @@
+// src/ffi.rs
+// C API
+// -----
+//
+// The conversion core as a C-compatible library (feature `ffi`), so other
+// applications (e.g. the city's .NET back office, via P/Invoke) use the same
+// phone/address rules. Declarations: include/be_alert_convert.h.
+//
+//   cargo build --release --lib --features ffi
+//   -> be_alert_convert.dll / libbe_alert_convert.so / .dylib
+//
+// One call converts XLSX bytes in memory; nothing is written to disk and
+// there is no history. Every result must be released with
+// be_alert_free_result.
+
+use crate::convert::convert_xlsx_bytes;
+use crate::presets;
+use crate::profile::Profile;
+use serde::Deserialize;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Result of be_alert_convert_buffer. Owned by the library.
+#[repr(C)]
+pub struct BeAlertResult {
+    /// 1 when converted, 0 on error.
+    pub ok: i32,
+    /// CSV bytes (not NUL-terminated); null on error.
+    pub csv: *mut u8,
+    pub csv_len: usize,
+    /// UTF-8, NUL-terminated JSON: the summary on success
+    /// (rows, warnings, rejected, ...), {"error": "..."} on error.
+    pub report_json: *mut c_char,
+}
+
+/// `options_json`: {"name": "alken.xlsx", "profile": {...}}, both optional.
+/// The profile has the keys of a profile TOML file; missing keys fall back
+/// to the built-in Alken profile, except `postcode` and `gemeente` of a
+/// profile with a `preset`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Options {
+    name: String,
+    profile: Profile,
+}
+
+fn json_cstring(value: &serde_json::Value) -> *mut c_char {
+    // serde_json escapes control characters, so there is no NUL inside
+    CString::new(value.to_string())
+        .unwrap_or_default()
+        .into_raw()
+}
+
+fn error_result(message: &str) -> *mut BeAlertResult {
+    Box::into_raw(Box::new(BeAlertResult {
+        ok: 0,
+        csv: ptr::null_mut(),
+        csv_len: 0,
+        report_json: json_cstring(&serde_json::json!({ "error": message })),
+    }))
+}
+
+fn convert(input: &[u8], mut options: Options) -> *mut BeAlertResult {
+    if let Err(e) = presets::apply(&mut options.profile) {
+        return error_result(&format!("profile: {}", e));
+    }
+    let name = if options.name.is_empty() {
+        "input.xlsx"
+    } else {
+        options.name.as_str()
+    };
+    match convert_xlsx_bytes(name, input, &options.profile) {
+        Ok(export) => {
+            let csv = Box::into_raw(export.csv.into_boxed_slice());
+            Box::into_raw(Box::new(BeAlertResult {
+                ok: 1,
+                csv: csv as *mut u8,
+                csv_len: csv.len(),
+                report_json: json_cstring(&export.summary.to_json()),
+            }))
+        }
+        Err(e) => error_result(&e.to_string()),
+    }
+}
+
+/// Convert an XLSX file held in memory to a BE-Alert CSV.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes. `options_json` must be null
+/// or a NUL-terminated string. The returned pointer is never null and must
+/// be passed to `be_alert_free_result` exactly once.
+#[no_mangle]
+pub unsafe extern "C" fn be_alert_convert_buffer(
+    input: *const u8,
+    len: usize,
+    options_json: *const c_char,
+) -> *mut BeAlertResult {
+    if input.is_null() {
+        return error_result("input is null");
+    }
+    let input = std::slice::from_raw_parts(input, len);
+    let options = if options_json.is_null() {
+        Ok(Options::default())
+    } else {
+        CStr::from_ptr(options_json)
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                if text.trim().is_empty() {
+                    Ok(Options::default())
+                } else {
+                    let value: serde_json::Value =
+                        serde_json::from_str(text).map_err(|e| e.to_string())?;
+                    let mut options: Options =
+                        serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+                    presets::clear_unset(&mut options.profile, |key| {
+                        value["profile"].get(key).is_some()
+                    });
+                    Ok(options)
+                }
+            })
+    };
+    match options {
+        // A bug must not unwind into the caller's runtime
+        Ok(options) => panic::catch_unwind(AssertUnwindSafe(|| convert(input, options)))
+            .unwrap_or_else(|_| error_result("internal error")),
+        Err(e) => error_result(&format!("options: {}", e)),
+    }
+}
+
+/// Release a result of `be_alert_convert_buffer`.
+///
+/// # Safety
+///
+/// `result` must come from `be_alert_convert_buffer` and not be freed yet;
+/// null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn be_alert_free_result(result: *mut BeAlertResult) {
+    if result.is_null() {
+        return;
+    }
+    let result = Box::from_raw(result);
+    if !result.csv.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            result.csv,
+            result.csv_len,
+        )));
+    }
+    if !result.report_json.is_null() {
+        drop(CString::from_raw(result.report_json));
+    }
+}
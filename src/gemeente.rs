@@ -0,0 +1,177 @@
+// src/gemeente.rs
+// Gemeente names
+// --------------
+//
+// Registers do not agree on how a gemeente is written: "Alken (Limburg)",
+// "ALKEN", "Bruxelles/Brussel", "Sint-Lambrechts-Woluwe" next to
+// "Woluwe-Saint-Lambert". BE-Alert expects the official name, which is the
+// Dutch one in Flanders, the French one in Wallonia and, in the bilingual
+// Brussels region, the one in the profile's language (Taal).
+//
+// The built-in table only lists gemeenten whose name differs between the
+// languages; a profile can add its own spellings in `[gemeente_aliases]`.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    Vlaanderen,
+    Wallonie,
+    Brussel,
+}
+
+struct Gemeente {
+    nl: &'static str,
+    fr: &'static str,
+    region: Region,
+}
+
+const fn g(nl: &'static str, fr: &'static str, region: Region) -> Gemeente {
+    Gemeente { nl, fr, region }
+}
+
+use Region::{Brussel, Vlaanderen, Wallonie};
+
+const TABLE: &[Gemeente] = &[
+    // Brussels-Capital Region
+    g("Anderlecht", "Anderlecht", Brussel),
+    g("Brussel", "Bruxelles", Brussel),
+    g("Elsene", "Ixelles", Brussel),
+    g("Etterbeek", "Etterbeek", Brussel),
+    g("Evere", "Evere", Brussel),
+    g("Ganshoren", "Ganshoren", Brussel),
+    g("Jette", "Jette", Brussel),
+    g("Koekelberg", "Koekelberg", Brussel),
+    g("Oudergem", "Auderghem", Brussel),
+    g("Schaarbeek", "Schaerbeek", Brussel),
+    g("Sint-Agatha-Berchem", "Berchem-Sainte-Agathe", Brussel),
+    g("Sint-Gillis", "Saint-Gilles", Brussel),
+    g("Sint-Jans-Molenbeek", "Molenbeek-Saint-Jean", Brussel),
+    g("Sint-Joost-ten-Node", "Saint-Josse-ten-Noode", Brussel),
+    g("Sint-Lambrechts-Woluwe", "Woluwe-Saint-Lambert", Brussel),
+    g("Sint-Pieters-Woluwe", "Woluwe-Saint-Pierre", Brussel),
+    g("Ukkel", "Uccle", Brussel),
+    g("Vorst", "Forest", Brussel),
+    g("Watermaal-Bosvoorde", "Watermael-Boitsfort", Brussel),
+    // Flanders
+    g("Antwerpen", "Anvers", Vlaanderen),
+    g("Brugge", "Bruges", Vlaanderen),
+    g("Drogenbos", "Drogenbos", Vlaanderen),
+    g("Geraardsbergen", "Grammont", Vlaanderen),
+    g("Gent", "Gand", Vlaanderen),
+    g("Ieper", "Ypres", Vlaanderen),
+    g("Kortrijk", "Courtrai", Vlaanderen),
+    g("Kraainem", "Kraainem", Vlaanderen),
+    g("Leuven", "Louvain", Vlaanderen),
+    g("Linkebeek", "Linkebeek", Vlaanderen),
+    g("Mechelen", "Malines", Vlaanderen),
+    g("Mesen", "Messines", Vlaanderen),
+    g("Oostende", "Ostende", Vlaanderen),
+    g("Ronse", "Renaix", Vlaanderen),
+    g("Sint-Genesius-Rode", "Rhode-Saint-Genèse", Vlaanderen),
+    g("Sint-Truiden", "Saint-Trond", Vlaanderen),
+    g("Spiere-Helkijn", "Espierres-Helchin", Vlaanderen),
+    g("Tongeren", "Tongres", Vlaanderen),
+    g("Voeren", "Fourons", Vlaanderen),
+    g("Wemmel", "Wemmel", Vlaanderen),
+    g("Wezembeek-Oppem", "Wezembeek-Oppem", Vlaanderen),
+    // Wallonia
+    g("Aat", "Ath", Wallonie),
+    g("Aarlen", "Arlon", Wallonie),
+    g("Bergen", "Mons", Wallonie),
+    g("Doornik", "Tournai", Wallonie),
+    g("Edingen", "Enghien", Wallonie),
+    g("Geldenaken", "Jodoigne", Wallonie),
+    g("Hoei", "Huy", Wallonie),
+    g("Komen-Waasten", "Comines-Warneton", Wallonie),
+    g("Lessen", "Lessines", Wallonie),
+    g("Luik", "Liège", Wallonie),
+    g("Moeskroen", "Mouscron", Wallonie),
+    g("Namen", "Namur", Wallonie),
+    g("Nijvel", "Nivelles", Wallonie),
+    g(
+        "Ottignies-Louvain-la-Neuve",
+        "Ottignies-Louvain-la-Neuve",
+        Wallonie,
+    ),
+    g("Tubeke", "Tubize", Wallonie),
+    g("Verviers", "Verviers", Wallonie),
+    g("Waver", "Wavre", Wallonie),
+];
+
+/// Comparison key: lowercase, common accents dropped, every run of spaces,
+/// hyphens and apostrophes as one space ("Saint-Genèse" == "saint genese").
+fn key(name: &str) -> String {
+    let folded: String = name
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ä' => 'a',
+            'ç' => 'c',
+            'è' | 'é' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' => 'i',
+            'ô' | 'ö' => 'o',
+            'ù' | 'û' | 'ü' => 'u',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// "Alken (Limburg)" -> "Alken".
+fn strip_qualifier(name: &str) -> &str {
+    match name.find('(') {
+        Some(i) if i > 0 && name.trim_end().ends_with(')') => name[..i].trim(),
+        _ => name.trim(),
+    }
+}
+
+/// The parts of a bilingual form: "Bruxelles/Brussel", "Brussel - Bruxelles".
+fn parts(name: &str) -> impl Iterator<Item = &str> {
+    name.split(['/', '|'])
+        .flat_map(|p| p.split(" - "))
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+}
+
+fn lookup(name: &str) -> Option<&'static Gemeente> {
+    let k = key(name);
+    TABLE.iter().find(|g| key(g.nl) == k || key(g.fr) == k)
+}
+
+/// French for profiles with Taal "FR", Dutch otherwise.
+fn official(g: &Gemeente, taal: &str) -> &'static str {
+    match g.region {
+        Vlaanderen => g.nl,
+        Wallonie => g.fr,
+        Brussel if taal.trim().eq_ignore_ascii_case("fr") => g.fr,
+        Brussel => g.nl,
+    }
+}
+
+/// Official spelling of a gemeente name as written in a register.
+/// `aliases` (from the profile) map local spellings to the official name and
+/// win over the built-in table; unknown names come back trimmed and without
+/// a "(province)" qualifier.
+pub fn normalize(name: &str, taal: &str, aliases: &BTreeMap<String, String>) -> String {
+    let name = strip_qualifier(name);
+    let k = key(name);
+    if let Some(official) = aliases
+        .iter()
+        .find(|(alias, _)| key(alias) == k)
+        .map(|(_, official)| official)
+    {
+        return official.clone();
+    }
+    if let Some(g) = lookup(name).or_else(|| parts(name).find_map(lookup)) {
+        return official(g, taal).to_string();
+    }
+    name.to_string()
+}
+
+/// Do two names (in any language or spelling) refer to the same gemeente?
+pub fn same(a: &str, b: &str, aliases: &BTreeMap<String, String>) -> bool {
+    // The language only matters for the spelling, not for the comparison
+    key(&normalize(a, "", aliases)) == key(&normalize(b, "", aliases))
+}
@@ -0,0 +1,197 @@
+// src/headerless.rs
+// Lists without a header row
+// --------------------------
+//
+// Hand-made lists sometimes start with the first contact instead of column
+// names. The roles of their columns are guessed from the content (phone-like,
+// e-mail-like, street names, house numbers, names in capitals) so the user
+// can confirm the mapping; a confirmed mapping is used as the profile's
+// `input_columns`, which converts every row of the sheet as data.
+//
+// The GUI remembers confirmed mappings by the layout's fingerprint (see
+// settings.rs): the next list with the same layout is converted without
+// asking again.
+
+use crate::convert::{cell_to_string, looks_like_phone, POSTCODE_COLUMN, REQUIRED_COLUMNS};
+use calamine::Data;
+use sha2::{Digest, Sha256};
+
+/// Rows looked at to guess the column roles.
+const SAMPLE_ROWS: usize = 200;
+
+/// Endings of Belgian street names (Dutch and French).
+const STREET_WORDS: [&str; 14] = [
+    "straat",
+    "laan",
+    "weg",
+    "plein",
+    "dreef",
+    "baan",
+    "lei",
+    "hof",
+    "veld",
+    "rue",
+    "avenue",
+    "chaussée",
+    "place",
+    "chemin",
+];
+
+/// A first row with none of the expected column names but with a phone
+/// number or e-mail address in it is data, not a header.
+pub fn looks_headerless(first_row: &[Data]) -> bool {
+    let cells: Vec<String> = first_row
+        .iter()
+        .map(|cell| cell_to_string(cell).trim().to_string())
+        .collect();
+    let named = cells.iter().any(|cell| {
+        REQUIRED_COLUMNS
+            .iter()
+            .any(|name| cell.eq_ignore_ascii_case(name))
+    });
+    !named
+        && cells
+            .iter()
+            .any(|cell| looks_like_phone(cell) || looks_like_email(cell))
+}
+
+fn looks_like_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((user, domain)) => !user.is_empty() && domain.contains('.') && !value.contains(' '),
+        None => false,
+    }
+}
+
+fn looks_like_house_number(value: &str) -> bool {
+    value.len() <= 10 && value.starts_with(|c: char| c.is_ascii_digit())
+}
+
+fn looks_like_postcode(value: &str) -> bool {
+    value.len() == 4 && value.chars().all(|c| c.is_ascii_digit()) && !value.starts_with('0')
+}
+
+fn looks_like_street(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    !value.chars().any(|c| c.is_ascii_digit())
+        && lower
+            .split_whitespace()
+            .any(|word| STREET_WORDS.iter().any(|end| word.ends_with(end)))
+}
+
+fn looks_like_name(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_alphabetic() || " -'.".contains(c))
+        && value.chars().any(char::is_alphabetic)
+}
+
+fn is_upper(value: &str) -> bool {
+    value.chars().any(char::is_alphabetic) && !value.chars().any(char::is_lowercase)
+}
+
+/// Guessed input column name per column of `rows` (all data rows), "" for
+/// columns that fit no role. Each role is given to one column at most:
+/// "Mobiel nummer", "E-mailadres", "Straat", "Huisnummer", "Postcode",
+/// "Naam" (mostly capitals) and "Voornaam".
+pub fn guess_columns<'a>(rows: impl Iterator<Item = &'a [Data]>) -> Vec<String> {
+    let mut columns: Vec<Vec<String>> = Vec::new();
+    for row in rows.take(SAMPLE_ROWS) {
+        if columns.len() < row.len() {
+            columns.resize(row.len(), Vec::new());
+        }
+        for (i, cell) in row.iter().enumerate() {
+            let value = cell_to_string(cell).trim().to_string();
+            if !value.is_empty() {
+                columns[i].push(value);
+            }
+        }
+    }
+
+    // Share of a column's filled cells matching a pattern
+    let share = |values: &[String], test: &dyn Fn(&str) -> bool| {
+        if values.is_empty() {
+            0.0
+        } else {
+            values.iter().filter(|v| test(v)).count() as f64 / values.len() as f64
+        }
+    };
+    let mostly = |values: &[String], test: &dyn Fn(&str) -> bool| share(values, test) > 0.6;
+
+    let mut names = vec![String::new(); columns.len()];
+    let assign = |names: &mut Vec<String>, role: &str, test: &dyn Fn(&[String]) -> bool| {
+        if names.iter().any(|n| n == role) {
+            return;
+        }
+        if let Some(i) = (0..columns.len()).find(|&i| names[i].is_empty() && test(&columns[i])) {
+            names[i] = role.to_string();
+        }
+    };
+    assign(&mut names, "E-mailadres", &|v| mostly(v, &looks_like_email));
+    assign(&mut names, "Mobiel nummer", &|v| {
+        mostly(v, &looks_like_phone)
+    });
+    assign(&mut names, POSTCODE_COLUMN, &|v| {
+        mostly(v, &looks_like_postcode)
+    });
+    assign(&mut names, "Huisnummer", &|v| {
+        mostly(v, &looks_like_house_number)
+    });
+    assign(&mut names, "Straat", &|v| mostly(v, &looks_like_street));
+    assign(&mut names, "Naam", &|v| {
+        mostly(v, &looks_like_name) && mostly(v, &is_upper)
+    });
+    assign(&mut names, "Voornaam", &|v| mostly(v, &looks_like_name));
+    // Two mixed-case name columns: the second one is the family name
+    assign(&mut names, "Naam", &|v| mostly(v, &looks_like_name));
+    names
+}
+
+/// "A: Voornaam, B: Naam, ..." for the confirmation, unguessed columns left out.
+pub fn mapping_text(columns: &[String]) -> String {
+    columns
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| !name.is_empty())
+        .map(|(i, name)| format!("{}: {}", column_letter(i), name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Fingerprint of a layout: the number of columns and the role guessed for
+/// each, hashed (16 hex digits).
+pub fn fingerprint(columns: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(columns.len().to_string().as_bytes());
+    for name in columns {
+        // Separator, so ("ab", "c") and ("a", "bc") differ
+        hasher.update([0x1f]);
+        hasher.update(name.as_bytes());
+    }
+    hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Required columns the guess did not find.
+pub fn missing_roles(columns: &[String]) -> Vec<&'static str> {
+    REQUIRED_COLUMNS
+        .iter()
+        .copied()
+        .filter(|name| !columns.iter().any(|c| c == name))
+        .collect()
+}
+
+/// Excel column letter of a 0-based index: 0 -> A, 26 -> AA.
+fn column_letter(mut index: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap_or_default()
+}
@@ -0,0 +1,488 @@
+// src/history.rs
+// Conversion history
+// ------------------
+//
+// Every single-CSV export of a file is recorded in <app dir>/history.sqlite:
+// the run (input name and SHA-256, profile, operator and remark, counts) and
+// the contacts written, with the note of their "Opmerking" column. When
+// the same file (same name or same content) is converted again, the new run
+// is compared with the last one, so a register export that shrank
+// unexpectedly stands out ("+124 rows, -3 rows, 7 new warnings"). The GUI
+// preview also compares every contact with the last exported version of its
+// Tel/Ref. (any input), to highlight new numbers and changed addresses/e-mails.
+// Profiles with `delete_file = true` get the contacts of the last run that are
+// gone as a separate CSV, for BE-Alert's removal upload.
+//
+// The database needs the `native` feature; without it (browser build) there
+// is no history and exports are never compared.
+
+#[cfg(feature = "native")]
+use crate::{
+    dedup::{self, Record},
+    paths,
+    report::ConvertSummary,
+};
+#[cfg(feature = "native")]
+use chrono::Local;
+#[cfg(feature = "native")]
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "native")]
+use std::collections::{HashMap, HashSet};
+use std::fs;
+#[cfg(feature = "native")]
+use std::path::Path;
+#[cfg(feature = "native")]
+use std::time::Duration;
+
+#[cfg(feature = "native")]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id             INTEGER PRIMARY KEY,
+    started        TEXT NOT NULL,
+    input_name     TEXT NOT NULL,
+    input_hash     TEXT NOT NULL,
+    output         TEXT NOT NULL,
+    profile        TEXT NOT NULL,
+    rows           INTEGER NOT NULL,
+    warnings       INTEGER NOT NULL,
+    unknown_phones INTEGER NOT NULL,
+    operator       TEXT NOT NULL DEFAULT '',
+    remark         TEXT NOT NULL DEFAULT ''
+);
+CREATE TABLE IF NOT EXISTS contacts (
+    run_id   INTEGER NOT NULL REFERENCES runs(id),
+    tel_ref  TEXT NOT NULL,
+    naam     TEXT NOT NULL,
+    voornaam TEXT NOT NULL,
+    adres    TEXT NOT NULL,
+    email    TEXT NOT NULL,
+    note     TEXT NOT NULL DEFAULT ''
+);
+CREATE INDEX IF NOT EXISTS contacts_run ON contacts(run_id);
+CREATE INDEX IF NOT EXISTS contacts_tel_ref ON contacts(tel_ref, run_id);
+";
+
+/// Columns added after the first release (table, column, definition), for
+/// databases created before them.
+#[cfg(feature = "native")]
+const ADDED_COLUMNS: [(&str, &str, &str); 3] = [
+    ("runs", "operator", "TEXT NOT NULL DEFAULT ''"),
+    ("runs", "remark", "TEXT NOT NULL DEFAULT ''"),
+    ("contacts", "note", "TEXT NOT NULL DEFAULT ''"),
+];
+
+/// Difference with the last run of the same input.
+#[derive(Debug, Clone)]
+pub struct RunDelta {
+    /// When the previous run was made.
+    pub previous: String,
+    /// Tel/Ref. numbers not in the previous run.
+    pub added: usize,
+    /// Tel/Ref. numbers of the previous run that are gone.
+    pub removed: usize,
+    pub rows_before: usize,
+    /// Warnings now minus warnings then (negative: fewer).
+    pub new_warnings: i64,
+    /// Byte-identical input.
+    pub same_file: bool,
+}
+
+impl RunDelta {
+    pub fn text(&self) -> String {
+        let mut text = format!(
+            "+{} rows, -{} rows, {} new warnings vs last run ({}, {} rows)",
+            self.added, self.removed, self.new_warnings, self.previous, self.rows_before
+        );
+        if self.same_file {
+            text.push_str(", same file");
+        }
+        text
+    }
+}
+
+/// How a contact differs from the last exported version of its Tel/Ref.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContactChange {
+    /// Tel/Ref. never exported before (a new contact or a changed number).
+    New,
+    /// Fields that changed: "address", "e-mail".
+    Changed(Vec<&'static str>),
+}
+
+impl ContactChange {
+    pub fn text(&self) -> String {
+        match self {
+            ContactChange::New => "new".to_string(),
+            ContactChange::Changed(fields) => fields.join(", "),
+        }
+    }
+}
+
+/// A contact of the last run whose Tel/Ref. is no longer in the export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovedContact {
+    pub tel_ref: String,
+    pub naam: String,
+    pub voornaam: String,
+    pub adres: String,
+    pub email: String,
+}
+
+#[cfg(feature = "native")]
+pub struct History {
+    conn: Connection,
+}
+
+#[cfg(feature = "native")]
+struct LastRun {
+    id: i64,
+    started: String,
+    hash: String,
+    rows: i64,
+    warnings: i64,
+}
+
+/// SHA-256 of a file, hex encoded. None for sources that are not a file
+/// (pasted rows, merged inputs): those are not recorded.
+pub fn file_hash(path: &str) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    Some(
+        Sha256::digest(&bytes)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    )
+}
+
+#[cfg(feature = "native")]
+/// File name used to recognise a re-converted input, case-insensitive.
+fn input_name(source: &str) -> String {
+    Path::new(source)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| source.to_lowercase())
+}
+
+#[cfg(feature = "native")]
+fn issue_count(summary: &ConvertSummary) -> usize {
+    summary.warnings.len() + summary.cell_warnings.len() + summary.rejected.len()
+}
+
+#[cfg(feature = "native")]
+impl History {
+    /// Open (or create) the history database in the app folder.
+    pub fn open() -> rusqlite::Result<History> {
+        let path = paths::history_path()
+            .ok_or(rusqlite::Error::InvalidPath(paths::APP_DIR_NAME.into()))?;
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        Self::open_at(&path)
+    }
+
+    pub fn open_at(path: &Path) -> rusqlite::Result<History> {
+        let conn = Connection::open(path)?;
+        // Batch conversions run in parallel, each with its own connection
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.execute_batch(SCHEMA)?;
+        let existing: HashSet<(String, String)> = conn
+            .prepare(
+                "SELECT m.name, p.name FROM sqlite_master m, pragma_table_info(m.name) p
+                 WHERE m.type = 'table'",
+            )?
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (table, column, definition) in ADDED_COLUMNS {
+            if !existing.contains(&(table.to_string(), column.to_string())) {
+                conn.execute_batch(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    table, column, definition
+                ))?;
+            }
+        }
+        Ok(History { conn })
+    }
+
+    /// Last run of the same input (same file name or same content).
+    fn last_run(&self, source: &str, hash: &str) -> rusqlite::Result<Option<LastRun>> {
+        self.conn
+            .query_row(
+                "SELECT id, started, input_hash, rows, warnings FROM runs
+                 WHERE input_name = ?1 OR input_hash = ?2
+                 ORDER BY id DESC LIMIT 1",
+                params![input_name(source), hash],
+                |r| {
+                    Ok(LastRun {
+                        id: r.get(0)?,
+                        started: r.get(1)?,
+                        hash: r.get(2)?,
+                        rows: r.get(3)?,
+                        warnings: r.get(4)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// Compare an export about to be written with the last run of the same
+    /// input (same file name or same content). None the first time.
+    pub fn compare(
+        &self,
+        source: &str,
+        hash: &str,
+        records: &[Record],
+        summary: &ConvertSummary,
+    ) -> rusqlite::Result<Option<RunDelta>> {
+        let Some(LastRun {
+            id,
+            started,
+            hash: previous_hash,
+            rows,
+            warnings,
+        }) = self.last_run(source, hash)?
+        else {
+            return Ok(None);
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tel_ref FROM contacts WHERE run_id = ?1 AND tel_ref <> ''")?;
+        let before: HashSet<String> = stmt
+            .query_map([id], |r| r.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        let now: HashSet<&str> = records
+            .iter()
+            .map(|(_, r)| dedup::contact_number(r))
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        Ok(Some(RunDelta {
+            previous: started,
+            added: now.iter().filter(|t| !before.contains(**t)).count(),
+            removed: before.iter().filter(|t| !now.contains(t.as_str())).count(),
+            rows_before: rows as usize,
+            new_warnings: issue_count(summary) as i64 - warnings,
+            same_file: hash == previous_hash,
+        }))
+    }
+
+    /// Contacts of the last run of the same input whose Tel/Ref. is not in
+    /// `records`, in the order they were exported. Empty the first time.
+    pub fn removed_contacts(
+        &self,
+        source: &str,
+        hash: &str,
+        records: &[Record],
+    ) -> rusqlite::Result<Vec<RemovedContact>> {
+        let Some(LastRun { id, .. }) = self.last_run(source, hash)? else {
+            return Ok(Vec::new());
+        };
+        let now: HashSet<&str> = records
+            .iter()
+            .map(|(_, r)| dedup::contact_number(r))
+            .collect();
+        let mut stmt = self.conn.prepare(
+            "SELECT tel_ref, naam, voornaam, adres, email FROM contacts
+             WHERE run_id = ?1 AND tel_ref <> '' ORDER BY rowid",
+        )?;
+        let contacts = stmt.query_map([id], |r| {
+            Ok(RemovedContact {
+                tel_ref: r.get(0)?,
+                naam: r.get(1)?,
+                voornaam: r.get(2)?,
+                adres: r.get(3)?,
+                email: r.get(4)?,
+            })
+        })?;
+        let mut removed = Vec::new();
+        let mut seen = HashSet::new();
+        for contact in contacts {
+            let contact = contact?;
+            if !now.contains(contact.tel_ref.as_str()) && seen.insert(contact.tel_ref.clone()) {
+                removed.push(contact);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Compare contacts with the last exported version of the same Tel/Ref.
+    /// One entry per record: None when unchanged or without Tel/Ref.
+    pub fn contact_changes(
+        &self,
+        records: &[Record],
+    ) -> rusqlite::Result<Vec<Option<ContactChange>>> {
+        // Only the last version of each number in the records, looked up in
+        // the contacts_tel_ref index: the history holds every run ever made
+        let mut stmt = self.conn.prepare(
+            "SELECT adres, email FROM contacts WHERE tel_ref = ?1
+             ORDER BY run_id DESC, rowid DESC LIMIT 1",
+        )?;
+        let mut last: HashMap<&str, (String, String)> = HashMap::new();
+        for (_, r) in records {
+            let number = dedup::contact_number(r);
+            if number.is_empty() || last.contains_key(number) {
+                continue;
+            }
+            let version = stmt
+                .query_row([number], |r| Ok((r.get(0)?, r.get(1)?)))
+                .optional()?;
+            if let Some(version) = version {
+                last.insert(number, version);
+            }
+        }
+
+        Ok(records
+            .iter()
+            .map(|(_, r)| {
+                let number = dedup::contact_number(r);
+                if number.is_empty() {
+                    return None;
+                }
+                let Some((adres, email)) = last.get(number) else {
+                    return Some(ContactChange::New);
+                };
+                // Adres incl huisnummer, Email
+                let mut fields = Vec::new();
+                if r[4] != *adres {
+                    fields.push("address");
+                }
+                if !r[9].eq_ignore_ascii_case(email) {
+                    fields.push("e-mail");
+                }
+                (!fields.is_empty()).then_some(ContactChange::Changed(fields))
+            })
+            .collect())
+    }
+
+    /// Store a written export and its contacts; returns the id of the run.
+    pub fn record(
+        &mut self,
+        source: &str,
+        hash: &str,
+        output: &Path,
+        profile: &str,
+        records: &[Record],
+        summary: &ConvertSummary,
+    ) -> rusqlite::Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (started, input_name, input_hash, output, profile, rows, warnings, unknown_phones, operator, remark)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                Local::now().format("%d/%m/%Y %H:%M").to_string(),
+                input_name(source),
+                hash,
+                output.display().to_string(),
+                profile,
+                records.len() as i64,
+                issue_count(summary) as i64,
+                summary.unknown_phones as i64,
+                summary.run.operator,
+                summary.run.remark,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO contacts (run_id, tel_ref, naam, voornaam, adres, email, note)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            let notes: HashMap<usize, &str> = summary
+                .notes
+                .iter()
+                .map(|n| (n.row, n.text.as_str()))
+                .collect();
+            for (row, r) in records {
+                // Tel/Ref. (or SMS), Naam, Voornaam, Adres incl huisnummer, Email
+                let note = notes.get(row).copied().unwrap_or_default();
+                let number = dedup::contact_number(r);
+                stmt.execute(params![run_id, number, r[2], r[3], r[4], r[9], note])?;
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// The output of a run was moved (a staged batch, see batch.rs).
+    pub fn set_output(&self, run_id: i64, output: &Path) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE runs SET output = ?1 WHERE id = ?2",
+            params![output.display().to_string(), run_id],
+        )?;
+        Ok(())
+    }
+
+    /// Take back a run whose output was never delivered (a staged batch that
+    /// failed), so the next run is compared with the one before.
+    pub fn forget(&mut self, run_id: i64) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM contacts WHERE run_id = ?1", params![run_id])?;
+        tx.execute("DELETE FROM runs WHERE id = ?1", params![run_id])?;
+        tx.commit()
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+
+    fn contact(tel_ref: &str, adres: &str, email: &str) -> Record {
+        let mut record = vec![String::new(); 33];
+        record[0] = tel_ref.to_string();
+        record[4] = adres.to_string();
+        record[9] = email.to_string();
+        (2, record)
+    }
+
+    #[test]
+    fn contact_changes_compare_with_the_last_version_of_each_number() {
+        let path =
+            std::env::temp_dir().join(format!("be-alert-history-{}.sqlite", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut history = History::open_at(&path).unwrap();
+        let summary = ConvertSummary::default();
+        let output = Path::new("out.csv");
+        let first = [
+            contact("0032475111111", "Kerkstraat 1", "a@telenet.be"),
+            contact("0032475222222", "Kerkstraat 2", ""),
+        ];
+        history
+            .record("a.xlsx", "1", output, "p", &first, &summary)
+            .unwrap();
+        let second = [contact("0032475111111", "Dorpstraat 1", "a@telenet.be")];
+        history
+            .record("b.xlsx", "2", output, "p", &second, &summary)
+            .unwrap();
+
+        let now = [
+            contact("0032475111111", "Dorpstraat 1", "A@Telenet.be"),
+            contact("0032475222222", "Kerkstraat 2", "b@skynet.be"),
+            contact("0032475333333", "Kerkstraat 3", ""),
+            contact("", "Kerkstraat 4", "d@skynet.be"),
+        ];
+        let changes = history.contact_changes(&now).unwrap();
+        assert_eq!(
+            changes,
+            [
+                None,
+                Some(ContactChange::Changed(vec!["e-mail"])),
+                Some(ContactChange::New),
+                None,
+            ]
+        );
+
+        let plan: String = history
+            .conn
+            .query_row(
+                "EXPLAIN QUERY PLAN SELECT adres, email FROM contacts WHERE tel_ref = '0'
+                 ORDER BY run_id DESC, rowid DESC LIMIT 1",
+                [],
+                |r| r.get(3),
+            )
+            .unwrap();
+        assert!(plan.contains("contacts_tel_ref"), "{}", plan);
+        drop(history);
+        let _ = fs::remove_file(&path);
+    }
+}
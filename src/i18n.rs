@@ -0,0 +1,582 @@
+// src/i18n.rs
+// Language of reports and command line messages
+// ---------------------------------------------
+//
+// Reports are forwarded to provincial coordinators who read French (or
+// Dutch). Messages are written in English in the code; TABLE holds the Dutch
+// and French text of each, looked up by the English one, so a message missing
+// from the table is shown in English (a test makes sure the report and the
+// command line find theirs). The summary warnings are formatted in English
+// while converting, before the language of the run is known; `translate`
+// recognizes them by their English text when they are shown. Row-level
+// details (rejection reasons, cell warnings) are not translated yet.
+//
+// The language is the command line's `--lang`, else the locale variables
+// (LC_ALL, LC_MESSAGES or LANG, e.g. "fr_BE.UTF-8"); English when neither
+// says. Windows sets none of them: there the language is English unless the
+// user sets LANG.
+
+use std::env;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Nl,
+    Fr,
+}
+
+impl Lang {
+    /// Language of a code or locale: "nl", "fr-BE", "nl_BE.UTF-8", "EN".
+    pub fn parse(code: &str) -> Option<Lang> {
+        let language = code
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Lang::En),
+            "nl" => Some(Lang::Nl),
+            "fr" => Some(Lang::Fr),
+            _ => None,
+        }
+    }
+
+    /// Language of the locale variables, English when unknown (or unset, as
+    /// on Windows).
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// (English, Dutch, French)
+const TABLE: &[(&str, &str, &str)] = &[
+    // Report
+    (
+        "BE-Alert conversion report",
+        "BE-Alert conversierapport",
+        "Rapport de conversion BE-Alert",
+    ),
+    ("Summary", "Samenvatting", "Résumé"),
+    ("Operator", "Operator", "Opérateur"),
+    ("Remark", "Opmerking", "Remarque"),
+    (
+        "Convertor version",
+        "Versie convertor",
+        "Version du convertisseur",
+    ),
+    ("Profile", "Profiel", "Profil"),
+    ("settings", "instellingen", "paramètres"),
+    ("Input", "Invoer", "Entrée"),
+    ("Output", "Uitvoer", "Sortie"),
+    ("Rows written", "Rijen geschreven", "Lignes écrites"),
+    (
+        "Contacts expected from the register",
+        "Verwachte contacten volgens het register",
+        "Contacts attendus selon le registre",
+    ),
+    (
+        "Rows matching no profile",
+        "Rijen zonder passend profiel",
+        "Lignes sans profil correspondant",
+    ),
+    (
+        "Phone numbers matching no known pattern",
+        "Telefoonnummers in een onbekend formaat",
+        "Numéros de téléphone au format inconnu",
+    ),
+    ("Rows rejected", "Geweigerde rijen", "Lignes refusées"),
+    (
+        "Rejected rows, with their input",
+        "Geweigerde rijen, met hun invoer",
+        "Lignes refusées, avec leurs données",
+    ),
+    (
+        "Contacts gone since the last run, for removal",
+        "Contacten verdwenen sinds de vorige run, te verwijderen",
+        "Contacts disparus depuis la dernière exécution, à supprimer",
+    ),
+    (
+        "Contacts under the minimum age left out",
+        "Contacten onder de minimumleeftijd weggelaten",
+        "Contacts sous l'âge minimum écartés",
+    ),
+    (
+        "Rows marked not to export, left out",
+        "Rijen gemarkeerd om niet te exporteren, weggelaten",
+        "Lignes marquées à ne pas exporter, écartées",
+    ),
+    (
+        "Cells not converted exactly (numbers over 15 digits, unreadable dates)",
+        "Cellen niet exact omgezet (getallen van meer dan 15 cijfers, onleesbare datums)",
+        "Cellules non converties exactement (nombres de plus de 15 chiffres, dates illisibles)",
+    ),
+    (
+        "Addresses without street (BE-Alert cannot locate them)",
+        "Adressen zonder straat (BE-Alert kan ze niet lokaliseren)",
+        "Adresses sans rue (BE-Alert ne peut pas les localiser)",
+    ),
+    (
+        "Contacts preferring SMS (number in the SMS field)",
+        "Contacten met voorkeur voor SMS (nummer in het veld SMS)",
+        "Contacts préférant le SMS (numéro dans le champ SMS)",
+    ),
+    (
+        "Placeholder or test values (\"0000000000\", \"test@test.be\", ...)",
+        "Opvul- of testwaarden (\"0000000000\", \"test@test.be\", ...)",
+        "Valeurs fictives ou de test (\"0000000000\", \"test@test.be\", ...)",
+    ),
+    (
+        "Phone numbers that did not fit in Telefoon 2..7",
+        "Telefoonnummers die niet in Telefoon 2..7 pasten",
+        "Numéros de téléphone sans place dans Telefoon 2..7",
+    ),
+    (
+        "E-mail addresses with a mistyped domain (\"gmial.com\" -> \"gmail.com\")",
+        "E-mailadressen met een tikfout in het domein (\"gmial.com\" -> \"gmail.com\")",
+        "Adresses e-mail au domaine mal tapé (\"gmial.com\" -> \"gmail.com\")",
+    ),
+    (
+        "Addresses with abbreviations written out (\"Kon.\" -> \"Koning\")",
+        "Adressen met voluit geschreven afkortingen (\"Kon.\" -> \"Koning\")",
+        "Adresses aux abréviations développées (\"Kon.\" -> \"Koning\")",
+    ),
+    (
+        "Compared with the last run",
+        "Vergeleken met de vorige run",
+        "Par rapport à la dernière exécution",
+    ),
+    ("Rejected rows", "Geweigerde rijen", "Lignes refusées"),
+    (
+        "Cell warnings",
+        "Waarschuwingen per cel",
+        "Avertissements par cellule",
+    ),
+    ("Warnings", "Waarschuwingen", "Avertissements"),
+    ("Notes", "Opmerkingen", "Remarques"),
+    (
+        "From the Opmerking column; not in the CSV.",
+        "Uit de kolom Opmerking; niet in de CSV.",
+        "De la colonne Opmerking ; pas dans le CSV.",
+    ),
+    (
+        "Coverage per street",
+        "Bereik per straat",
+        "Couverture par rue",
+    ),
+    (
+        "Streets where less than half of the contacts can get an SMS are highlighted.",
+        "Straten waar minder dan de helft van de contacten een SMS kan krijgen, zijn gemarkeerd.",
+        "Les rues où moins de la moitié des contacts peuvent recevoir un SMS sont surlignées.",
+    ),
+    ("(no address)", "(geen adres)", "(pas d'adresse)"),
+    (
+        "Phone audit",
+        "Controle telefoonnummers",
+        "Contrôle des numéros",
+    ),
+    ("Cell", "Cel", "Cellule"),
+    ("Column", "Kolom", "Colonne"),
+    ("Problem", "Probleem", "Problème"),
+    ("Name", "Naam", "Nom"),
+    ("Note", "Opmerking", "Remarque"),
+    ("Number read", "Gelezen nummer", "Numéro lu"),
+    ("Street", "Straat", "Rue"),
+    ("Contacts", "Contacten", "Contacts"),
+    ("Mobile", "Mobiel", "Mobile"),
+    ("Landline only", "Enkel vast", "Fixe uniquement"),
+    ("E-mail only", "Enkel e-mail", "E-mail uniquement"),
+    ("Unreachable", "Onbereikbaar", "Injoignable"),
+    // Status line (ConvertSummary::warning_text)
+    (
+        " {} unknown phone numbers.",
+        " {} onbekende telefoonnummers.",
+        " {} numéros de téléphone inconnus.",
+    ),
+    (
+        " {} rows rejected.",
+        " {} rijen geweigerd.",
+        " {} lignes refusées.",
+    ),
+    (
+        " {} contacts under the minimum age left out.",
+        " {} contacten onder de minimumleeftijd weggelaten.",
+        " {} contacts sous l'âge minimum écartés.",
+    ),
+    (
+        " {} rows marked not to export.",
+        " {} rijen gemarkeerd om niet te exporteren.",
+        " {} lignes marquées à ne pas exporter.",
+    ),
+    (
+        " {} addresses without street (see report).",
+        " {} adressen zonder straat (zie rapport).",
+        " {} adresses sans rue (voir le rapport).",
+    ),
+    (
+        " {} placeholder or test values (see report).",
+        " {} opvul- of testwaarden (zie rapport).",
+        " {} valeurs fictives ou de test (voir le rapport).",
+    ),
+    (
+        " {} phone numbers did not fit in Telefoon 2..7 (see report).",
+        " {} telefoonnummers pasten niet in Telefoon 2..7 (zie rapport).",
+        " {} numéros sans place dans Telefoon 2..7 (voir le rapport).",
+    ),
+    (
+        " {} e-mail domains look mistyped (see report).",
+        " {} e-maildomeinen lijken verkeerd getypt (zie rapport).",
+        " {} domaines e-mail semblent mal tapés (voir le rapport).",
+    ),
+    (
+        " {} date cells, {} error cells (see report).",
+        " {} datumcellen, {} foutcellen (zie rapport).",
+        " {} cellules de date, {} cellules d'erreur (voir le rapport).",
+    ),
+    (
+        " {} cells not converted exactly (see report).",
+        " {} cellen niet exact omgezet (zie rapport).",
+        " {} cellules non converties exactement (voir le rapport).",
+    ),
+    (
+        " {} numbers to remove in {}.",
+        " {} nummers te verwijderen in {}.",
+        " {} numéros à supprimer dans {}.",
+    ),
+    (
+        " Wrong export? {}.",
+        " Verkeerde export? {}.",
+        " Mauvaise exportation ? {}.",
+    ),
+    (
+        "is this the right export?",
+        "is dit de juiste export?",
+        "est-ce la bonne exportation ?",
+    ),
+    (" Warning: {}", " Waarschuwing: {}", " Avertissement : {}"),
+    (
+        "+{} rows, -{} rows, {} new warnings vs last run ({}, {} rows)",
+        "+{} rijen, -{} rijen, {} nieuwe waarschuwingen t.o.v. de vorige run ({}, {} rijen)",
+        "+{} lignes, -{} lignes, {} nouveaux avertissements par rapport à la dernière exécution ({}, {} lignes)",
+    ),
+    (
+        "+{} rows, -{} rows, {} new warnings vs last run ({}, {} rows), same file",
+        "+{} rijen, -{} rijen, {} nieuwe waarschuwingen t.o.v. de vorige run ({}, {} rijen), zelfde bestand",
+        "+{} lignes, -{} lignes, {} nouveaux avertissements par rapport à la dernière exécution ({}, {} lignes), même fichier",
+    ),
+    // Summary warnings (see `translate`)
+    (
+        "\"{}\" is empty in {} of {} rows",
+        "\"{}\" is leeg in {} van {} rijen",
+        "\"{}\" est vide dans {} lignes sur {}",
+    ),
+    (
+        "{} Tel/Ref. numbers on more than one row (all rows kept)",
+        "{} Tel/Ref.-nummers op meer dan één rij (alle rijen behouden)",
+        "{} numéros Tél/Réf. sur plus d'une ligne (toutes les lignes conservées)",
+    ),
+    (
+        "{} duplicate Tel/Ref. groups reviewed, {} rows removed",
+        "{} groepen dubbele Tel/Ref.-nummers nagekeken, {} rijen verwijderd",
+        "{} groupes de Tél/Réf. en double vérifiés, {} lignes supprimées",
+    ),
+    (
+        "{} rows exceed the BE-Alert quota of {} contacts for {}: {} rows over the limit would be dropped on upload",
+        "{} rijen overschrijden het BE-Alert-quotum van {} contacten voor {}: {} rijen boven de limiet vallen weg bij het opladen",
+        "{} lignes dépassent le quota BE-Alert de {} contacts pour {} : {} lignes au-delà de la limite seraient écartées au chargement",
+    ),
+    (
+        "{} contacts converted where the register counts {}, more than {}% off. Nothing exported; is this the complete export?",
+        "{} contacten omgezet waar het register er {} telt, meer dan {}% verschil. Niets geëxporteerd; is dit de volledige export?",
+        "{} contacts convertis alors que le registre en compte {}, plus de {} % d'écart. Rien n'a été exporté ; est-ce l'exportation complète ?",
+    ),
+    (
+        "{}: macros are not run, values are as last saved in Excel",
+        "{}: macro's worden niet uitgevoerd, de waarden zijn zoals laatst bewaard in Excel",
+        "{} : les macros ne sont pas exécutées, les valeurs sont celles du dernier enregistrement dans Excel",
+    ),
+    (
+        "{}: column \"{}\" appears {} times, read from column {}",
+        "{}: kolom \"{}\" komt {} keer voor, gelezen uit kolom {}",
+        "{} : la colonne \"{}\" apparaît {} fois, lue dans la colonne {}",
+    ),
+    (
+        "{}: formula cells without a saved result (exported empty): {}",
+        "{}: formulecellen zonder bewaard resultaat (leeg geëxporteerd): {}",
+        "{} : cellules de formule sans résultat enregistré (exportées vides) : {}",
+    ),
+    (
+        "profile {}: unexpected columns {}",
+        "profiel {}: onverwachte kolommen {}",
+        "profil {} : colonnes inattendues {}",
+    ),
+    (
+        "{} cells look like a rijksregisternummer (GDPR); masked in the output",
+        "{} cellen lijken op een rijksregisternummer (AVG); gemaskeerd in de uitvoer",
+        "{} cellules ressemblent à un numéro de registre national (RGPD) ; masquées dans le fichier",
+    ),
+    (
+        "profile gemeente \"{}\" exported as the official \"{}\"",
+        "gemeente \"{}\" van het profiel geëxporteerd als de officiële \"{}\"",
+        "commune \"{}\" du profil exportée sous le nom officiel \"{}\"",
+    ),
+    (
+        "{} rows write the gemeente unofficially (e.g. \"{}\"), exported as \"{}\"",
+        "{} rijen schrijven de gemeente niet officieel (bv. \"{}\"), geëxporteerd als \"{}\"",
+        "{} lignes écrivent la commune de façon non officielle (p. ex. \"{}\"), exportée comme \"{}\"",
+    ),
+    (
+        "{} rows name another gemeente than {} (e.g. \"{}\")",
+        "{} rijen noemen een andere gemeente dan {} (bv. \"{}\")",
+        "{} lignes indiquent une autre commune que {} (p. ex. \"{}\")",
+    ),
+    (
+        "{} contacts in both files, {} with different details (kept the first file's version)",
+        "{} contacten in beide bestanden, {} met andere gegevens (versie van het eerste bestand behouden)",
+        "{} contacts dans les deux fichiers, {} avec des données différentes (version du premier fichier conservée)",
+    ),
+    (
+        "{} contacts in both files, {} with different details (kept the second file's version)",
+        "{} contacten in beide bestanden, {} met andere gegevens (versie van het tweede bestand behouden)",
+        "{} contacts dans les deux fichiers, {} avec des données différentes (version du second fichier conservée)",
+    ),
+    (
+        "history unavailable: {}",
+        "geschiedenis niet beschikbaar: {}",
+        "historique indisponible : {}",
+    ),
+    (
+        "duplicate review unavailable: {}",
+        "nakijken van dubbels niet beschikbaar: {}",
+        "vérification des doublons indisponible : {}",
+    ),
+    // Command line
+    (
+        "{} converted, {} failed, {} rows. Report: {}",
+        "{} omgezet, {} mislukt, {} rijen. Rapport: {}",
+        "{} convertis, {} en échec, {} lignes. Rapport : {}",
+    ),
+    (
+        "{} converted, {} failed",
+        "{} omgezet, {} mislukt",
+        "{} convertis, {} en échec",
+    ),
+    (
+        "{} verified, {} with problems",
+        "{} gecontroleerd, {} met problemen",
+        "{} vérifiés, {} avec des problèmes",
+    ),
+    (
+        "{} peeked at, {} with problems",
+        "{} bekeken, {} met problemen",
+        "{} consultés, {} avec des problèmes",
+    ),
+    ("{} rows.", "{} rijen.", "{} lignes."),
+    (
+        "error: no files match {}",
+        "fout: geen bestanden gevonden voor {}",
+        "erreur : aucun fichier ne correspond à {}",
+    ),
+];
+
+/// `en` in `lang`; `en` itself when the table has no translation.
+pub fn tr(lang: Lang, en: &'static str) -> &'static str {
+    if lang == Lang::En {
+        return en;
+    }
+    match TABLE.iter().find(|(key, _, _)| *key == en) {
+        Some(&(_, nl, _)) if lang == Lang::Nl => nl,
+        Some(&(_, _, fr)) => fr,
+        None => en,
+    }
+}
+
+/// `text`, formatted from one of the table's English messages, in `lang`: the
+/// values in the place of its "{}" are put in the translation. Text that is
+/// not in the table is returned as it is.
+pub fn translate(lang: Lang, text: &str) -> String {
+    if lang == Lang::En {
+        return text.to_string();
+    }
+    TABLE
+        .iter()
+        .find_map(|&(en, _, _)| {
+            let args = placeholders(en, text)?;
+            let args: Vec<&dyn Display> = args.iter().map(|arg| arg as &dyn Display).collect();
+            Some(trf(lang, en, &args))
+        })
+        .unwrap_or_else(|| text.to_string())
+}
+
+/// The values `text` has in the place of each "{}" of `en`, when it is `en`
+/// formatted.
+fn placeholders<'a>(en: &str, text: &'a str) -> Option<Vec<&'a str>> {
+    let mut parts = en.split("{}");
+    let mut rest = text.strip_prefix(parts.next().unwrap_or_default())?;
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty().then(Vec::new);
+    };
+    let mut args = Vec::new();
+    for part in middle {
+        let end = rest.find(part)?;
+        args.push(&rest[..end]);
+        rest = &rest[end + part.len()..];
+    }
+    args.push(rest.strip_suffix(last)?);
+    Some(args)
+}
+
+/// `tr` with each "{}" replaced by the next of `args`.
+pub fn trf(lang: Lang, en: &'static str, args: &[&dyn Display]) -> String {
+    let mut text = String::new();
+    let mut args = args.iter();
+    let mut parts = tr(lang, en).split("{}");
+    text.push_str(parts.next().unwrap_or_default());
+    for part in parts {
+        if let Some(arg) = args.next() {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The string literal at the start of `code` (after spaces), unescaped.
+    fn literal(code: &str) -> Option<String> {
+        let mut chars = code.trim_start().strip_prefix('"')?.chars();
+        let mut text = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Some(text),
+                '\\' => match chars.next()? {
+                    'n' => text.push('\n'),
+                    c => text.push(c),
+                },
+                c => text.push(c),
+            }
+        }
+        None
+    }
+
+    /// Literal texts passed to `tr`, `trf` and `t` (report.rs's shorthand).
+    fn translated_in(source: &str) -> Vec<String> {
+        let mut texts = Vec::new();
+        for call in ["tr(", "trf(", "t("] {
+            for (at, _) in source.match_indices(call) {
+                let before = source[..at].chars().next_back().unwrap_or(' ');
+                if before.is_alphanumeric() || before == '_' || source[..at].ends_with("fn ") {
+                    continue;
+                }
+                let args = &source[at + call.len()..];
+                let text = match call {
+                    "t(" => literal(args),
+                    // After the language
+                    _ => args.split_once(',').and_then(|(_, rest)| literal(rest)),
+                };
+                texts.extend(text);
+            }
+        }
+        texts
+    }
+
+    /// Format strings of the warnings pushed to a summary.
+    fn warnings_in(source: &str) -> Vec<String> {
+        let mut texts = Vec::new();
+        for (at, _) in source.match_indices("warnings.push(") {
+            let push = &source[at..];
+            let push = &push[..push.find(");\n").unwrap_or(push.len())];
+            texts.extend(
+                push.match_indices("format!(")
+                    .filter_map(|(at, call)| literal(&push[at + call.len()..])),
+            );
+        }
+        texts
+    }
+
+    #[test]
+    fn every_message_of_the_report_and_command_line_is_in_the_table() {
+        let translated = [
+            include_str!("report.rs"),
+            include_str!("bin/be-alert-convert.rs"),
+        ];
+        let texts: Vec<String> = translated.iter().flat_map(|s| translated_in(s)).collect();
+        assert!(texts.iter().any(|t| t == "Phone audit"), "{:?}", texts);
+        assert!(
+            texts.iter().any(|t| t == " {} rows rejected."),
+            "{:?}",
+            texts
+        );
+        let warned = [
+            include_str!("convert.rs"),
+            include_str!("batch.rs"),
+            include_str!("main.rs"),
+        ];
+        let warnings: Vec<String> = warned.iter().flat_map(|s| warnings_in(s)).collect();
+        assert!(warnings.len() >= 15, "{:?}", warnings);
+        for text in texts.iter().chain(&warnings) {
+            assert!(
+                TABLE.iter().any(|(en, _, _)| en == text),
+                "not in TABLE: {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn translate_recognizes_a_formatted_message() {
+        let quota = "1200 rows exceed the BE-Alert quota of 1000 contacts for alken: 200 rows over the limit would be dropped on upload";
+        assert_eq!(
+            translate(Lang::Nl, quota),
+            "1200 rijen overschrijden het BE-Alert-quotum van 1000 contacten voor alken: 200 rijen boven de limiet vallen weg bij het opladen"
+        );
+        assert_eq!(
+            translate(Lang::Fr, "\"Mobiel nummer\" is empty in 998 of 1000 rows"),
+            "\"Mobiel nummer\" est vide dans 998 lignes sur 1000"
+        );
+        assert_eq!(
+            translate(
+                Lang::Fr,
+                "3 Tel/Ref. numbers on more than one row (all rows kept)"
+            ),
+            "3 numéros Tél/Réf. sur plus d'une ligne (toutes les lignes conservées)"
+        );
+        assert_eq!(translate(Lang::En, quota), quota);
+        // Messages formatted outside a warnings.push
+        let column = crate::report::EmptyColumn {
+            column: "Naam".to_string(),
+            filled: 0,
+            rows: 10,
+        };
+        let delta = crate::history::RunDelta {
+            previous: "01/10/2026 09:00".to_string(),
+            added: 3,
+            removed: 1,
+            rows_before: 998,
+            new_warnings: 0,
+            same_file: true,
+        };
+        let count = crate::error::ConvertError::UnexpectedCount {
+            rows: 10,
+            expected: 1000,
+            tolerance: 5,
+        };
+        for text in [column.text(), delta.text(), count.to_string()] {
+            assert_ne!(translate(Lang::Nl, &text), text);
+        }
+        assert_eq!(
+            translate(Lang::Nl, "not a known message"),
+            "not a known message"
+        );
+    }
+}
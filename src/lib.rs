@@ -0,0 +1,46 @@
+// src/lib.rs
+// Conversion library, shared by the GUI (main.rs) and the
+// be-alert-convert command line (bin/be-alert-convert.rs).
+//
+// Feature `native` (default) adds the desktop-only parts; without it, and
+// with `wasm`, the library builds for the browser (see wasm.rs). Feature
+// `ffi` adds the C API (ffi.rs), `python` the Python module (python.rs).
+
+pub mod abbreviations;
+pub mod batch;
+#[cfg(feature = "native")]
+pub mod clipboard;
+pub mod column_stats;
+pub mod convert;
+pub mod dedup;
+pub mod dialect;
+pub mod email_domains;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gemeente;
+pub mod headerless;
+pub mod history;
+pub mod i18n;
+pub mod lock;
+pub mod package;
+pub mod paths;
+pub mod peek;
+pub mod pipeline;
+pub mod placeholder;
+pub mod presets;
+pub mod profile;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod report;
+pub mod reverse;
+pub mod rijksregister;
+pub mod schedule;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod session;
+pub mod settings;
+pub mod template;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
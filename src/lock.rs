@@ -0,0 +1,93 @@
+// src/lock.rs
+// Output lock
+// -----------
+//
+// Two clerks saving to the same CSV on the network share would otherwise
+// overwrite each other without notice: the second export silently wins.
+// While an export writes, a `<output>.lock` file next to the CSV names who is
+// writing; a second export to the same path is refused with that name (the
+// command line and the schedule wait and try again, as for a file open in
+// Excel). The lock file is created with create_new, which is atomic on SMB
+// shares as well, and removed when the export is done.
+//
+// A lock older than STALE_AFTER is left over from a crashed export and is
+// taken over.
+
+use crate::error::{ConvertError, Result};
+use crate::report::RunInfo;
+use chrono::Local;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Sidecar lock file of an output.
+pub fn lock_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+fn is_stale(lock: &Path) -> bool {
+    fs::metadata(lock)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_AFTER)
+}
+
+/// Held while an output is written; the lock file is removed on drop.
+pub struct OutputLock {
+    path: PathBuf,
+}
+
+impl OutputLock {
+    /// Lock `output` for this run, or `OutputLocked` naming who holds it.
+    pub fn acquire(output: &Path, run: &RunInfo) -> Result<OutputLock> {
+        let path = lock_path(output);
+        // Second attempt only after removing a stale lock
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let host = env::var("COMPUTERNAME")
+                        .or_else(|_| env::var("HOSTNAME"))
+                        .unwrap_or_default();
+                    // Best effort: an empty lock file still locks
+                    let _ = write!(
+                        file,
+                        "{} on {} since {}",
+                        if run.operator.is_empty() {
+                            "?"
+                        } else {
+                            &run.operator
+                        },
+                        if host.is_empty() { "?" } else { &host },
+                        Local::now().format("%d/%m/%Y %H:%M:%S")
+                    );
+                    return Ok(OutputLock { path });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if is_stale(&path) && fs::remove_file(&path).is_ok() {
+                        continue;
+                    }
+                    break;
+                }
+                Err(e) => return Err(ConvertError::io_at(&path, e)),
+            }
+        }
+        let holder = fs::read_to_string(&path).unwrap_or_default();
+        Err(ConvertError::OutputLocked {
+            path: output.to_path_buf(),
+            holder: holder.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
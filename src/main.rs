@@ -25,274 +25,1135 @@
 // - "Adres incl huisnummer" uses ONLY numeric part of Huisnummer (e.g. 11A -> 11, "12 Bus 3" -> 12)
 // - NOTE: Output columns "Voornaam" and "Naam" must be swapped (provider error)
 //
-// Fixed values:
+// Fixed values (built-in Alken profile, see profile.rs):
 // - Postcode = BE-3570
 // - Gemeente = Alken
 // - Taal = NL
 // - Land = BE
 // - Type Contact = P
+//
+// Optional "Aantal inwoners" input column (sources aggregated per address) is
+// copied to the output "Aantal inwoners" field when it holds a whole number.
+//
+// With several profiles selected, rows are split per profile on the optional
+// "Postcode" / "Gemeente" input columns and one CSV is written per profile.
 
 
 
-use anyhow::{anyhow, Result};
-use calamine::{open_workbook, Data, Reader, Xlsx};
-use csv::WriterBuilder;
-use rfd::FileDialog;
-use std::collections::HashMap;
-use std::path::Path;
-use slint::CloseRequestResponse;
-
-const REQUIRED_COLUMNS: [&str; 6] = [
-    "Voornaam",
-    "Naam",
-    "Straat",
-    "Huisnummer",
-    "Mobiel nummer",
-    "E-mailadres",
-];
+use be_alert_convert::convert::{
+    cell_to_string, check_expected, check_not_input, check_writable, convert_merge_to_csv, convert_xlsx_per_profile, diff_existing, file_stem,
+    header_columns, is_macro_workbook, open_first_sheet, prepare_export, prepare_xlsx_export,
+    prepare_xlsx_export_with_progress, resolve_duplicates, skip_boilerplate, validate_xlsx_columns, write_export, PreparedExport, EXCEL_EXTENSIONS,
+    OUTPUT_HEADER,
+};
+use be_alert_convert::dedup::{self, DuplicateAction, DuplicateGroup, Record};
+use be_alert_convert::error::{ConvertError, Result};
+use be_alert_convert::history::{ContactChange, History};
+use be_alert_convert::i18n::Lang;
+use be_alert_convert::profile::{self, Profile};
+use be_alert_convert::report::{column_name, ConvertSummary, EmptyColumn, RunInfo};
+use be_alert_convert::schedule::{self, Cron, Schedule};
+use be_alert_convert::session::{Overrides, Review, Session};
+use be_alert_convert::settings::{ColumnLayout, Settings, QUICK_EXPORT_SUFFIX};
+use be_alert_convert::{clipboard, column_stats, headerless, package, paths, peek, reverse, template, verify};
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use std::path::{Path, PathBuf};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::thread;
+use std::rc::Rc;
+use std::sync::mpsc::{self, TryRecvError};
+use std::time::{Duration, Instant};
+use std::{fs, io};
+use slint::{CloseRequestResponse, Model, ModelNotify, ModelTracker, SharedString, Timer, TimerMode, VecModel};
 
 slint::include_modules!();
 
-fn cell_to_string(cell: &Data) -> String {
-    match cell {
-        Data::String(s) => s.clone(),
-        Data::Float(f) => {
-            if f.fract() == 0.0 {
-                (*f as i64).to_string()
-            } else {
-                f.to_string()
-            }
+fn report_text(summary: &ConvertSummary) -> String {
+    summary
+        .report
+        .as_ref()
+        .map(|p| format!(" Report: {}", p.display()))
+        .unwrap_or_default()
+}
+
+/// Status line text for the GUI, tailored per error kind.
+fn error_status(e: &ConvertError) -> String {
+    match e {
+        ConvertError::MissingColumn { .. } | ConvertError::AmbiguousColumn { .. } => format!("XLSX error: {}", e),
+        ConvertError::UnexpectedColumns { .. } => format!("Export blocked. {}", e),
+        ConvertError::SwappedColumns { .. }
+        | ConvertError::TooManyRejected { .. }
+        | ConvertError::UnexpectedCount { .. }
+        | ConvertError::OutputIsInput { .. } => format!("Export blocked. {}", e),
+        ConvertError::OutputIncomplete { .. } => format!("Export failed. {}", e),
+        ConvertError::FileLocked { .. } | ConvertError::OutputLocked { .. } => {
+            format!("File in use: {}", e)
+        }
+        ConvertError::UnsupportedFormat { .. } => format!("Not a valid XLSX file. {}", e),
+        ConvertError::RowError { .. } => format!("Row error: {}", e),
+        ConvertError::InvalidProfileValue { .. } | ConvertError::Script { .. } => {
+            format!("Profile error: {}", e)
         }
-        Data::Int(i) => i.to_string(),
-        Data::Bool(b) => b.to_string(),
-        Data::Empty => String::new(),
-        _ => String::new(),
+        ConvertError::Cancelled => format!("Export stopped. {}", e),
+        ConvertError::IoError(_) => format!("Error: {}", e),
     }
 }
 
-fn get(cols: &HashMap<String, usize>, row: &[Data], name: &str) -> String {
-    cols.get(name)
-        .and_then(|&i| row.get(i))
-        .map(cell_to_string)
-        .unwrap_or_default()
-        .trim()
-        .to_string()
-}
-
-/// Keep only leading digits; stop at first non-digit.
-/// Examples:
-/// - "11A" -> "11"
-/// - "12 Bus 3" -> "12"
-fn extract_house_number(input: &str) -> String {
-    let mut digits = String::new();
-    for c in input.trim().chars() {
-        if c.is_ascii_digit() {
-            digits.push(c);
+/// "Close the file in Excel" dialog; true when the user chose Retry.
+fn ask_retry_locked(path: &Path) -> bool {
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("File in use")
+        .set_description(format!(
+            "{} is open in another program (probably Excel).\n\nClose the file in Excel and click Retry.",
+            path.display()
+        ))
+        .set_buttons(MessageButtons::OkCancelCustom("Retry".into(), "Cancel".into()))
+        .show();
+    // Only an explicit Retry: closing the dialog reports Ok or Cancel on some
+    // platforms and must not start another attempt
+    matches!(answer, MessageDialogResult::Yes) || answer == MessageDialogResult::Custom("Retry".into())
+}
+
+/// Operator, remark and expected number of contacts as entered above the
+/// export buttons ("12.345" as written in the register report counts too).
+fn run_info(ui: &MainWindow) -> RunInfo {
+    let expected: String = ui
+        .get_expected_contacts()
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+    RunInfo {
+        operator: ui.get_operator().trim().to_string(),
+        remark: ui.get_remark().trim().to_string(),
+        expected_contacts: expected.parse().ok(),
+        lang: Lang::from_env(),
+    }
+}
+
+/// For a list whose first row is data: guess the column roles and ask the
+/// user to confirm them, unless a list with the same layout was confirmed
+/// before. Some((columns, remembered)) when confirmed, for `input_columns`.
+fn confirm_headerless(path: &str, settings: &RefCell<Settings>) -> Option<(Vec<String>, bool)> {
+    let range = open_first_sheet(path).ok()?;
+    if !headerless::looks_headerless(range.rows().next()?) {
+        return None;
+    }
+    let columns = headerless::guess_columns(range.rows());
+    let missing = headerless::missing_roles(&columns);
+    if !missing.is_empty() {
+        MessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("No header row")
+            .set_description(format!(
+                "The first row of this file is a contact, not column names, and these columns could not be recognised: {}.\n\nAdd a header row in Excel, or set input_columns in the profile.",
+                missing.join(", ")
+            ))
+            .set_buttons(MessageButtons::Ok)
+            .show();
+        return None;
+    }
+    let fingerprint = headerless::fingerprint(&columns);
+    if let Some(remembered) = settings.borrow().remembered_mapping(&fingerprint) {
+        return Some((remembered.columns.clone(), true));
+    }
+    let changed = if settings.borrow().layout_changed(Path::new(path), &fingerprint) {
+        "The columns of this file changed since its mapping was confirmed. "
+    } else {
+        ""
+    };
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Info)
+        .set_title("No header row")
+        .set_description(format!(
+            "The first row of this file is a contact, not column names. {}The columns look like:\n\n{}\n\nConvert every row with these columns?",
+            changed,
+            headerless::mapping_text(&columns)
+        ))
+        .set_buttons(MessageButtons::YesNo)
+        .show();
+    if answer != MessageDialogResult::Yes {
+        return None;
+    }
+    settings
+        .borrow_mut()
+        .remember_mapping(Path::new(path), &columns, fingerprint);
+    // Not saved: asked again next time
+    let _ = settings.borrow().save();
+    Some((columns, false))
+}
+
+/// Columns confirmed for a list without header row, unless the profile sets its own.
+fn apply_input_columns(profile: &mut Profile, confirmed: &[String]) {
+    if profile.input_columns.is_empty() {
+        profile.input_columns = confirmed.to_vec();
+    }
+}
+
+/// A column name the conversion reads appears more than once in the header
+/// (two "Naam", one of them the maiden name): show how full each column is
+/// and what is in it, and let the user choose. Some(n) (1-based, as in the
+/// profile's `[duplicate_columns]`) when chosen.
+fn choose_duplicate_column(path: &str, profile: &Profile, name: &str, columns: &[usize]) -> Option<usize> {
+    let range = open_first_sheet(path).ok()?;
+    let rows: Vec<_> = skip_boilerplate(range.rows(), profile).skip(1).collect();
+    let labels: Vec<String> = columns.iter().map(|&c| format!("Column {}", column_name(c))).collect();
+    let lines: Vec<String> = columns
+        .iter()
+        .zip(&labels)
+        .map(|(&c, label)| {
+            let values: Vec<String> = rows
+                .iter()
+                .filter_map(|row| row.get(c))
+                .map(|cell| cell_to_string(cell).trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect();
+            let examples: Vec<&str> = values.iter().take(3).map(String::as_str).collect();
+            format!("{}: {} of {} rows filled, e.g. {}", label, values.len(), rows.len(), examples.join(", "))
+        })
+        .collect();
+    if columns.len() != 2 {
+        MessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("Column found more than once")
+            .set_description(format!(
+                "This file has {} columns named \"{}\":\n\n{}\n\nChoose the one to read in the profile's [duplicate_columns] (\"{}\" = 1 for the leftmost).",
+                columns.len(),
+                name,
+                lines.join("\n"),
+                name
+            ))
+            .set_buttons(MessageButtons::Ok)
+            .show();
+        return None;
+    }
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Column found more than once")
+        .set_description(format!(
+            "This file has two columns named \"{}\" (one may be a maiden name or an old value):\n\n{}\n\nWhich one should be read as \"{}\"?",
+            name,
+            lines.join("\n"),
+            name
+        ))
+        .set_buttons(MessageButtons::YesNoCancelCustom(labels[0].clone(), labels[1].clone(), "Cancel".into()))
+        .show();
+    match answer {
+        MessageDialogResult::Yes => Some(1),
+        MessageDialogResult::No => Some(2),
+        MessageDialogResult::Custom(label) => labels.iter().position(|l| *l == label).map(|i| i + 1),
+        _ => None,
+    }
+}
+
+/// Columns chosen for duplicated header names, unless the profile chooses itself.
+fn apply_column_choices(profile: &mut Profile, chosen: &BTreeMap<String, usize>) {
+    for (name, &n) in chosen {
+        profile.duplicate_columns.entry(name.clone()).or_insert(n);
+    }
+}
+
+/// Run `op` again for as long as it fails on a file open in Excel and the user chooses Retry.
+fn retry_if_locked<T>(mut op: impl FnMut() -> Result<T>) -> Result<T> {
+    loop {
+        match op() {
+            Err(ConvertError::FileLocked { ref path }) if ask_retry_locked(path) => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Before overwriting a CSV, what the export changes in it ("12 rows added,
+/// 3 removed, ..."), so a re-export of the wrong input is caught; true to go on.
+fn confirm_overwrite(prepared: &PreparedExport, out: &Path) -> bool {
+    let comparison = match diff_existing(prepared, out) {
+        Ok(None) => return true,
+        Ok(Some(diff)) => format!("Compared with it, this export has {}.", diff.text()),
+        Err(e) => format!("It cannot be compared with this export ({}).", e),
+    };
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Overwrite CSV?")
+        .set_description(format!(
+            "{} already exists. {}\n\nOverwrite it?",
+            out.display(),
+            comparison
+        ))
+        .set_buttons(MessageButtons::YesNo)
+        .show();
+    answer == MessageDialogResult::Yes
+}
+
+/// Outcome of the import checks of the current XLSX: its data rows and the
+/// (almost) empty required columns, or the problem found. Shown again before
+/// export, so the file is not read a second time on the UI thread.
+type ImportCheck = std::result::Result<(usize, Vec<EmptyColumn>), String>;
+
+/// What an export is about to write, confirmed before its output is chosen:
+/// the rows, the profiles with their fixed values and what the checks of the
+/// import still find, so a file that failed them is not exported by a
+/// reflex click. True to go on.
+fn confirm_export(
+    input: &str,
+    check: Option<&ImportCheck>,
+    profiles: &[Profile],
+    merge_file: &str,
+    run: &RunInfo,
+) -> bool {
+    let mut lines = Vec::new();
+    let mut problems = Vec::new();
+    match check {
+        Some(Ok((rows, empty))) => {
+            let rows = *rows;
+            lines.push(format!("{} data rows in {}", rows, input));
+            problems.extend(empty.iter().map(EmptyColumn::text));
+            let tolerance = profiles[0].expected_tolerance_percent;
+            if let Some(expected) = run.expected_contacts {
+                // Rows, not contacts yet: the export checks the contacts it writes
+                if check_expected(rows, Some(expected), tolerance).is_err() {
+                    problems.push(format!(
+                        "the register counts {} contacts and the file has {} data rows; the export is refused when the contacts written differ more than {}%",
+                        expected, rows, tolerance
+                    ));
+                }
+            }
+            if let Some(quota) = profiles[0].quota.filter(|&quota| profiles.len() == 1 && rows > quota) {
+                problems.push(format!("{} rows, over the BE-Alert quota of {} contacts", rows, quota));
+            }
+        }
+        Some(Err(problem)) => {
+            lines.push(input.to_string());
+            problems.push(problem.clone());
+        }
+        None => {
+            lines.push(input.to_string());
+            problems.push("the file was not checked on import; import it again".to_string());
+        }
+    }
+    if !merge_file.is_empty() {
+        lines.push(format!("merged with {}", merge_file));
+    }
+    for profile in profiles {
+        lines.push(format!(
+            "Profile {}: Postcode {}, Gemeente {}, Taal {}, Land {}, Type Contact {}",
+            profile.name, profile.postcode, profile.gemeente, profile.taal, profile.land, profile.type_contact
+        ));
+    }
+    let outstanding = if problems.is_empty() {
+        "The import checks found no problems.".to_string()
+    } else {
+        format!("Not resolved:\n- {}", problems.join("\n- "))
+    };
+    let answer = MessageDialog::new()
+        .set_level(if problems.is_empty() {
+            MessageLevel::Info
         } else {
-            break;
+            MessageLevel::Warning
+        })
+        .set_title("Export CSV")
+        .set_description(format!("{}\n\n{}\n\nExport?", lines.join("\n"), outstanding))
+        .set_buttons(MessageButtons::OkCancelCustom("Export".into(), "Cancel".into()))
+        .show();
+    matches!(answer, MessageDialogResult::Ok) || answer == MessageDialogResult::Custom("Export".into())
+}
+
+/// After an export to several CSVs: offer to bundle them with the report and
+/// a manifest into one ZIP for the colleague doing the upload (see
+/// package.rs). Returns the ZIP when it was written.
+fn offer_package(ui: &MainWindow, dir: &Path, name: &str, files: &[PathBuf]) -> Option<PathBuf> {
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Info)
+        .set_title("Package for upload")
+        .set_description(format!(
+            "{} files were written to {}.\n\nBundle them with a manifest into one ZIP for the upload?",
+            files.len(),
+            dir.display()
+        ))
+        .set_buttons(MessageButtons::YesNo)
+        .show();
+    if answer != MessageDialogResult::Yes {
+        return None;
+    }
+    let zip = package::package_path(dir, name, chrono::Local::now());
+    match package::write_package(&zip, dir, files, &run_info(ui)) {
+        Ok(()) => {
+            ui.set_status(format!("{} Packaged in {}.", ui.get_status(), zip.display()).into());
+            Some(zip)
+        }
+        Err(e) => {
+            ui.set_status(format!("CSV files saved, but not packaged. {}", error_status(&e)).into());
+            None
         }
     }
-    digits
 }
 
-/// Belgium-style normalization:
-/// - "+32..." -> "0032..."
-/// - "0..."   -> "0032..." (drop leading 0)
-/// - strips spaces/dashes/etc (keeps digits and leading '+')
-fn normalize_be_phone(input: &str) -> String {
-    let mut s: String = input
-        .trim()
-        .chars()
-        .filter(|c| c.is_ascii_digit() || *c == '+')
+/// Write a prepared export and show the result, first confirming an overwrite
+/// and making sure an existing CSV is not open in Excel (the prepared rows
+/// cannot be re-read once writing failed). Returns the files written.
+fn write_checked(ui: &MainWindow, prepared: PreparedExport, out: &Path) -> LastExport {
+    if let Err(e) = check_not_input(out, &prepared.inputs) {
+        return show_export_result(ui, out, Err(e));
+    }
+    if !confirm_overwrite(&prepared, out) {
+        ui.set_status(format!("Export cancelled; {} was left as it was.", out.display()).into());
+        show_export_step(ui, None);
+        return LastExport::default();
+    }
+    let result = retry_if_locked(|| check_writable(out))
+        .and_then(|()| write_export(prepared, out.to_str().unwrap()));
+    show_export_result(ui, out, result)
+}
+
+/// State of the window's conversion task: idle -> validating -> converting -> done/failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskState {
+    Idle,
+    Validating,
+    Converting,
+    Done,
+    Failed,
+}
+
+impl TaskState {
+    /// Name of the state as seen by the window (`task_state` property).
+    fn name(self) -> &'static str {
+        match self {
+            TaskState::Idle => "idle",
+            TaskState::Validating => "validating",
+            TaskState::Converting => "converting",
+            TaskState::Done => "done",
+            TaskState::Failed => "failed",
+        }
+    }
+
+    fn busy(self) -> bool {
+        matches!(self, TaskState::Validating | TaskState::Converting)
+    }
+}
+
+/// Owns the one conversion the window runs at a time. While it is busy the
+/// buttons that convert are disabled, and a second Export (e.g. clicked while
+/// a file dialog runs its own event loop) is refused instead of writing the
+/// same file twice.
+struct TaskManager {
+    state: Cell<TaskState>,
+    ui: slint::Weak<MainWindow>,
+}
+
+impl TaskManager {
+    fn set(&self, state: TaskState) {
+        self.state.set(state);
+        if state == TaskState::Done {
+            // Exported: nothing left to restore after a crash
+            Session::clear();
+        }
+        if let Some(ui) = self.ui.upgrade() {
+            ui.set_task_state(state.name().into());
+        }
+    }
+
+    /// Start a task; None (and a status message) while another one runs.
+    fn begin(self: &Rc<Self>) -> Option<Task> {
+        if self.state.get().busy() {
+            if let Some(ui) = self.ui.upgrade() {
+                ui.set_status("A conversion is still running; wait for it to finish.".into());
+            }
+            return None;
+        }
+        self.set(TaskState::Validating);
+        Some(Task {
+            manager: self.clone(),
+            ended: false,
+        })
+    }
+}
+
+/// The running task. Dropped without `finish` (nothing chosen, dialog
+/// cancelled) it returns the manager to idle.
+struct Task {
+    manager: Rc<TaskManager>,
+    ended: bool,
+}
+
+impl Task {
+    fn converting(&self) {
+        self.manager.set(TaskState::Converting);
+    }
+
+    fn finish(mut self, ok: bool) {
+        self.ended = true;
+        self.manager.set(if ok { TaskState::Done } else { TaskState::Failed });
+    }
+
+    /// Keep the task converting after the handler returns; whoever takes over
+    /// (the duplicate review) ends it through the manager.
+    fn hand_over(mut self) {
+        self.ended = true;
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.manager.set(TaskState::Idle);
+        }
+    }
+}
+
+/// Message from the conversion thread to the window.
+enum Background {
+    /// Rows done, data rows in total.
+    Progress(usize, usize),
+    Done(Box<Result<PreparedExport>>),
+}
+
+/// 80000 -> "80.000"
+fn thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut text = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            text.push('.');
+        }
+        text.push(c);
+    }
+    text
+}
+
+/// "12.500 / 80.000 rows, ~35s remaining", the estimate from the rate so far.
+fn progress_text(done: usize, total: usize, elapsed: Duration) -> String {
+    let rows = format!("Converting: {} / {} rows", thousands(done), thousands(total));
+    // Too early for a useful rate
+    if done == 0 || elapsed < Duration::from_secs(1) {
+        return format!("{}.", rows);
+    }
+    let remaining = elapsed.as_secs_f64() * (total - done) as f64 / done as f64;
+    if remaining < 90.0 {
+        format!("{}, ~{}s remaining.", rows, remaining.ceil() as u64)
+    } else {
+        format!("{}, ~{} min remaining.", rows, (remaining / 60.0).ceil() as u64)
+    }
+}
+
+/// Read and convert the rows of `input` on a worker thread, so the window
+/// keeps repainting on large files; the status line shows the rows done and
+/// the time left. `done` gets the result on the UI thread. `timer` polls the
+/// worker; one export runs at a time (TaskManager), so one timer serves all.
+fn prepare_in_background(
+    ui: &MainWindow,
+    timer: &Rc<Timer>,
+    input: String,
+    profile: Profile,
+    done: impl FnOnce(&MainWindow, Result<PreparedExport>) + 'static,
+) {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = prepare_xlsx_export_with_progress(&input, &profile, &|rows, total| {
+            let _ = tx.send(Background::Progress(rows, total));
+        });
+        let _ = tx.send(Background::Done(Box::new(result)));
+    });
+
+    let ui_handle = ui.as_weak();
+    let weak_timer = Rc::downgrade(timer);
+    let mut done = Some(done);
+    // From the first progress report: reading the workbook is not counted
+    let mut started: Option<Instant> = None;
+    timer.start(TimerMode::Repeated, Duration::from_millis(200), move || {
+        let Some(ui) = ui_handle.upgrade() else {
+            return;
+        };
+        let result = loop {
+            match rx.try_recv() {
+                Ok(Background::Progress(rows, total)) => {
+                    let started = *started.get_or_insert_with(Instant::now);
+                    ui.set_status(progress_text(rows, total, started.elapsed()).into());
+                }
+                Ok(Background::Done(result)) => break *result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    break Err(ConvertError::IoError(io::Error::other(
+                        "the conversion stopped unexpectedly",
+                    )))
+                }
+            }
+        };
+        if let Some(timer) = weak_timer.upgrade() {
+            timer.stop();
+        }
+        if let Some(done) = done.take() {
+            done(&ui, result);
+        }
+    });
+}
+
+/// An export waiting for the duplicate review.
+struct PendingExport {
+    prepared: PreparedExport,
+    groups: Vec<DuplicateGroup>,
+    out: PathBuf,
+}
+
+/// Problems shown in the Verify CSV dialog; the rest is counted.
+const VERIFY_DIALOG_PROBLEMS: usize = 25;
+
+/// Check an existing BE-Alert CSV and show what is wrong with it.
+fn verify_csv_file(ui: &MainWindow, path: &Path) {
+    let result = match verify::verify_csv(path) {
+        Ok(result) => result,
+        Err(e) => {
+            ui.set_status(error_status(&e).into());
+            return;
+        }
+    };
+    ui.set_status(format!("Verify {}: {}.", path.display(), result.summary()).into());
+    if result.is_ok() {
+        return;
+    }
+    let mut lines: Vec<String> = result
+        .problems
+        .iter()
+        .take(VERIFY_DIALOG_PROBLEMS)
+        .map(verify::Problem::text)
+        .collect();
+    if result.problems.len() > VERIFY_DIALOG_PROBLEMS {
+        lines.push(format!("... and {} more", result.problems.len() - VERIFY_DIALOG_PROBLEMS));
+    }
+    MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Verify CSV")
+        .set_description(format!(
+            "{}: {}. Correct these before uploading:\n\n{}",
+            path.display(),
+            result.summary(),
+            lines.join("\n")
+        ))
+        .set_buttons(MessageButtons::Ok)
+        .show();
+}
+
+/// Rows of a quick peek shown in its dialog (the peek reads PEEK_ROWS).
+const PEEK_DIALOG_ROWS: usize = 10;
+
+/// Header and first rows of an XLSX, read without loading the whole file:
+/// for finding the right export on a slow network share before importing it.
+fn peek_xlsx_file(ui: &MainWindow, path: &Path, profile: &Profile) {
+    let peek = match peek::peek_xlsx(path, profile, peek::PEEK_ROWS) {
+        Ok(peek) => peek,
+        Err(e) => {
+            ui.set_status(error_status(&e).into());
+            return;
+        }
+    };
+    ui.set_status(format!("Peek {}: {}.", path.display(), peek.text()).into());
+    let lines: Vec<String> = std::iter::once(&peek.header)
+        .chain(peek.rows.iter().take(PEEK_DIALOG_ROWS))
+        .map(|row| row.join(" | "))
         .collect();
+    MessageDialog::new()
+        .set_level(if peek.problem.is_some() {
+            MessageLevel::Warning
+        } else {
+            MessageLevel::Info
+        })
+        .set_title("Quick peek")
+        .set_description(format!(
+            "{}: {}.\n\n{}\n\nNothing was imported; use Import XLSX to convert this file.",
+            path.display(),
+            peek.text(),
+            lines.join("\n")
+        ))
+        .set_buttons(MessageButtons::Ok)
+        .show();
+}
+
+/// Keeps session.toml in step with the window, so a crash mid-review can be
+/// restored at the next start (see session.rs).
+struct SessionKeeper {
+    ui: slint::Weak<MainWindow>,
+    profiles: Rc<Vec<Profile>>,
+    profile_model: Rc<VecModel<ProfileItem>>,
+    input_columns: Rc<RefCell<Vec<String>>>,
+    column_choices: Rc<RefCell<BTreeMap<String, usize>>>,
+    import_check: Rc<RefCell<Option<ImportCheck>>>,
+    pending: Rc<RefCell<Option<PendingExport>>>,
+    duplicate_model: Rc<VecModel<DuplicateGroupItem>>,
+}
 
-    if s.is_empty() {
-        return String::new();
+impl SessionKeeper {
+    /// Save the window's state; only a chosen file on disk is worth restoring.
+    fn save(&self) {
+        let Some(ui) = self.ui.upgrade() else {
+            return;
+        };
+        let input_file = ui.get_input_file().to_string();
+        if !Path::new(&input_file).is_file() {
+            Session::clear();
+            return;
+        }
+        let review = self.pending.borrow().as_ref().map(|pending| Review {
+            out: pending.out.clone(),
+            choices: pending
+                .groups
+                .iter()
+                .zip(self.duplicate_model.iter())
+                .map(|(group, item)| (group.tel_ref.clone(), item.choice))
+                .collect(),
+        });
+        let session = Session {
+            input_file,
+            profiles: self
+                .profile_model
+                .iter()
+                .filter(|item| item.selected)
+                .map(|item| item.name.to_string())
+                .collect(),
+            input_columns: self.input_columns.borrow().clone(),
+            column_choices: self.column_choices.borrow().clone(),
+            merge_file: ui.get_merge_file().to_string(),
+            merge_profile: self
+                .profiles
+                .get(ui.get_merge_profile_index() as usize)
+                .map(|p| p.name.clone())
+                .unwrap_or_default(),
+            merge_second_wins: ui.get_merge_second_wins(),
+            overrides: Overrides {
+                postcode: ui.get_override_postcode().to_string(),
+                gemeente: ui.get_override_gemeente().to_string(),
+                taal: ui.get_override_taal().to_string(),
+                land: ui.get_override_land().to_string(),
+                type_contact: ui.get_override_type_contact().to_string(),
+            },
+            operator: ui.get_operator().to_string(),
+            remark: ui.get_remark().to_string(),
+            review,
+        };
+        // Best effort: without the file only the restore is lost
+        let _ = session.save();
     }
 
-    if s.starts_with("+32") {
-        return format!("0032{}", &s[3..]);
+    /// Put a saved session back in the window. Returns the duplicate review
+    /// to reopen, if the file still validates.
+    fn restore(&self, session: Session) -> Option<Review> {
+        let ui = self.ui.upgrade()?;
+        for (i, mut item) in self.profile_model.iter().enumerate() {
+            item.selected = session.profiles.iter().any(|name| name == item.name.as_str());
+            self.profile_model.set_row_data(i, item);
+        }
+        if let Some(index) = self.profiles.iter().position(|p| p.name == session.merge_profile) {
+            ui.set_merge_profile_index(index as i32);
+        }
+        ui.set_input_file(session.input_file.clone().into());
+        ui.set_merge_file(session.merge_file.into());
+        ui.set_merge_second_wins(session.merge_second_wins);
+        ui.set_override_postcode(session.overrides.postcode.into());
+        ui.set_override_gemeente(session.overrides.gemeente.into());
+        ui.set_override_taal(session.overrides.taal.into());
+        ui.set_override_land(session.overrides.land.into());
+        ui.set_override_type_contact(session.overrides.type_contact.into());
+        ui.set_operator(session.operator.into());
+        ui.set_remark(session.remark.into());
+        *self.input_columns.borrow_mut() = session.input_columns.clone();
+        *self.column_choices.borrow_mut() = session.column_choices.clone();
+
+        let mut profile = selected_profiles(&self.profile_model, &self.profiles)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        apply_input_columns(&mut profile, &session.input_columns);
+        apply_column_choices(&mut profile, &session.column_choices);
+        let validated = validate_xlsx_columns(&session.input_file, &profile);
+        *self.import_check.borrow_mut() = Some(validated.as_ref().map(Clone::clone).map_err(ToString::to_string));
+        match validated {
+            Ok((rows, _)) => {
+                show_import_step(&ui, StepState::Ok, format!("{} rows", rows));
+                ui.set_status("Previous session restored.".into());
+                session.review
+            }
+            Err(e) => {
+                show_import_step(&ui, StepState::Failed, String::new());
+                ui.set_status(format!("Previous session restored. {}", error_status(&e)).into());
+                None
+            }
+        }
     }
+}
 
-    if s.starts_with('0') {
-        s.remove(0);
-        return format!("0032{}", s);
+/// Ask whether to continue the session a crash left behind.
+fn confirm_restore(session: &Session) -> bool {
+    let review = match &session.review {
+        Some(review) => format!(
+            "\n\nThe duplicate review was open ({} choices made) and will be reopened.",
+            review.choices.values().filter(|&&choice| choice != 0).count()
+        ),
+        None => String::new(),
+    };
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Info)
+        .set_title("Restore session")
+        .set_description(format!(
+            "The convertor was not closed normally while working on:\n\n{}{}\n\nRestore that session?",
+            session.input_file, review
+        ))
+        .set_buttons(MessageButtons::YesNo)
+        .show();
+    answer == MessageDialogResult::Yes
+}
+
+/// Dialog rows for the duplicate groups. Choices per group: keep the first
+/// row, keep one of the other rows, merge fields, keep all (see `duplicate_action`).
+fn duplicate_items(records: &[Record], groups: &[DuplicateGroup]) -> Vec<DuplicateGroupItem> {
+    groups
+        .iter()
+        .map(|group| {
+            let rows: Vec<usize> = group.members.iter().map(|&m| records[m].0).collect();
+            let details: Vec<String> = group
+                .members
+                .iter()
+                .map(|&m| {
+                    let (row, r) = &records[m];
+                    // Naam, Voornaam, Adres, Email
+                    format!("Row {}: {} {}, {} {}", row, r[2], r[3], r[4], r[9])
+                })
+                .collect();
+            let mut options = vec![SharedString::from(format!("Keep first (row {})", rows[0]))];
+            options.extend(rows[1..].iter().map(|row| format!("Keep row {}", row).into()));
+            options.push("Merge fields into first".into());
+            options.push("Keep all".into());
+            DuplicateGroupItem {
+                title: format!("Tel/Ref. {} ({} rows)", group.tel_ref, rows.len()).into(),
+                details: details.join("\n").into(),
+                options: Rc::new(VecModel::from(options)).into(),
+                choice: 0,
+            }
+        })
+        .collect()
+}
+
+/// Choices of a restored review, matched on Tel/Ref. (the file may have changed).
+fn restore_choices(
+    model: &VecModel<DuplicateGroupItem>,
+    groups: &[DuplicateGroup],
+    review: &Review,
+) {
+    for (index, group) in groups.iter().enumerate() {
+        let saved = review.choices.get(&group.tel_ref);
+        if let (Some(&choice), Some(mut item)) = (saved, model.row_data(index)) {
+            item.choice = choice.clamp(0, item.options.row_count() as i32 - 1);
+            model.set_row_data(index, item);
+        }
     }
+}
 
-    if s.starts_with('4') {
-        return format!("0032{}", s);
+fn duplicate_action(choice: usize, members: usize) -> DuplicateAction {
+    match choice {
+        0 => DuplicateAction::KeepFirst,
+        c if c < members => DuplicateAction::Keep(c),
+        c if c == members => DuplicateAction::Merge,
+        _ => DuplicateAction::KeepAll,
     }
+}
 
-    s
+/// Profiles ticked in the profile list, in list order.
+fn selected_profiles(model: &VecModel<ProfileItem>, profiles: &[Profile]) -> Vec<Profile> {
+    model
+        .iter()
+        .zip(profiles)
+        .filter(|(item, _)| item.selected)
+        .map(|(_, p)| p.clone())
+        .collect()
 }
 
-fn validate_xlsx_columns(input_xlsx: &str) -> Result<()> {
-    let mut workbook: Xlsx<_> = open_workbook(input_xlsx)?;
-    let range = workbook
-        .worksheet_range_at(0)
-        .ok_or_else(|| anyhow!("No sheet found in XLSX"))??;
+/// Fixed values typed in the window override the profile for this session.
+/// When splitting over several profiles, Postcode and Gemeente stay those of
+/// each profile: they decide which rows go where.
+fn apply_overrides(ui: &MainWindow, profile: &mut Profile, split: bool) {
+    let fields = [
+        (ui.get_override_postcode(), &mut profile.postcode, !split),
+        (ui.get_override_gemeente(), &mut profile.gemeente, !split),
+        (ui.get_override_taal(), &mut profile.taal, true),
+        (ui.get_override_land(), &mut profile.land, true),
+        (ui.get_override_type_contact(), &mut profile.type_contact, true),
+    ];
+    for (value, field, applies) in fields {
+        let value = value.trim();
+        if applies && !value.is_empty() {
+            *field = value.to_string();
+        }
+    }
+}
 
-    let mut rows = range.rows();
-    let header = rows
-        .next()
-        .ok_or_else(|| anyhow!("Empty sheet (no header row)"))?;
+/// Contacts of the open preview. The grid shows every output field, in
+/// the column order the user arranged.
+#[derive(Default)]
+struct PreviewState {
+    records: Vec<Record>,
+    changes: Vec<Option<ContactChange>>,
+    /// Output field (index into OUTPUT_HEADER) of each display column.
+    order: Vec<usize>,
+}
+
+fn default_column_width(field: usize) -> f32 {
+    // Adres incl huisnummer and Email hold the longest values
+    if field == 4 || field == 9 {
+        170.0
+    } else {
+        110.0
+    }
+}
 
-    let mut cols: HashMap<String, usize> = HashMap::new();
-    for (i, cell) in header.iter().enumerate() {
-        let name = cell_to_string(cell).trim().to_string();
-        if !name.is_empty() {
-            cols.insert(name, i);
+/// Columns of the preview grid: the saved layout when it still names every
+/// output column, otherwise the output order with the columns that are empty
+/// in every row hidden.
+fn preview_columns(saved: &[ColumnLayout], records: &[Record]) -> (Vec<usize>, Vec<PreviewColumn>) {
+    let fields: Option<Vec<usize>> = saved
+        .iter()
+        .map(|c| OUTPUT_HEADER.iter().position(|h| *h == c.name))
+        .collect();
+    if let Some(order) = fields.filter(|f| {
+        f.len() == OUTPUT_HEADER.len() && (0..OUTPUT_HEADER.len()).all(|i| f.contains(&i))
+    }) {
+        let columns = saved
+            .iter()
+            .map(|c| PreviewColumn {
+                title: c.name.as_str().into(),
+                width: c.width as f32,
+                visible: c.visible,
+            })
+            .collect();
+        return (order, columns);
+    }
+    let columns = OUTPUT_HEADER
+        .iter()
+        .enumerate()
+        .map(|(field, name)| PreviewColumn {
+            title: (*name).into(),
+            width: default_column_width(field),
+            visible: records.iter().any(|(_, r)| !r[field].is_empty()),
+        })
+        .collect();
+    ((0..OUTPUT_HEADER.len()).collect(), columns)
+}
+
+/// Grid rows, built when the ListView asks for them: only the rows in view
+/// are ever made, so a province file of 80 000 contacts previews as fast as
+/// a street list. Each row has all output fields in display order and the
+/// change since the last upload.
+struct PreviewRows {
+    state: Rc<RefCell<PreviewState>>,
+    /// Records shown (indexes into `state.records`): all of them, or only
+    /// the changed contacts.
+    shown: RefCell<Vec<usize>>,
+    notify: ModelNotify,
+}
+
+impl PreviewRows {
+    fn new(state: Rc<RefCell<PreviewState>>) -> Self {
+        PreviewRows {
+            state,
+            shown: RefCell::new(Vec::new()),
+            notify: ModelNotify::default(),
         }
     }
 
-    for required in REQUIRED_COLUMNS {
-        if !cols.contains_key(required) {
-            return Err(anyhow!("Missing required XLSX column: {}", required));
+    /// Show the current state again (new records, column order, filter).
+    /// The state must not be borrowed mutably while the grid redraws.
+    fn refresh(&self, changed_only: bool) {
+        *self.shown.borrow_mut() = self
+            .state
+            .borrow()
+            .changes
+            .iter()
+            .enumerate()
+            .filter(|(_, change)| !changed_only || change.is_some())
+            .map(|(i, _)| i)
+            .collect();
+        self.notify.reset();
+    }
+}
+
+impl Model for PreviewRows {
+    type Data = PreviewRow;
+
+    fn row_count(&self) -> usize {
+        self.shown.borrow().len()
+    }
+
+    fn row_data(&self, row: usize) -> Option<PreviewRow> {
+        let i = *self.shown.borrow().get(row)?;
+        let state = self.state.borrow();
+        let (_, record) = state.records.get(i)?;
+        Some(PreviewRow {
+            cells: Rc::new(VecModel::from(
+                state
+                    .order
+                    .iter()
+                    .map(|&field| SharedString::from(record[field].as_str()))
+                    .collect::<Vec<_>>(),
+            ))
+            .into(),
+            change: state.changes[i].as_ref().map(ContactChange::text).unwrap_or_default().into(),
+        })
+    }
+
+    fn model_tracker(&self) -> &dyn ModelTracker {
+        &self.notify
+    }
+}
+
+/// Keep the arranged preview columns in settings.toml for the next preview.
+fn save_preview_layout(ui: &MainWindow, settings: &RefCell<Settings>, columns: &VecModel<PreviewColumn>) {
+    settings.borrow_mut().preview_columns = columns
+        .iter()
+        .map(|c| ColumnLayout {
+            name: c.title.to_string(),
+            visible: c.visible,
+            width: c.width.round().max(0.0) as u32,
+        })
+        .collect();
+    if let Err(e) = settings.borrow().save() {
+        ui.set_status(format!("Preview layout not saved: {}", e).into());
+    }
+}
+
+/// Check mark and counts of the import step.
+fn show_import_step(ui: &MainWindow, state: StepState, detail: String) {
+    ui.set_import_state(state);
+    ui.set_import_detail(detail.into());
+}
+
+/// Check mark and counts of the export step (None: failed). Partial when rows
+/// were rejected: the CSV was saved, but not every contact is in it.
+fn show_export_step(ui: &MainWindow, summary: Option<&ConvertSummary>) {
+    let (state, detail) = match summary {
+        None => (StepState::Failed, String::new()),
+        Some(s) if s.rejected.is_empty() => (StepState::Ok, format!("{} rows", s.rows)),
+        Some(s) => (
+            StepState::Partial,
+            format!("{} rows, {} rejected", s.rows, s.rejected.len()),
+        ),
+    };
+    ui.set_export_state(state);
+    ui.set_export_detail(detail.into());
+}
+
+/// Back to "not run yet" for the export step.
+fn clear_export_step(ui: &MainWindow) {
+    ui.set_export_state(StepState::None);
+    ui.set_export_detail("".into());
+}
+
+/// Status and check marks after a single-CSV export.
+/// Returns the files written (CSV and report), for "Undo export".
+fn show_export_result(ui: &MainWindow, out: &Path, result: Result<ConvertSummary>) -> LastExport {
+    match result {
+        Ok(summary) => {
+            ui.set_output_file(out.display().to_string().into());
+            ui.set_status(
+                format!(
+                    "CSV saved ({} rows).{}{}",
+                    summary.rows,
+                    summary.warning_text(),
+                    report_text(&summary)
+                )
+                .into(),
+            );
+            show_export_step(ui, Some(&summary));
+            LastExport {
+                history_run: summary.history_run,
+                files: std::iter::once(out.to_path_buf())
+                    .chain(summary.report)
+                    .chain(summary.review_file)
+                    .chain(summary.rejected_file)
+                    .chain(summary.delete_file.map(|(file, _)| file))
+                    .collect(),
+            }
+        }
+        Err(e) => {
+            ui.set_status(error_status(&e).into());
+            show_export_step(ui, None);
+            LastExport::default()
         }
     }
+}
 
-    Ok(())
+/// Files and history run of the last successful export, for "Undo export".
+#[derive(Default)]
+struct LastExport {
+    files: Vec<PathBuf>,
+    /// Run stored in the history, taken back on undo so the next export of
+    /// the file is not compared with one that was undone.
+    history_run: Option<i64>,
 }
 
-fn convert_xlsx_to_csv(input_xlsx: &str, output_csv: &str) -> Result<()> {
-    let mut workbook: Xlsx<_> = open_workbook(input_xlsx)?;
-    let range = workbook
-        .worksheet_range_at(0)
-        .ok_or_else(|| anyhow!("No sheet found in XLSX"))??;
-
-    let mut rows = range.rows();
-
-    // Header row -> column name -> index
-    let header = rows.next().ok_or_else(|| anyhow!("Empty sheet (no header row)"))?;
-    let mut cols: HashMap<String, usize> = HashMap::new();
-    for (i, cell) in header.iter().enumerate() {
-        let name = cell_to_string(cell).trim().to_string();
-        if !name.is_empty() {
-            cols.insert(name, i);
-        }
-    }
-
-    // Required input columns
-    for required in REQUIRED_COLUMNS {
-        if !cols.contains_key(required) {
-            return Err(anyhow!("Missing required XLSX column: {}", required));
-        }
-    }
-
-    let mut writer = WriterBuilder::new()
-        .delimiter(b';')
-        .from_path(output_csv)?;
-
-    // NEW output CSV header (33 columns)
-    writer.write_record(&[
-        "Tel/Ref.",
-        "Civilité",
-        "Naam",
-        "Voornaam",
-        "Adres incl huisnummer",
-        "Bijkomend adres",
-        "Postcode",
-        "Gemeente",
-        "Geboortedatum",
-        "Email",
-        "FAX",
-        "FAX2",
-        "FAX3",
-        "Verdieping",
-        "Aantal inwoners",
-        "Telefoon 2",
-        "Telefoon 3",
-        "Telefoon 4",
-        "Telefoon 5",
-        "Telefoone 6",
-        "Telefoon 7",
-        "SMS",
-        "SMS 2",
-        "SMS 3",
-        "Pager",
-        "Zone libre 1",
-        "Zone libre 2",
-        "Zone libre 3",
-        "Taal",
-        "Land",
-        "Rode lijst",
-        "Type Contact",
-        "GPS coördinaten",
-    ])?;
-
-    // Fixed values
-    let fixed_postcode = "3570";
-    let fixed_gemeente = "Alken";
-    let fixed_taal = "NL";
-    let fixed_land = "BE";
-    let fixed_zwarte_lijst = "0";
-    let fixed_type_contact = "P";
-
-    for row in rows {
-        // Read XLSX fields
-        let xlsx_voornaam = get(&cols, row, "Voornaam");
-        let xlsx_naam = get(&cols, row, "Naam");
-
-        let straat = get(&cols, row, "Straat");
-        let huisnr_raw = get(&cols, row, "Huisnummer");
-        let huisnr_clean = extract_house_number(&huisnr_raw);
-
-        let email = get(&cols, row, "E-mailadres");
-
-        let mobiel_raw = get(&cols, row, "Mobiel nummer");
-        let tel_ref = normalize_be_phone(&mobiel_raw);
-
-        let adres_incl = format!("{} {}", straat, huisnr_clean).trim().to_string();
-
-        // IMPORTANT: swap output fields (provider error)
-        // CSV "Voornaam" <- XLSX "Naam"
-        // CSV "Naam"     <- XLSX "Voornaam"
-        let csv_voornaam = xlsx_voornaam;
-        let csv_naam = xlsx_naam;
-
-        writer.write_record([
-            tel_ref,                       // Tel/Ref.
-            String::new(),                 // Civilité
-            csv_naam,                  // Naam 
-            csv_voornaam,                   // VoorNaam  
-            adres_incl,                    // Adres incl huisnummer
-            String::new(),                 // Bijkomend adres
-            fixed_postcode.to_string(),    // Postcode
-            fixed_gemeente.to_string(),    // Gemeente
-            String::new(),                 // Geboortedatum
-            email,                         // Email
-            String::new(),                 // FAX
-            String::new(),                 // FAX2
-            String::new(),                 // FAX3
-            String::new(),                 // Verdieping
-            String::new(),                 // Aantal inwoners
-            String::new(),                 // Telefoon 2
-            String::new(),                 // Telefoon 3
-            String::new(),                 // Telefoon 4
-            String::new(),                 // Telefoon 5
-            String::new(),                 // Telefoon 6
-            String::new(),                 // Telefoon 7
-            String::new(),                 // SMS
-            String::new(),                 // SMS 2
-            String::new(),                 // SMS 3
-            String::new(),                 // Pager
-            String::new(),                 // Zone libre 1
-            String::new(),                 // Zone libre 2
-            String::new(),                 // Zone libre 3
-            fixed_taal.to_string(),        // Taal
-            fixed_land.to_string(),        // Land
-            fixed_zwarte_lijst.to_string(),// Zwarte lijst
-            fixed_type_contact.to_string(), // Type Contact
-            String::new(),                 // GPS coördinaten
-        ])?;
-    }
-
-    writer.flush()?;
-    Ok(())
+impl LastExport {
+    fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn clear(&mut self) {
+        *self = LastExport::default();
+    }
+}
+
+/// Delete the files of the last export. Files already gone are not an error.
+fn delete_export(files: &[PathBuf]) -> std::result::Result<usize, String> {
+    let mut deleted = 0;
+    let mut problems = Vec::new();
+    for file in files {
+        match std::fs::remove_file(file) {
+            Ok(()) => deleted += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => problems.push(ConvertError::io_at(file, e).to_string()),
+        }
+    }
+    if problems.is_empty() {
+        Ok(deleted)
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Run the scheduled export (settings.toml [schedule]) on a background thread;
+/// every run is shown in the status line.
+fn start_schedule(ui: &MainWindow, config: Schedule, profiles: &[Profile]) {
+    let status = ui.get_status();
+    let cron = match Cron::parse(&config.cron) {
+        Ok(cron) => cron,
+        Err(e) => {
+            ui.set_status(format!("{} Scheduled export off: {}", status, e).into());
+            return;
+        }
+    };
+    let profile = match profile::find_profile(profiles, config.profile.as_deref()) {
+        Ok(p) => p,
+        Err(e) => {
+            ui.set_status(format!("{} Scheduled export off: {}.", status, e).into());
+            return;
+        }
+    };
+    if let Some(next) = schedule::next_run_text(&cron) {
+        ui.set_status(format!("{} Scheduled export: next run {}.", status, next).into());
+    }
+
+    let ui_handle = ui.as_weak();
+    thread::spawn(move || {
+        schedule::run_forever(&config, &cron, &profile, |outcome| {
+            let text = format!("Scheduled export: {}.", outcome.text());
+            let ui_handle = ui_handle.clone();
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_handle.upgrade() {
+                    ui.set_status(text.into());
+                }
+            });
+        });
+    });
 }
 
-fn main() -> Result<()> {
+fn main() -> std::result::Result<(), slint::PlatformError> {
     let ui = MainWindow::new()?;
+    ui.set_operator(RunInfo::from_os().operator.into());
 
     {
         let window = ui.window();
@@ -302,38 +1163,192 @@ fn main() -> Result<()> {
         });
     }
 
+    let (profiles, profile_problems) = profile::load_profiles();
+    let profiles = Rc::new(profiles);
+    let profile_model = Rc::new(VecModel::from(
+        profiles
+            .iter()
+            .enumerate()
+            .map(|(i, p)| ProfileItem {
+                name: p.name.clone().into(),
+                selected: i == 0,
+            })
+            .collect::<Vec<_>>(),
+    ));
+    ui.set_profiles(profile_model.clone().into());
+    let settings = Rc::new(RefCell::new(Settings::load()));
+    ui.set_quick_export(settings.borrow().quick_export);
+
+    let ready = if paths::is_portable() {
+        "Ready (portable mode)."
+    } else {
+        "Ready."
+    };
+    if profile_problems.is_empty() {
+        ui.set_status(ready.into());
+    } else {
+        ui.set_status(format!("{} Skipped profiles: {}", ready, profile_problems.join("; ")).into());
+    }
+    let schedule = settings.borrow().schedule.clone();
+    if let Some(config) = schedule {
+        start_schedule(&ui, config, &profiles);
+    }
+
+    let tasks = Rc::new(TaskManager {
+        state: Cell::new(TaskState::Idle),
+        ui: ui.as_weak(),
+    });
+
+    let duplicates_dialog = DuplicatesDialog::new()?;
+    let duplicate_model = Rc::new(VecModel::<DuplicateGroupItem>::default());
+    duplicates_dialog.set_groups(duplicate_model.clone().into());
+    let pending: Rc<RefCell<Option<PendingExport>>> = Rc::new(RefCell::new(None));
+    // Files of the last successful export, for "Undo export"
+    let last_export: Rc<RefCell<LastExport>> = Rc::new(RefCell::new(LastExport::default()));
+    // Column names confirmed for an imported list without header row
+    let input_columns: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    // Column chosen per header name the imported file has more than once
+    let column_choices: Rc<RefCell<BTreeMap<String, usize>>> = Rc::new(RefCell::new(BTreeMap::new()));
+    // Import checks of the current XLSX, for the confirmation before export
+    let import_check: Rc<RefCell<Option<ImportCheck>>> = Rc::new(RefCell::new(None));
+    let keeper = Rc::new(SessionKeeper {
+        ui: ui.as_weak(),
+        profiles: profiles.clone(),
+        profile_model: profile_model.clone(),
+        input_columns: input_columns.clone(),
+        column_choices: column_choices.clone(),
+        import_check: import_check.clone(),
+        pending: pending.clone(),
+        duplicate_model: duplicate_model.clone(),
+    });
+    // Duplicate review of a restored session, reopened by the next export
+    let restored_review: Rc<RefCell<Option<Review>>> = Rc::new(RefCell::new(None));
+    // Polls the conversion thread of an export (see prepare_in_background)
+    let prepare_timer = Rc::new(Timer::default());
+
     ui.on_import_clicked({
         let ui_handle = ui.as_weak();
+        let profile_model = profile_model.clone();
+        let profiles = profiles.clone();
+        let input_columns = input_columns.clone();
+        let column_choices = column_choices.clone();
+        let import_check = import_check.clone();
+        let keeper = keeper.clone();
+        let settings = settings.clone();
         move || {
             if let Some(ui) = ui_handle.upgrade() {
                 if let Some(file) = FileDialog::new()
-                    .add_filter("Excel", &["xlsx"])
+                    .add_filter("Excel", &EXCEL_EXTENSIONS)
                     .pick_file()
                 {
                     let path_str = file.display().to_string();
                     ui.set_input_file(path_str.clone().into());
+                    ui.set_pasted_rows(0);
                     ui.set_output_file("".into());
-                    ui.set_export_checked(false);
-                    ui.set_export_ok(false);
+                    clear_export_step(&ui);
 
-                    ui.set_import_checked(true);
-                    match validate_xlsx_columns(&path_str) {
-                        Ok(_) => {
-                            ui.set_import_ok(true);
-                            ui.set_status("XLSX selected and columns OK.".into());
+                    input_columns.borrow_mut().clear();
+                    column_choices.borrow_mut().clear();
+                    let mut profile = selected_profiles(&profile_model, &profiles)
+                        .into_iter()
+                        .next()
+                        .unwrap_or_default();
+                    let mut validated = retry_if_locked(|| validate_xlsx_columns(&path_str, &profile));
+                    // One question per header name found more than once
+                    while let Err(ConvertError::AmbiguousColumn { name, columns }) = &validated {
+                        let Some(n) = choose_duplicate_column(&path_str, &profile, name, columns) else {
+                            break;
+                        };
+                        column_choices.borrow_mut().insert(name.clone(), n);
+                        profile.duplicate_columns.insert(name.clone(), n);
+                        validated = retry_if_locked(|| validate_xlsx_columns(&path_str, &profile));
+                    }
+                    *import_check.borrow_mut() = Some(validated.as_ref().map(Clone::clone).map_err(ToString::to_string));
+                    match validated {
+                        Ok((rows, empty)) if !empty.is_empty() => {
+                            let columns: Vec<String> = empty.iter().map(EmptyColumn::text).collect();
+                            show_import_step(&ui, StepState::Partial, format!("{} rows", rows));
+                            ui.set_status(
+                                format!("Columns OK, but {}. Is this the right export?", columns.join("; ")).into(),
+                            );
+                        }
+                        Ok((rows, _)) => {
+                            show_import_step(&ui, StepState::Ok, format!("{} rows", rows));
+                            ui.set_status(if is_macro_workbook(&path_str) {
+                                "XLSM selected and columns OK (data only: macros are not run).".into()
+                            } else {
+                                "XLSX selected and columns OK.".into()
+                            });
+                        }
+                        Err(e @ ConvertError::MissingColumn { .. }) if profile.input_columns.is_empty() => {
+                            match confirm_headerless(&path_str, &settings) {
+                                Some((columns, remembered)) => {
+                                    apply_input_columns(&mut profile, &columns);
+                                    *import_check.borrow_mut() = Some(
+                                        validate_xlsx_columns(&path_str, &profile).map_err(|e| e.to_string()),
+                                    );
+                                    show_import_step(&ui, StepState::Ok, "no header row".to_string());
+                                    ui.set_status(
+                                        format!(
+                                            "List without header row, columns{}: {}.",
+                                            if remembered { " (as confirmed before)" } else { "" },
+                                            headerless::mapping_text(&columns)
+                                        )
+                                        .into(),
+                                    );
+                                    *input_columns.borrow_mut() = columns;
+                                }
+                                None => {
+                                    show_import_step(&ui, StepState::Failed, String::new());
+                                    ui.set_status(error_status(&e).into());
+                                }
+                            }
                         }
                         Err(e) => {
-                            ui.set_import_ok(false);
-                            ui.set_status(format!("XLSX error: {}", e).into());
+                            show_import_step(&ui, StepState::Failed, String::new());
+                            ui.set_status(error_status(&e).into());
                         }
                     }
+                    keeper.save();
                 }
             }
         }
     });
 
+    ui.on_profile_toggled({
+        let profile_model = profile_model.clone();
+        let keeper = keeper.clone();
+        move |index, checked| {
+            let index = index as usize;
+            if let Some(mut item) = profile_model.row_data(index) {
+                item.selected = checked;
+                profile_model.set_row_data(index, item);
+            }
+            keeper.save();
+        }
+    });
+
+    ui.on_session_changed({
+        let keeper = keeper.clone();
+        move || keeper.save()
+    });
+
     ui.on_export_clicked({
         let ui_handle = ui.as_weak();
+        let import_check = import_check.clone();
+        let dialog_handle = duplicates_dialog.as_weak();
+        let duplicate_model = duplicate_model.clone();
+        let pending = pending.clone();
+        let last_export = last_export.clone();
+        let settings = settings.clone();
+        let profile_model = profile_model.clone();
+        let profiles = profiles.clone();
+        let tasks = tasks.clone();
+        let input_columns = input_columns.clone();
+        let column_choices = column_choices.clone();
+        let keeper = keeper.clone();
+        let restored_review = restored_review.clone();
+        let prepare_timer = prepare_timer.clone();
         move || {
             if let Some(ui) = ui_handle.upgrade() {
                 let input = ui.get_input_file().to_string();
@@ -341,6 +1356,123 @@ fn main() -> Result<()> {
                     ui.set_status("No XLSX selected.".into());
                     return;
                 }
+                let Some(task) = tasks.begin() else {
+                    return;
+                };
+
+                let mut selected = selected_profiles(&profile_model, &profiles);
+                if selected.is_empty() {
+                    ui.set_status("Select at least one profile.".into());
+                    return;
+                }
+                let split = selected.len() > 1;
+                for profile in &mut selected {
+                    apply_overrides(&ui, profile, split);
+                    apply_input_columns(profile, &input_columns.borrow());
+                    apply_column_choices(profile, &column_choices.borrow());
+                }
+
+                let merge_file = ui.get_merge_file().to_string();
+                // A restored review was confirmed before the restart
+                let check = import_check.borrow().clone();
+                if restored_review.borrow().is_none()
+                    && !confirm_export(&input, check.as_ref(), &selected, &merge_file, &run_info(&ui))
+                {
+                    ui.set_status("Export cancelled.".into());
+                    return;
+                }
+                if !merge_file.is_empty() {
+                    if selected.len() > 1 {
+                        ui.set_status("Merging uses one profile for the first file; select exactly one.".into());
+                        return;
+                    }
+                    let mut merge_profile = profiles
+                        .get(ui.get_merge_profile_index() as usize)
+                        .cloned()
+                        .unwrap_or_default();
+                    apply_overrides(&ui, &mut merge_profile, false);
+                    if let Some(out) = FileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .set_file_name(format!("{}_merged.csv", file_stem(&input)))
+                        .save_file()
+                    {
+                        task.converting();
+                        let second_wins = ui.get_merge_second_wins();
+                        let result = retry_if_locked(|| {
+                            convert_merge_to_csv(
+                                (&input, &selected[0]),
+                                (&merge_file, &merge_profile),
+                                out.to_str().unwrap(),
+                                second_wins,
+                                &run_info(&ui),
+                            )
+                        });
+                        *last_export.borrow_mut() = show_export_result(&ui, &out, result);
+                        task.finish(!last_export.borrow().is_empty());
+                    }
+                    return;
+                }
+
+                if selected.len() > 1 {
+                    let dir = if ui.get_quick_export() {
+                        Some(settings.borrow().quick_export_dir(Path::new(&input)))
+                    } else {
+                        FileDialog::new().pick_folder()
+                    };
+                    if let Some(dir) = dir {
+                        task.converting();
+                        match retry_if_locked(|| convert_xlsx_per_profile(&input, &dir, &selected, &run_info(&ui))) {
+                            Ok((exports, summary)) => {
+                                let counts: Vec<String> = exports
+                                    .iter()
+                                    .map(|e| format!("{}: {} rows", e.profile, e.rows))
+                                    .collect();
+                                ui.set_output_file(
+                                    exports
+                                        .iter()
+                                        .map(|e| e.path.display().to_string())
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                        .into(),
+                                );
+                                ui.set_status(
+                                    format!(
+                                        "{} CSV files saved ({}); {} rows matched no profile.{}{}",
+                                        exports.len(),
+                                        counts.join(", "),
+                                        summary.unmatched,
+                                        summary.warning_text(),
+                                        report_text(&summary)
+                                    )
+                                    .into(),
+                                );
+                                show_export_step(&ui, Some(&summary));
+                                let mut written: Vec<PathBuf> = exports
+                                    .into_iter()
+                                    .map(|e| e.path)
+                                    .chain(summary.report)
+                                    .chain(summary.review_file)
+                                    .chain(summary.rejected_file)
+                                    .collect();
+                                if let Some(zip) = offer_package(&ui, &dir, &file_stem(&input), &written) {
+                                    written.push(zip);
+                                }
+                                *last_export.borrow_mut() = LastExport {
+                                    files: written,
+                                    history_run: summary.history_run,
+                                };
+                                task.finish(true);
+                            }
+                            Err(e) => {
+                                ui.set_status(error_status(&e).into());
+                                show_export_step(&ui, None);
+                                last_export.borrow_mut().clear();
+                                task.finish(false);
+                            }
+                        }
+                    }
+                    return;
+                }
 
                 let suggested_name = Path::new(&input)
                     .file_stem()
@@ -348,24 +1480,667 @@ fn main() -> Result<()> {
                     .map(|stem| format!("{}.csv", stem))
                     .unwrap_or_else(|| "output.csv".to_string());
 
+                let restored = restored_review.borrow_mut().take();
+                let out = if let Some(review) = &restored {
+                    Some(review.out.clone())
+                } else if ui.get_quick_export() {
+                    Some(
+                        settings
+                            .borrow()
+                            .quick_export_dir(Path::new(&input))
+                            .join(format!("{}{}.csv", file_stem(&input), QUICK_EXPORT_SUFFIX)),
+                    )
+                } else {
+                    FileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .set_file_name(suggested_name)
+                        .save_file()
+                };
+                if let Some(out) = out {
+                    // Before converting, so no duplicate review is done for nothing
+                    if let Err(e) = check_not_input(&out, &[PathBuf::from(&input)]) {
+                        *last_export.borrow_mut() = show_export_result(&ui, &out, Err(e));
+                        task.finish(false);
+                        return;
+                    }
+                    // Asked here: the conversion thread cannot show the Retry dialog
+                    let readable = retry_if_locked(|| {
+                        fs::File::open(&input)
+                            .map(drop)
+                            .map_err(|e| ConvertError::io_at(&input, e))
+                    });
+                    if let Err(e) = readable {
+                        *last_export.borrow_mut() = show_export_result(&ui, &out, Err(e));
+                        task.finish(false);
+                        return;
+                    }
+                    task.converting();
+                    let run = run_info(&ui);
+                    let duplicate_model = duplicate_model.clone();
+                    let dialog_handle = dialog_handle.clone();
+                    let pending = pending.clone();
+                    let last_export = last_export.clone();
+                    let keeper = keeper.clone();
+                    let profile = selected.swap_remove(0);
+                    prepare_in_background(&ui, &prepare_timer, input, profile, move |ui, result| {
+                        let mut prepared = match result {
+                            Ok(prepared) => prepared,
+                            Err(e) => {
+                                *last_export.borrow_mut() = show_export_result(ui, &out, Err(e));
+                                task.finish(false);
+                                return;
+                            }
+                        };
+                        prepared.summary.run = run;
+                        let groups = dedup::find_groups(&prepared.records);
+                        if groups.is_empty() {
+                            *last_export.borrow_mut() = write_checked(ui, prepared, &out);
+                            task.finish(!last_export.borrow().is_empty());
+                            return;
+                        }
+
+                        duplicate_model.set_vec(duplicate_items(&prepared.records, &groups));
+                        if let Some(review) = &restored {
+                            restore_choices(&duplicate_model, &groups, review);
+                        }
+                        let text = format!(
+                            "{} Tel/Ref. numbers occur on more than one row. Choose per number which rows to export.",
+                            groups.len()
+                        );
+                        if let Some(dialog) = dialog_handle.upgrade() {
+                            dialog.set_summary(text.clone().into());
+                            ui.set_status(format!("{} Review the duplicates to save the CSV.", text).into());
+                            if let Err(e) = dialog.show() {
+                                // No dialog: export without removing anything
+                                let actions = vec![DuplicateAction::KeepAll; groups.len()];
+                                resolve_duplicates(&mut prepared, &groups, &actions);
+                                prepared.summary.warnings.push(format!("duplicate review unavailable: {}", e));
+                                *last_export.borrow_mut() = write_checked(ui, prepared, &out);
+                                task.finish(!last_export.borrow().is_empty());
+                                return;
+                            }
+                        }
+                        *pending.borrow_mut() = Some(PendingExport {
+                            prepared,
+                            groups,
+                            out,
+                        });
+                        keeper.save();
+                        task.hand_over();
+                    });
+                }
+            }
+        }
+    });
+
+    ui.on_paste_clicked({
+        let ui_handle = ui.as_weak();
+        let last_export = last_export.clone();
+        let profile_model = profile_model.clone();
+        let profiles = profiles.clone();
+        let tasks = tasks.clone();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                let mut selected = selected_profiles(&profile_model, &profiles);
+                if selected.len() != 1 {
+                    ui.set_status("Select exactly one profile to export pasted rows.".into());
+                    return;
+                }
+                let Some(task) = tasks.begin() else {
+                    return;
+                };
+                apply_overrides(&ui, &mut selected[0], false);
+
+                let pasted = match clipboard::read_rows() {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        ui.set_status(error_status(&e).into());
+                        return;
+                    }
+                };
+
+                // Not a file: Save CSV, Preview and Merge have nothing to open
+                ui.set_input_file("".into());
+                ui.set_pasted_rows((pasted.len() - 1) as i32);
+                ui.set_output_file("".into());
+                let header = pasted.first().map(|r| r.as_slice());
+                if let Err(e) = header_columns(clipboard::SOURCE_NAME, header, &selected[0]) {
+                    show_import_step(&ui, StepState::Failed, String::new());
+                    clear_export_step(&ui);
+                    ui.set_status(error_status(&e).into());
+                    task.finish(false);
+                    return;
+                }
+                show_import_step(&ui, StepState::Ok, format!("{} rows", pasted.len() - 1));
+
                 if let Some(out) = FileDialog::new()
                     .add_filter("CSV", &["csv"])
-                    .set_file_name(suggested_name)
+                    .set_file_name("pasted.csv")
                     .save_file()
                 {
-                    match convert_xlsx_to_csv(&input, out.to_str().unwrap()) {
+                    task.converting();
+                    let result = retry_if_locked(|| {
+                        let rows = pasted.iter().map(|r| r.as_slice());
+                        let mut prepared = prepare_export(clipboard::SOURCE_NAME, rows, &selected[0])?;
+                        prepared.summary.run = run_info(&ui);
+                        write_export(prepared, out.to_str().unwrap())
+                    });
+                    *last_export.borrow_mut() = show_export_result(&ui, &out, result);
+                    task.finish(!last_export.borrow().is_empty());
+                }
+            }
+        }
+    });
+
+    ui.on_verify_clicked({
+        let ui_handle = ui.as_weak();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                if let Some(file) = FileDialog::new().add_filter("CSV", &["csv"]).pick_file() {
+                    verify_csv_file(&ui, &file);
+                }
+            }
+        }
+    });
+
+    ui.on_peek_clicked({
+        let ui_handle = ui.as_weak();
+        let profile_model = profile_model.clone();
+        let profiles = profiles.clone();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                if let Some(file) = FileDialog::new().add_filter("Excel", &EXCEL_EXTENSIONS).pick_file() {
+                    let profile = selected_profiles(&profile_model, &profiles)
+                        .into_iter()
+                        .next()
+                        .unwrap_or_default();
+                    peek_xlsx_file(&ui, &file, &profile);
+                }
+            }
+        }
+    });
+
+    ui.on_to_xlsx_clicked({
+        let ui_handle = ui.as_weak();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                let Some(csv) = FileDialog::new().add_filter("CSV", &["csv"]).pick_file() else {
+                    return;
+                };
+                let Some(out) = FileDialog::new()
+                    .add_filter("Excel", &["xlsx"])
+                    .set_file_name(format!("{}.xlsx", file_stem(&csv.display().to_string())))
+                    .save_file()
+                else {
+                    return;
+                };
+                match reverse::csv_to_xlsx(&csv, &out) {
+                    Ok(rows) => ui.set_status(
+                        format!("{} contacts written to {} (input layout).", rows, out.display()).into(),
+                    ),
+                    Err(e) => ui.set_status(error_status(&e).into()),
+                }
+            }
+        }
+    });
+
+    ui.on_template_clicked({
+        let ui_handle = ui.as_weak();
+        let profile_model = profile_model.clone();
+        let profiles = profiles.clone();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                let Some(csv) = FileDialog::new().add_filter("CSV", &["csv"]).pick_file() else {
+                    return;
+                };
+                let inferred = match template::infer_file(&csv) {
+                    Ok(inferred) => inferred,
+                    Err(e) => {
+                        ui.set_status(error_status(&e).into());
+                        return;
+                    }
+                };
+                // The fixed values of the selected profile, the layout of the example
+                let base = selected_profiles(&profile_model, &profiles)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                let profile = Profile {
+                    name: format!("{} ({})", base.name, file_stem(&csv.display().to_string())),
+                    template: Some(inferred.template.clone()),
+                    ..base
+                };
+                let separator = match inferred.template.delimiter {
+                    '\t' => "tab".to_string(),
+                    c => format!("'{}'", c),
+                };
+                match profile::save_new(&profile) {
+                    Ok(path) => ui.set_status(
+                        format!(
+                            "Profile \"{}\" saved ({}): {} columns, {} headers, separated by {}. Restart to use it.",
+                            profile.name,
+                            path.display(),
+                            inferred.template.columns.len(),
+                            inferred.language,
+                            separator
+                        )
+                        .into(),
+                    ),
+                    Err(e) => ui.set_status(format!("Template not saved: {}", e).into()),
+                }
+            }
+        }
+    });
+
+    ui.set_profile_names(
+        Rc::new(VecModel::from(
+            profiles
+                .iter()
+                .map(|p| SharedString::from(p.name.as_str()))
+                .collect::<Vec<_>>(),
+        ))
+        .into(),
+    );
+
+    ui.on_merge_clicked({
+        let ui_handle = ui.as_weak();
+        let profiles = profiles.clone();
+        let keeper = keeper.clone();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                if let Some(file) = FileDialog::new()
+                    .add_filter("Excel", &EXCEL_EXTENSIONS)
+                    .pick_file()
+                {
+                    let path_str = file.display().to_string();
+                    let profile = profiles
+                        .get(ui.get_merge_profile_index() as usize)
+                        .cloned()
+                        .unwrap_or_default();
+                    match retry_if_locked(|| validate_xlsx_columns(&path_str, &profile)) {
                         Ok(_) => {
-                            ui.set_output_file(out.display().to_string().into());
-                            ui.set_status("CSV saved.".into());
-                            ui.set_export_checked(true);
-                            ui.set_export_ok(true);
+                            ui.set_merge_file(path_str.into());
+                            ui.set_status("Second XLSX selected; Save CSV merges both files.".into());
                         }
                         Err(e) => {
-                            ui.set_status(format!("Error: {}", e).into());
-                            ui.set_export_checked(true);
-                            ui.set_export_ok(false);
+                            ui.set_merge_file("".into());
+                            ui.set_status(format!("Second file: {}", error_status(&e)).into());
                         }
                     }
+                    keeper.save();
+                }
+            }
+        }
+    });
+
+    duplicates_dialog.on_choice_changed({
+        let duplicate_model = duplicate_model.clone();
+        let keeper = keeper.clone();
+        move |index, choice| {
+            let index = index as usize;
+            if let Some(mut item) = duplicate_model.row_data(index) {
+                item.choice = choice;
+                duplicate_model.set_row_data(index, item);
+            }
+            keeper.save();
+        }
+    });
+
+    duplicates_dialog.on_apply_clicked({
+        let ui_handle = ui.as_weak();
+        let dialog_handle = duplicates_dialog.as_weak();
+        let duplicate_model = duplicate_model.clone();
+        let pending = pending.clone();
+        let last_export = last_export.clone();
+        let tasks = tasks.clone();
+        move || {
+            if let Some(dialog) = dialog_handle.upgrade() {
+                let _ = dialog.hide();
+            }
+            let Some(PendingExport {
+                mut prepared,
+                groups,
+                out,
+            }) = pending.borrow_mut().take()
+            else {
+                return;
+            };
+            let actions: Vec<DuplicateAction> = duplicate_model
+                .iter()
+                .zip(&groups)
+                .map(|(item, group)| duplicate_action(item.choice.max(0) as usize, group.members.len()))
+                .collect();
+            resolve_duplicates(&mut prepared, &groups, &actions);
+            if let Some(ui) = ui_handle.upgrade() {
+                *last_export.borrow_mut() = write_checked(&ui, prepared, &out);
+            }
+            tasks.set(if last_export.borrow().is_empty() {
+                TaskState::Failed
+            } else {
+                TaskState::Done
+            });
+        }
+    });
+
+    duplicates_dialog.on_cancel_clicked({
+        let ui_handle = ui.as_weak();
+        let dialog_handle = duplicates_dialog.as_weak();
+        let pending = pending.clone();
+        let tasks = tasks.clone();
+        let keeper = keeper.clone();
+        move || {
+            if let Some(dialog) = dialog_handle.upgrade() {
+                let _ = dialog.hide();
+            }
+            if pending.borrow_mut().take().is_some() {
+                keeper.save();
+                tasks.set(TaskState::Idle);
+                if let Some(ui) = ui_handle.upgrade() {
+                    ui.set_status("Export cancelled; nothing written.".into());
+                }
+            }
+        }
+    });
+
+    duplicates_dialog.window().on_close_requested({
+        let dialog_handle = duplicates_dialog.as_weak();
+        move || {
+            if let Some(dialog) = dialog_handle.upgrade() {
+                dialog.invoke_cancel_clicked();
+            }
+            CloseRequestResponse::HideWindow
+        }
+    });
+
+    ui.on_profile_data_clicked({
+        let ui_handle = ui.as_weak();
+        let profile_model = profile_model.clone();
+        let profiles = profiles.clone();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                let input = ui.get_input_file().to_string();
+                if !Path::new(&input).is_file() {
+                    ui.set_status("Profile data needs an imported XLSX file.".into());
+                    return;
+                }
+                let range = match retry_if_locked(|| open_first_sheet(&input)) {
+                    Ok(range) => range,
+                    Err(e) => {
+                        ui.set_status(error_status(&e).into());
+                        return;
+                    }
+                };
+                let profile = selected_profiles(&profile_model, &profiles)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                let (stats, rows) = column_stats::column_stats(skip_boilerplate(range.rows(), &profile));
+
+                if let Some(out) = FileDialog::new()
+                    .add_filter("HTML", &["html"])
+                    .set_file_name(format!("{}_columns.html", file_stem(&input)))
+                    .save_file()
+                {
+                    match column_stats::write_html(&out, &input, &stats, rows) {
+                        Ok(()) => {
+                            let sparse = stats.iter().filter(|c| c.non_empty * 2 < rows).count();
+                            ui.set_status(
+                                format!(
+                                    "Column profile saved ({} columns, {} less than half filled): {}",
+                                    stats.len(),
+                                    sparse,
+                                    out.display()
+                                )
+                                .into(),
+                            );
+                        }
+                        Err(e) => ui.set_status(error_status(&ConvertError::io_at(&out, e)).into()),
+                    }
+                }
+            }
+        }
+    });
+
+    let preview_dialog = PreviewWindow::new()?;
+    let preview_state: Rc<RefCell<PreviewState>> = Rc::new(RefCell::new(PreviewState::default()));
+    let preview_model = Rc::new(PreviewRows::new(preview_state.clone()));
+    let preview_column_model = Rc::new(VecModel::<PreviewColumn>::default());
+    preview_dialog.set_rows(preview_model.clone().into());
+    preview_dialog.set_columns(preview_column_model.clone().into());
+
+    ui.on_preview_clicked({
+        let ui_handle = ui.as_weak();
+        let dialog_handle = preview_dialog.as_weak();
+        let preview_model = preview_model.clone();
+        let preview_column_model = preview_column_model.clone();
+        let preview_state = preview_state.clone();
+        let settings = settings.clone();
+        let profile_model = profile_model.clone();
+        let profiles = profiles.clone();
+        let tasks = tasks.clone();
+        let input_columns = input_columns.clone();
+        let column_choices = column_choices.clone();
+        move || {
+            let (Some(ui), Some(dialog)) = (ui_handle.upgrade(), dialog_handle.upgrade()) else {
+                return;
+            };
+            let input = ui.get_input_file().to_string();
+            if !Path::new(&input).is_file() {
+                ui.set_status("Preview needs an imported XLSX file.".into());
+                return;
+            }
+            let Some(task) = tasks.begin() else {
+                return;
+            };
+            let mut profile = selected_profiles(&profile_model, &profiles)
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            apply_overrides(&ui, &mut profile, false);
+            apply_input_columns(&mut profile, &input_columns.borrow());
+            apply_column_choices(&mut profile, &column_choices.borrow());
+            let prepared = match retry_if_locked(|| prepare_xlsx_export(&input, &profile)) {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    ui.set_status(error_status(&e).into());
+                    task.finish(false);
+                    return;
+                }
+            };
+            // Nothing is written: the preview ends the task
+            drop(task);
+
+            let changes = History::open().and_then(|h| h.contact_changes(&prepared.records));
+            let summary = match &changes {
+                Ok(changes) => {
+                    let new = changes.iter().filter(|c| c == &&Some(ContactChange::New)).count();
+                    let changed = changes.iter().flatten().count() - new;
+                    format!(
+                        "{} rows with profile {}: {} new numbers (green), {} with a changed address or e-mail (yellow) since the last upload.",
+                        prepared.records.len(),
+                        profile.name,
+                        new,
+                        changed
+                    )
+                }
+                Err(e) => format!(
+                    "{} rows with profile {}. History not available, changes are not shown: {}",
+                    prepared.records.len(),
+                    profile.name,
+                    e
+                ),
+            };
+            let changes = changes.unwrap_or_else(|_| vec![None; prepared.records.len()]);
+            let (order, columns) = preview_columns(&settings.borrow().preview_columns, &prepared.records);
+            *preview_state.borrow_mut() = PreviewState {
+                records: prepared.records,
+                changes,
+                order,
+            };
+            preview_column_model.set_vec(columns);
+            preview_model.refresh(false);
+            dialog.set_changed_only(false);
+            dialog.set_summary(summary.into());
+            let _ = dialog.show();
+        }
+    });
+
+    preview_dialog.on_changed_only_toggled({
+        let preview_model = preview_model.clone();
+        move |changed_only| preview_model.refresh(changed_only)
+    });
+
+    preview_dialog.on_column_toggled({
+        let preview_column_model = preview_column_model.clone();
+        move |index, visible| {
+            let index = index as usize;
+            if let Some(mut column) = preview_column_model.row_data(index) {
+                column.visible = visible;
+                preview_column_model.set_row_data(index, column);
+            }
+        }
+    });
+
+    preview_dialog.on_column_resized({
+        let preview_column_model = preview_column_model.clone();
+        move |index, delta| {
+            let index = index as usize;
+            if let Some(mut column) = preview_column_model.row_data(index) {
+                column.width = (column.width + delta).max(40.0);
+                preview_column_model.set_row_data(index, column);
+            }
+        }
+    });
+
+    preview_dialog.on_column_moved({
+        let dialog_handle = preview_dialog.as_weak();
+        let preview_model = preview_model.clone();
+        let preview_column_model = preview_column_model.clone();
+        let preview_state = preview_state.clone();
+        move |index, step| {
+            let from = index as usize;
+            let Some(to) = from.checked_add_signed(step as isize) else {
+                return;
+            };
+            let (Some(a), Some(b)) = (preview_column_model.row_data(from), preview_column_model.row_data(to)) else {
+                return;
+            };
+            preview_column_model.set_row_data(from, b);
+            preview_column_model.set_row_data(to, a);
+            preview_state.borrow_mut().order.swap(from, to);
+            let changed_only = dialog_handle.upgrade().is_some_and(|d| d.get_changed_only());
+            preview_model.refresh(changed_only);
+        }
+    });
+
+    preview_dialog.on_hide_empty_clicked({
+        let preview_column_model = preview_column_model.clone();
+        let preview_state = preview_state.clone();
+        move || {
+            let state = preview_state.borrow();
+            for (i, &field) in state.order.iter().enumerate() {
+                if let Some(mut column) = preview_column_model.row_data(i) {
+                    column.visible = state.records.iter().any(|(_, r)| !r[field].is_empty());
+                    preview_column_model.set_row_data(i, column);
+                }
+            }
+        }
+    });
+
+    preview_dialog.on_show_all_clicked({
+        let preview_column_model = preview_column_model.clone();
+        move || {
+            for i in 0..preview_column_model.row_count() {
+                if let Some(mut column) = preview_column_model.row_data(i) {
+                    column.visible = true;
+                    preview_column_model.set_row_data(i, column);
+                }
+            }
+        }
+    });
+
+    preview_dialog.on_close_clicked({
+        let ui_handle = ui.as_weak();
+        let dialog_handle = preview_dialog.as_weak();
+        let preview_column_model = preview_column_model.clone();
+        let settings = settings.clone();
+        move || {
+            if let Some(dialog) = dialog_handle.upgrade() {
+                let _ = dialog.hide();
+            }
+            if let Some(ui) = ui_handle.upgrade() {
+                save_preview_layout(&ui, &settings, &preview_column_model);
+            }
+        }
+    });
+
+    preview_dialog.window().on_close_requested({
+        let dialog_handle = preview_dialog.as_weak();
+        move || {
+            if let Some(dialog) = dialog_handle.upgrade() {
+                dialog.invoke_close_clicked();
+            }
+            CloseRequestResponse::HideWindow
+        }
+    });
+
+    ui.on_undo_clicked({
+        let ui_handle = ui.as_weak();
+        let last_export = last_export.clone();
+        move || {
+            if let Some(ui) = ui_handle.upgrade() {
+                let files = last_export.borrow().files.clone();
+                if files.is_empty() {
+                    ui.set_status("Nothing to undo.".into());
+                    return;
+                }
+                let list: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+                let confirmed = MessageDialog::new()
+                    .set_level(MessageLevel::Warning)
+                    .set_title("Undo export")
+                    .set_description(format!("Delete these files?\n\n{}", list.join("\n")))
+                    .set_buttons(MessageButtons::YesNo)
+                    .show();
+                if confirmed != MessageDialogResult::Yes {
+                    return;
+                }
+
+                match delete_export(&files) {
+                    Ok(deleted) => {
+                        let history_run = last_export.borrow().history_run;
+                        last_export.borrow_mut().clear();
+                        ui.set_output_file("".into());
+                        clear_export_step(&ui);
+                        let forgotten = match history_run {
+                            Some(run_id) => History::open().and_then(|mut h| h.forget(run_id)),
+                            None => Ok(()),
+                        };
+                        ui.set_status(match forgotten {
+                            Ok(()) => format!(
+                                "Export undone ({} files deleted). Select the right profile and save again.",
+                                deleted
+                            ),
+                            Err(e) => format!(
+                                "Export undone ({} files deleted), but it is still in the history: {}",
+                                deleted, e
+                            ),
+                        }
+                        .into());
+                    }
+                    Err(e) => ui.set_status(format!("Undo failed: {}", e).into()),
+                }
+            }
+        }
+    });
+
+    ui.on_quick_export_toggled({
+        let ui_handle = ui.as_weak();
+        let settings = settings.clone();
+        move |checked| {
+            settings.borrow_mut().quick_export = checked;
+            if let Err(e) = settings.borrow().save() {
+                if let Some(ui) = ui_handle.upgrade() {
+                    ui.set_status(format!("Setting not saved: {}", e).into());
                 }
             }
         }
@@ -373,19 +2148,46 @@ fn main() -> Result<()> {
 
     ui.on_reset_clicked({
         let ui_handle = ui.as_weak();
+        let last_export = last_export.clone();
+        let input_columns = input_columns.clone();
+        let column_choices = column_choices.clone();
+        let import_check = import_check.clone();
         move || {
+            last_export.borrow_mut().clear();
+            input_columns.borrow_mut().clear();
+            column_choices.borrow_mut().clear();
+            import_check.borrow_mut().take();
+            Session::clear();
             if let Some(ui) = ui_handle.upgrade() {
                 ui.set_input_file("".into());
+                ui.set_pasted_rows(0);
+                ui.set_merge_file("".into());
                 ui.set_output_file("".into());
                 ui.set_status("Ready.".into());
-                ui.set_import_checked(false);
-                ui.set_import_ok(false);
-                ui.set_export_checked(false);
-                ui.set_export_ok(false);
+                show_import_step(&ui, StepState::None, String::new());
+                clear_export_step(&ui);
+                ui.set_override_postcode("".into());
+                ui.set_override_gemeente("".into());
+                ui.set_override_taal("".into());
+                ui.set_override_land("".into());
+                ui.set_override_type_contact("".into());
             }
         }
     });
 
+    if let Some(saved) = Session::load() {
+        if Path::new(&saved.input_file).is_file() && confirm_restore(&saved) {
+            if let Some(review) = keeper.restore(saved) {
+                *restored_review.borrow_mut() = Some(review);
+                ui.invoke_export_clicked();
+            }
+        } else {
+            Session::clear();
+        }
+    }
+
     ui.run()?;
+    // Closed normally: the session is not to be restored
+    Session::clear();
     Ok(())
 }
@@ -0,0 +1,69 @@
+// src/package.rs
+// Handover ZIP
+// ------------
+//
+// A split export (one CSV per gemeente) or a batch leaves several CSVs,
+// reports and a batch report in a folder, and the colleague doing the
+// BE-Alert upload has to get all of them and none of the older files next to
+// them. Packaging puts the files of one export into
+// `<name>_<YYYYMMDD-HHMMSS>.zip` with a manifest.json listing each file with
+// its size and SHA-256, so the receiver can see the set is complete and
+// unchanged. The files themselves are left where they are.
+
+use crate::error::{ConvertError, Result};
+use crate::report::{RunInfo, TOOL_VERSION};
+use chrono::{DateTime, Local};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Name of the manifest inside the ZIP.
+pub const MANIFEST: &str = "manifest.json";
+
+/// `<dir>/<name>_<YYYYMMDD-HHMMSS>.zip`.
+pub fn package_path(dir: &Path, name: &str, now: DateTime<Local>) -> PathBuf {
+    dir.join(format!("{}_{}.zip", name, now.format("%Y%m%d-%H%M%S")))
+}
+
+/// Write `files` and a manifest to the ZIP at `zip_path`. Entries are named
+/// by their path below `base` (the file name for files outside it).
+pub fn write_package(zip_path: &Path, base: &Path, files: &[PathBuf], run: &RunInfo) -> Result<()> {
+    let at = |e: io::Error| ConvertError::io_at(zip_path, e);
+    let zip_error = |e: zip::result::ZipError| at(io::Error::other(e));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(File::create(zip_path).map_err(at)?);
+
+    let mut entries = Vec::new();
+    for path in files {
+        let bytes = fs::read(path).map_err(|e| ConvertError::io_at(path, e))?;
+        let name = path
+            .strip_prefix(base)
+            .unwrap_or_else(|_| Path::new(path.file_name().unwrap_or_default()))
+            .to_string_lossy()
+            .replace('\\', "/");
+        zip.start_file(name.as_str(), options).map_err(zip_error)?;
+        zip.write_all(&bytes).map_err(at)?;
+        let sha256: String = Sha256::digest(&bytes)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        entries.push(json!({ "name": name, "bytes": bytes.len(), "sha256": sha256 }));
+    }
+
+    let manifest = json!({
+        "version": TOOL_VERSION,
+        "created": Local::now().to_rfc3339(),
+        "operator": run.operator,
+        "remark": run.remark,
+        "files": entries,
+    });
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|e| at(e.into()))?;
+    zip.start_file(MANIFEST, options).map_err(zip_error)?;
+    zip.write_all(&manifest).map_err(at)?;
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// src/paths.rs
+// Where the convertor keeps its own files
+// ---------------------------------------
+//
+// Normal install: <config dir>/BIN-Convertor (%APPDATA% on Windows).
+// Portable mode:  the folder of the executable, when it contains a
+//                 `portable.flag` file (e.g. on the crisis-center network
+//                 drive, so every workstation shares the same settings).
+//
+// Profiles live in <app dir>/profiles, the conversion history in
+// <app dir>/history.sqlite, GUI settings in <app dir>/settings.toml, the
+// scheduled export log in <app dir>/schedule.log and an unfinished GUI
+// session in <app dir>/session.toml.
+
+use std::env;
+use std::path::PathBuf;
+
+pub const APP_DIR_NAME: &str = "BIN-Convertor";
+pub const PORTABLE_FLAG: &str = "portable.flag";
+
+/// Folder of the running executable, if it holds the portable flag.
+pub fn portable_dir() -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    dir.join(PORTABLE_FLAG).is_file().then(|| dir.to_path_buf())
+}
+
+pub fn is_portable() -> bool {
+    portable_dir().is_some()
+}
+
+pub fn app_dir() -> Option<PathBuf> {
+    portable_dir().or_else(|| dirs::config_dir().map(|d| d.join(APP_DIR_NAME)))
+}
+
+pub fn profiles_dir() -> Option<PathBuf> {
+    app_dir().map(|d| d.join("profiles"))
+}
+
+pub fn history_path() -> Option<PathBuf> {
+    app_dir().map(|d| d.join("history.sqlite"))
+}
+
+pub fn settings_path() -> Option<PathBuf> {
+    app_dir().map(|d| d.join("settings.toml"))
+}
+
+pub fn schedule_log_path() -> Option<PathBuf> {
+    app_dir().map(|d| d.join("schedule.log"))
+}
+
+pub fn session_path() -> Option<PathBuf> {
+    app_dir().map(|d| d.join("session.toml"))
+}
@@ -0,0 +1,152 @@
+// src/peek.rs
+// Quick peek at a contact list
+// ----------------------------
+//
+// Finding the right export on the gemeente's file share meant importing each
+// candidate, and a register export of 30 MB over a slow share takes minutes
+// to read in full. A peek opens the workbook (its directory and shared
+// strings) and streams the first sheet only up to PEEK_ROWS data rows, so the
+// rest of the sheet is never fetched. The header is checked against the
+// required columns (with the profile's `[columns]`, after its
+// `skip_leading_rows`, or its `input_columns` for a list without a header
+// row); nothing is converted.
+
+use crate::convert::{assumed_header, cell_to_string, header_columns};
+use crate::error::{ConvertError, Result};
+use crate::profile::Profile;
+use calamine::{Data, Reader, Xlsx};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Data rows read by a peek.
+pub const PEEK_ROWS: usize = 50;
+
+/// Header and first rows of a contact list.
+pub struct Peek {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Why a conversion would refuse the header (a missing column); None when
+    /// the required columns are there.
+    pub problem: Option<String>,
+}
+
+impl Peek {
+    /// "12 columns, first 50 rows read; all required columns found".
+    pub fn text(&self) -> String {
+        format!(
+            "{} columns, first {} rows read; {}",
+            self.header.len(),
+            self.rows.len(),
+            self.problem
+                .as_deref()
+                .unwrap_or("all required columns found")
+        )
+    }
+}
+
+/// Header and up to `max_rows` data rows of the first sheet of `path`.
+pub fn peek_xlsx(path: &Path, profile: &Profile, max_rows: usize) -> Result<Peek> {
+    let name = path.display().to_string();
+    let file = File::open(path).map_err(|e| ConvertError::io_at(path, e))?;
+    let mut workbook =
+        Xlsx::new(BufReader::new(file)).map_err(|e| ConvertError::from_xlsx(path, e))?;
+    let sheet =
+        workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or_else(|| ConvertError::UnsupportedFormat {
+                path: path.into(),
+                reason: "no sheet found in XLSX".to_string(),
+            })?;
+    let mut cells = workbook
+        .worksheet_cells_reader(&sheet)
+        .map_err(|e| ConvertError::from_xlsx(path, e))?;
+
+    // Cells come row by row; rows count from the first one with a value,
+    // as in a full read
+    let mut grid: Vec<Vec<Data>> = Vec::new();
+    let mut top = None;
+    while let Some(cell) = cells
+        .next_cell()
+        .map_err(|e| ConvertError::from_xlsx(path, e))?
+    {
+        let (row, col) = cell.get_position();
+        let row = (row - *top.get_or_insert(row)) as usize;
+        if row > profile.skip_leading_rows + max_rows {
+            break;
+        }
+        if grid.len() <= row {
+            grid.resize(row + 1, Vec::new());
+        }
+        let line = &mut grid[row];
+        if line.len() <= col as usize {
+            line.resize(col as usize + 1, Data::Empty);
+        }
+        line[col as usize] = Data::from(cell.get_value().clone());
+    }
+
+    // Columns count from column A, as in a full read. A list without a header
+    // row has the profile's input_columns instead
+    let mut grid = grid.into_iter().skip(profile.skip_leading_rows);
+    let Some(header) = assumed_header(profile).or_else(|| grid.next()) else {
+        return Err(ConvertError::UnsupportedFormat {
+            path: path.into(),
+            reason: "empty sheet (no header row)".to_string(),
+        });
+    };
+    let problem = header_columns(&name, Some(&header), profile)
+        .err()
+        .map(|e| e.to_string());
+    let text = |row: Vec<Data>| -> Vec<String> {
+        row.iter()
+            .map(|cell| cell_to_string(cell).trim().to_string())
+            .collect()
+    };
+    Ok(Peek {
+        header: text(header),
+        rows: grid.take(max_rows).map(text).collect(),
+        problem,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_xlsxwriter::Workbook;
+
+    #[test]
+    fn a_list_without_header_is_peeked_with_input_columns() {
+        let path = std::env::temp_dir().join(format!("be-alert-peek-{}.xlsx", std::process::id()));
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        for (c, value) in ["Jan", "Peeters", "Kerkstraat", "12", "0475 12 34 56", ""]
+            .iter()
+            .enumerate()
+        {
+            sheet.write_string(0, c as u16, *value).unwrap();
+        }
+        workbook.save(&path).unwrap();
+        let profile = Profile {
+            input_columns: [
+                "Voornaam",
+                "Naam",
+                "Straat",
+                "Huisnummer",
+                "Mobiel nummer",
+                "E-mailadres",
+            ]
+            .map(String::from)
+            .to_vec(),
+            ..Profile::default()
+        };
+
+        let peek = peek_xlsx(&path, &profile, PEEK_ROWS).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(peek.problem, None);
+        assert_eq!(peek.header[0], "Voornaam");
+        assert_eq!(peek.rows.len(), 1);
+        assert_eq!(peek.rows[0][1], "Peeters");
+    }
+}
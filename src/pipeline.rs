@@ -0,0 +1,1377 @@
+// src/pipeline.rs
+// Per-row conversion pipeline
+// ---------------------------
+//
+// Every data row passes the same stages, in order:
+//
+//   read cells -> (skip marked) -> (age filter) -> map columns -> (type contact) -> (group)
+//   -> (age bracket) -> normalize phone -> (extra phones) -> (sms voorkeur) -> placeholders
+//   -> email domains -> (require phone)
+//   -> clean address -> (abbreviations) -> (row hash) -> validate -> (mask rijksregister)
+//   -> (profile script)
+//   -> (canonicalize) -> check fields -> (keep note)
+//
+// Writing the CSV is left to the caller (convert.rs). Each stage implements
+// `Transform`; `Pipeline::for_profile` picks the stages a profile needs, so
+// optional behaviour (masking, filters, ...) is one more stage instead of
+// another branch in one long function.
+
+use crate::abbreviations::Abbreviations;
+use crate::convert::{
+    cell_to_string, extract_house_number, get, get_mapped, is_lossy, normalize_be_phone,
+    normalize_email, street_and_number, SheetRow, ADDRESS_COLUMN, GEBOORTEDATUM_COLUMN,
+    INWONERS_COLUMN, LANDLINE_COLUMN, OPMERKING_COLUMN, OPTIONAL_COLUMNS, OUTPUT_HEADER,
+    REQUIRED_COLUMNS, SMS_VOORKEUR_COLUMN,
+};
+use crate::dedup::Record;
+use crate::email_domains::EmailDomains;
+#[cfg(not(feature = "scripting"))]
+use crate::error::ConvertError;
+use crate::error::{Result, RowErrorKind};
+use crate::placeholder::Placeholders;
+use crate::profile::{
+    EmailTypoPolicy, MissingStreetPolicy, PhoneChannel, PhoneOrder, PlaceholderPolicy, Profile,
+    RijksregisterPolicy, RowHash, SmsPreference, UnknownPhonePolicy,
+};
+use crate::report::{CellWarning, ConvertSummary, PhoneAudit, RejectedRow, RowNote};
+use crate::rijksregister;
+#[cfg(feature = "scripting")]
+use crate::script::RowScript;
+use calamine::Data;
+use chrono::{Local, NaiveDate};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// Output fields the stages work on (index into OUTPUT_HEADER)
+const TEL_REF: usize = 0;
+const CIVILITE: usize = 1;
+const NAAM: usize = 2;
+const VOORNAAM: usize = 3;
+const ADRES: usize = 4;
+const EMAIL: usize = 9;
+const FAX: [usize; 3] = [10, 11, 12];
+const AANTAL_INWONERS: usize = 14;
+const TELEFOON: [usize; 6] = [15, 16, 17, 18, 19, 20];
+const SMS: usize = 21;
+const ZONE_LIBRE: [usize; 3] = [25, 26, 27];
+const TYPE_CONTACT: usize = 31;
+
+/// Rows between two progress reports of `Pipeline::run_with_progress`.
+const PROGRESS_STEP: usize = 1000;
+
+/// One data row on its way to the output.
+pub struct PipelineRow<'a> {
+    /// 1-based Excel row number.
+    pub row_nr: usize,
+    pub row: &'a [Data],
+    /// Output record, filled by the map stage.
+    pub record: Vec<String>,
+    /// 0-based input column each output field was read from (None for fixed
+    /// values), so problems can name the Excel cell.
+    pub sources: Vec<Option<usize>>,
+}
+
+impl PipelineRow<'_> {
+    /// The row as left out of the output, for the report and the rejected-rows file.
+    /// Rijksregisternummers are masked as in the CSV: a file holding them is
+    /// only converted under `rijksregister = "mask"`.
+    fn rejected(&self, field: &str, column: Option<usize>, kind: RowErrorKind) -> RejectedRow {
+        let kind = match kind {
+            RowErrorKind::Invalid { value, reason } => RowErrorKind::Invalid {
+                value: rijksregister::mask(&value),
+                reason,
+            },
+            kind => kind,
+        };
+        RejectedRow {
+            row: self.row_nr,
+            column,
+            field: field.to_string(),
+            kind,
+            cells: self
+                .row
+                .iter()
+                .map(|cell| rijksregister::mask(&cell_to_string(cell)))
+                .collect(),
+        }
+    }
+}
+
+pub trait Transform {
+    /// Work on one row. Returns false to leave the row out of the output;
+    /// the stage records why in the summary.
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool;
+}
+
+type Columns = HashMap<String, usize>;
+
+/// Read: date/time, error and over-long number cells in the used columns.
+/// They convert (dates as dd/mm/yyyy, errors as empty) but almost always
+/// mean the source is wrong, e.g. a phone column formatted as date. Cells
+/// that cannot be converted exactly are counted per column.
+struct ReadCells<'a> {
+    used: Vec<(&'a str, usize)>,
+}
+
+impl<'a> ReadCells<'a> {
+    fn new(cols: &'a Columns, profile: &'a Profile) -> Self {
+        let used = REQUIRED_COLUMNS
+            .into_iter()
+            .chain(OPTIONAL_COLUMNS)
+            .chain(profile.mapped_columns())
+            .filter_map(|name| cols.get(name).map(|&i| (name, i)))
+            .collect();
+        ReadCells { used }
+    }
+}
+
+impl Transform for ReadCells<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        for &(name, i) in &self.used {
+            let Some(cell) = row.row.get(i) else {
+                continue;
+            };
+            if is_lossy(cell) {
+                *summary.lossy_cells.entry(name.to_string()).or_default() += 1;
+            }
+            let (message, fix) = match cell {
+                Data::DateTime(_) | Data::DateTimeIso(_) | Data::DurationIso(_) => {
+                    summary.datetime_cells += 1;
+                    (
+                        format!(
+                            "formatted as date/time, exported as \"{}\"",
+                            cell_to_string(cell)
+                        ),
+                        "format the column as text",
+                    )
+                }
+                Data::Error(e) => {
+                    summary.error_cells += 1;
+                    (
+                        format!("Excel error {}, exported empty", e),
+                        "correct the formula or type the value",
+                    )
+                }
+                Data::Float(_) if is_lossy(cell) => (
+                    format!(
+                        "number too long for Excel (digits after the 15th are lost), exported as \"{}\"",
+                        cell_to_string(cell)
+                    ),
+                    "format the column as text and type the number again",
+                ),
+                _ => continue,
+            };
+            summary.warn_cell(CellWarning {
+                row: row.row_nr,
+                column: Some(i),
+                field: name.to_string(),
+                value: row.row.get(i).map(cell_to_string).unwrap_or_default(),
+                message,
+                fix: fix.to_string(),
+            });
+        }
+        true
+    }
+}
+
+/// Skip marked: rows with a no ("N", "nee", "0", ...) in the profile's
+/// `export_column` are left out and counted. Empty cells are exported;
+/// other values too, with a cell warning.
+struct SkipMarked<'a> {
+    name: &'a str,
+    column: usize,
+}
+
+impl Transform for SkipMarked<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let text = row
+            .row
+            .get(self.column)
+            .map(cell_to_string)
+            .unwrap_or_default();
+        if text.trim().is_empty() {
+            return true;
+        }
+        match yes_no(&text) {
+            Some(true) => true,
+            Some(false) => {
+                summary.skipped += 1;
+                false
+            }
+            None => {
+                summary.warn_cell(CellWarning {
+                    row: row.row_nr,
+                    column: Some(self.column),
+                    field: self.name.to_string(),
+                    value: text.clone(),
+                    message: format!("\"{}\" is not J or N, row exported", text.trim()),
+                    fix: "enter J or N".to_string(),
+                });
+                true
+            }
+        }
+    }
+}
+
+/// Age filter: contacts younger than the profile's `min_age` on their
+/// "Geboortedatum" are left out (BE-Alert registration excludes minors).
+/// Rows without a readable birth date are kept, with a cell warning.
+struct AgeFilter<'a> {
+    column: usize,
+    min_age: u32,
+    today: NaiveDate,
+    /// The profile's `date_formats`, tried before the usual ones.
+    formats: &'a [String],
+}
+
+/// Birth date from a date cell or text in one of `formats` (chrono syntax,
+/// e.g. "%Y%m%d"), dd/mm/yyyy, dd-mm-yyyy, dd.mm.yyyy or yyyy-mm-dd.
+fn birth_date(cell: &Data, formats: &[String]) -> Option<NaiveDate> {
+    if let Data::DateTime(dt) = cell {
+        return dt.as_datetime().map(|d| d.date());
+    }
+    let text = cell_to_string(cell);
+    formats
+        .iter()
+        .map(String::as_str)
+        .chain(["%d/%m/%Y", "%d-%m-%Y", "%d.%m.%Y", "%Y-%m-%d"])
+        .find_map(|format| NaiveDate::parse_from_str(text.trim(), format).ok())
+}
+
+impl Transform for AgeFilter<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let cell = row.row.get(self.column).unwrap_or(&Data::Empty);
+        let Some(born) = birth_date(cell, self.formats) else {
+            summary.warn_cell(CellWarning {
+                row: row.row_nr,
+                column: Some(self.column),
+                field: GEBOORTEDATUM_COLUMN.to_string(),
+                value: cell_to_string(cell),
+                message: format!("no valid birth date (\"{}\"), kept", cell_to_string(cell)),
+                fix: "enter the date as dd/mm/yyyy".to_string(),
+            });
+            return true;
+        };
+        // Whole years: born.years_since is None for dates after today
+        let adult = self
+            .today
+            .years_since(born)
+            .is_some_and(|age| age >= self.min_age);
+        if !adult {
+            summary.under_age += 1;
+        }
+        adult
+    }
+}
+
+/// Age bracket: the highest of the profile's `age_brackets` the contact has
+/// reached on their "Geboortedatum" ("80+"), into Zone libre
+/// `age_bracket_zone`; empty below the lowest. Only the bracket is exported.
+struct AgeBracket<'a> {
+    column: usize,
+    field: usize,
+    /// Highest first.
+    brackets: Vec<u32>,
+    today: NaiveDate,
+    formats: &'a [String],
+    /// AgeFilter already warns about unreadable birth dates.
+    warn: bool,
+}
+
+impl Transform for AgeBracket<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let cell = row.row.get(self.column).unwrap_or(&Data::Empty);
+        let Some(born) = birth_date(cell, self.formats) else {
+            if self.warn && !cell_to_string(cell).trim().is_empty() {
+                summary.warn_cell(CellWarning {
+                    row: row.row_nr,
+                    column: Some(self.column),
+                    field: GEBOORTEDATUM_COLUMN.to_string(),
+                    value: cell_to_string(cell),
+                    message: format!(
+                        "no valid birth date (\"{}\"), no age bracket",
+                        cell_to_string(cell)
+                    ),
+                    fix: "enter the date as dd/mm/yyyy".to_string(),
+                });
+            }
+            return true;
+        };
+        let age = self.today.years_since(born).unwrap_or(0);
+        if let Some(bracket) = self.brackets.iter().find(|&&from| age >= from) {
+            row.record[self.field] = format!("{}+", bracket);
+            row.sources[self.field] = Some(self.column);
+        }
+        true
+    }
+}
+
+/// Input column of a phone channel.
+fn channel_column(channel: PhoneChannel) -> &'static str {
+    match channel {
+        PhoneChannel::Mobile => "Mobiel nummer",
+        PhoneChannel::Landline => LANDLINE_COLUMN,
+    }
+}
+
+/// Map: input columns and the profile's fixed values to the 33 output fields.
+/// Tel/Ref. comes from the first channel in the profile's priority list with
+/// a number. Phone numbers and the address are copied raw; later stages clean them.
+struct MapColumns<'a> {
+    cols: &'a Columns,
+    profile: &'a Profile,
+    /// Official spelling of the profile's gemeente.
+    gemeente: String,
+}
+
+impl Transform for MapColumns<'_> {
+    fn apply(&self, row: &mut PipelineRow, _summary: &mut ConvertSummary) -> bool {
+        let (cols, profile, row_data) = (self.cols, self.profile, row.row);
+
+        // Read XLSX fields
+        let xlsx_voornaam = get(cols, row_data, "Voornaam");
+        let xlsx_naam = get(cols, row_data, "Naam");
+
+        let (straat, huisnr_raw) = street_and_number(cols, row_data);
+        let adres_raw = format!("{} {}", straat, huisnr_raw).trim().to_string();
+
+        let email = normalize_email(&get(cols, row_data, "E-mailadres"));
+        // First channel of the profile's priority list that has a number;
+        // without one, the first channel is the cell to fill in
+        let channels: Vec<(String, Option<usize>)> = profile
+            .phone_channels
+            .iter()
+            .map(|&channel| {
+                let column = channel_column(channel);
+                (get(cols, row_data, column), cols.get(column).copied())
+            })
+            .collect();
+        let (phone_raw, phone_column) = channels
+            .iter()
+            .find(|(number, _)| !number.is_empty())
+            .or(channels.first())
+            .cloned()
+            .unwrap_or_default();
+        let inwoners = get(cols, row_data, INWONERS_COLUMN);
+
+        let civilite_column = profile
+            .civilite_column
+            .as_deref()
+            .filter(|&column| !get(cols, row_data, column).is_empty());
+        let civilite = civilite_column
+            .map(|column| get(cols, row_data, column))
+            .unwrap_or_else(|| profile.civilite.clone());
+
+        let fax = |slot| get_mapped(cols, row_data, &profile.fax_columns, slot);
+        let zone_libre = |slot| get_mapped(cols, row_data, &profile.zone_libre_columns, slot);
+
+        // IMPORTANT: swap output fields (provider error)
+        // CSV "Voornaam" <- XLSX "Naam"
+        // CSV "Naam"     <- XLSX "Voornaam"
+        let csv_voornaam = xlsx_voornaam;
+        let csv_naam = xlsx_naam;
+
+        row.record = vec![
+            phone_raw,                    // Tel/Ref.
+            civilite,                     // Civilité
+            csv_naam,                     // Naam
+            csv_voornaam,                 // VoorNaam
+            adres_raw,                    // Adres incl huisnummer
+            String::new(),                // Bijkomend adres
+            profile.postcode.clone(),     // Postcode
+            self.gemeente.clone(),        // Gemeente
+            String::new(),                // Geboortedatum
+            email,                        // Email
+            fax(0),                       // FAX
+            fax(1),                       // FAX2
+            fax(2),                       // FAX3
+            String::new(),                // Verdieping
+            inwoners,                     // Aantal inwoners
+            String::new(),                // Telefoon 2
+            String::new(),                // Telefoon 3
+            String::new(),                // Telefoon 4
+            String::new(),                // Telefoon 5
+            String::new(),                // Telefoon 6
+            String::new(),                // Telefoon 7
+            String::new(),                // SMS
+            String::new(),                // SMS 2
+            String::new(),                // SMS 3
+            String::new(),                // Pager
+            zone_libre(0),                // Zone libre 1
+            zone_libre(1),                // Zone libre 2
+            zone_libre(2),                // Zone libre 3
+            profile.taal.clone(),         // Taal
+            profile.land.clone(),         // Land
+            profile.rode_lijst.clone(),   // Zwarte lijst
+            profile.type_contact.clone(), // Type Contact
+            String::new(),                // GPS coördinaten
+        ];
+
+        let column = |name: &str| cols.get(name).copied();
+        let mapped = |columns: &[String], slot: usize| columns.get(slot).and_then(|c| column(c));
+        row.sources = vec![None; OUTPUT_HEADER.len()];
+        row.sources[TEL_REF] = phone_column;
+        row.sources[CIVILITE] = civilite_column.and_then(column);
+        row.sources[NAAM] = column("Naam");
+        row.sources[VOORNAAM] = column("Voornaam");
+        row.sources[ADRES] = column("Straat").or_else(|| column(ADDRESS_COLUMN));
+        row.sources[EMAIL] = column("E-mailadres");
+        row.sources[AANTAL_INWONERS] = column(INWONERS_COLUMN);
+        for slot in 0..3 {
+            row.sources[FAX[slot]] = mapped(&profile.fax_columns, slot);
+            row.sources[ZONE_LIBRE[slot]] = mapped(&profile.zone_libre_columns, slot);
+        }
+        true
+    }
+}
+
+/// Type Contact from the profile's category column: the value is looked up
+/// in `type_contacts` (case-insensitive). Empty cells keep the profile's
+/// fixed Type Contact; unknown values too, with a cell warning.
+struct MapTypeContact<'a> {
+    column: &'a str,
+    index: usize,
+    codes: HashMap<String, &'a str>,
+}
+
+impl<'a> MapTypeContact<'a> {
+    fn new(column: &'a str, index: usize, profile: &'a Profile) -> Self {
+        let codes = profile
+            .type_contacts
+            .iter()
+            .map(|(category, code)| (category.trim().to_lowercase(), code.as_str()))
+            .collect();
+        MapTypeContact {
+            column,
+            index,
+            codes,
+        }
+    }
+}
+
+impl Transform for MapTypeContact<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let category = row
+            .row
+            .get(self.index)
+            .map(cell_to_string)
+            .unwrap_or_default();
+        let category = category.trim();
+        if category.is_empty() {
+            return true;
+        }
+        match self.codes.get(&category.to_lowercase()) {
+            Some(code) => {
+                row.record[TYPE_CONTACT] = code.to_string();
+                row.sources[TYPE_CONTACT] = Some(self.index);
+            }
+            None => summary.warn_cell(CellWarning {
+                row: row.row_nr,
+                column: Some(self.index),
+                field: self.column.to_string(),
+                value: category.to_string(),
+                message: format!(
+                    "\"{}\" is not in type_contacts, exported as Type Contact \"{}\"",
+                    category, row.record[TYPE_CONTACT]
+                ),
+                fix: "use a known category, or add it to [type_contacts] in the profile"
+                    .to_string(),
+            }),
+        }
+        true
+    }
+}
+
+/// Group: the profile's `group_column` value, looked up in `groups`
+/// (case-insensitive), or the fixed `group`, into Zone libre `group_zone`.
+/// Unknown values are written as they are, with a cell warning.
+struct MapGroup<'a> {
+    /// Name and index of group_column, when the input has it.
+    column: Option<(&'a str, usize)>,
+    field: usize,
+    fixed: &'a str,
+    names: HashMap<String, &'a str>,
+}
+
+impl<'a> MapGroup<'a> {
+    fn new(column: Option<(&'a str, usize)>, profile: &'a Profile) -> Self {
+        let names = profile
+            .groups
+            .iter()
+            .map(|(code, name)| (code.trim().to_lowercase(), name.as_str()))
+            .collect();
+        MapGroup {
+            column,
+            field: ZONE_LIBRE[profile.group_zone - 1],
+            fixed: &profile.group,
+            names,
+        }
+    }
+}
+
+impl Transform for MapGroup<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let value = self
+            .column
+            .and_then(|(_, index)| row.row.get(index))
+            .map(cell_to_string)
+            .unwrap_or_default();
+        let value = value.trim();
+        let Some((name, index)) = self.column.filter(|_| !value.is_empty()) else {
+            row.record[self.field] = self.fixed.to_string();
+            return true;
+        };
+        row.sources[self.field] = Some(index);
+        row.record[self.field] = match self.names.get(&value.to_lowercase()) {
+            Some(group) => group.to_string(),
+            None => {
+                if !self.names.is_empty() {
+                    summary.warn_cell(CellWarning {
+                        row: row.row_nr,
+                        column: Some(index),
+                        field: name.to_string(),
+                        value: value.to_string(),
+                        message: format!("\"{}\" is not in groups, exported as it is", value),
+                        fix: "use a known code, or add it to [groups] in the profile".to_string(),
+                    });
+                }
+                value.to_string()
+            }
+        };
+        true
+    }
+}
+
+/// Normalize phone: Tel/Ref. and FAX fields to 0032..., recording the phone
+/// audit and applying the profile's unknown-number policy. Cells with words
+/// in them ("0475 12 34 56 (dochter)", "geen") get a cell warning.
+struct NormalizePhone<'a> {
+    cols: &'a Columns,
+    policy: UnknownPhonePolicy,
+    /// Input column of each phone channel, to name the one Tel/Ref. came from.
+    channels: Vec<(&'static str, usize)>,
+}
+
+impl Transform for NormalizePhone<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        for i in std::iter::once(TEL_REF).chain(FAX) {
+            let value = &row.record[i];
+            if value.chars().any(char::is_alphabetic) {
+                let number = normalize_be_phone(value);
+                summary.warn_cell(CellWarning {
+                    row: row.row_nr,
+                    column: row.sources[i],
+                    field: OUTPUT_HEADER[i].to_string(),
+                    value: value.clone(),
+                    message: if number.is_empty() {
+                        format!("\"{}\" holds no phone number", value)
+                    } else {
+                        format!(
+                            "\"{}\" holds text besides the number, read as {}",
+                            value, number
+                        )
+                    },
+                    fix: if number.is_empty() {
+                        "enter a phone number or clear the cell".to_string()
+                    } else {
+                        "keep only the number in the cell, notes in another column".to_string()
+                    },
+                });
+            }
+        }
+        for i in FAX {
+            row.record[i] = normalize_be_phone(&row.record[i]);
+        }
+        let original = std::mem::take(&mut row.record[TEL_REF]);
+        row.record[TEL_REF] = normalize_be_phone(&original);
+
+        let normalized = &row.record[TEL_REF];
+        let channel = self
+            .channels
+            .iter()
+            .find(|&&(_, column)| row.sources[TEL_REF] == Some(column))
+            .map_or("Tel/Ref.", |&(name, _)| name);
+        // Masked as in the CSV (see PipelineRow::rejected): the audit ends up
+        // in the report
+        let mut audit = PhoneAudit {
+            row: row.row_nr,
+            column: row.sources[TEL_REF],
+            channel: channel.to_string(),
+            name: rijksregister::mask(&format!(
+                "{} {}",
+                get(self.cols, row.row, "Voornaam"),
+                get(self.cols, row.row, "Naam")
+            )),
+            original: rijksregister::mask(&original),
+            normalized: rijksregister::mask(normalized),
+            // Anything normalize_be_phone could not turn into a 0032... number
+            unknown: !normalized.is_empty() && !normalized.starts_with("0032"),
+            note: String::new(),
+        };
+
+        let mut keep = true;
+        if audit.unknown {
+            summary.unknown_phones += 1;
+            match self.policy {
+                UnknownPhonePolicy::Keep => {}
+                UnknownPhonePolicy::Blank => {
+                    row.record[TEL_REF].clear();
+                    audit.note = "blanked".to_string();
+                }
+                UnknownPhonePolicy::Reject => {
+                    audit.note = "row rejected".to_string();
+                    summary.rejected.push(row.rejected(
+                        channel,
+                        row.sources[TEL_REF],
+                        RowErrorKind::Invalid {
+                            value: audit.original.clone(),
+                            reason: "is not a recognised Belgian phone number".to_string(),
+                        },
+                    ));
+                    keep = false;
+                }
+            }
+        }
+        summary.phone_audit.push(audit);
+        keep
+    }
+}
+
+/// Extra phones: the numbers of the phone channels that did not fill
+/// Tel/Ref. and of the profile's `extra_phone_columns`, normalized, to
+/// Telefoon 2..7 in the order of `extra_phone_order`. Numbers that do not fit
+/// are counted and get a cell warning, as do numbers that are not Belgian
+/// (left out); repeated numbers are written once.
+struct FillExtraPhones<'a> {
+    /// Candidate columns (name, index) in profile order.
+    columns: Vec<(&'a str, usize)>,
+    order: PhoneOrder,
+}
+
+impl Transform for FillExtraPhones<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let mut numbers: Vec<(&str, usize, String)> = Vec::new();
+        for &(name, index) in &self.columns {
+            if row.sources[TEL_REF] == Some(index) {
+                continue;
+            }
+            let raw = row.row.get(index).map(cell_to_string).unwrap_or_default();
+            let number = normalize_be_phone(&raw);
+            if number.is_empty()
+                || number == row.record[TEL_REF]
+                || numbers.iter().any(|(_, _, n)| *n == number)
+            {
+                continue;
+            }
+            if !number.starts_with("0032") {
+                summary.warn_cell(CellWarning {
+                    row: row.row_nr,
+                    column: Some(index),
+                    field: name.to_string(),
+                    value: raw.trim().to_string(),
+                    message: format!(
+                        "\"{}\" is not a recognised Belgian phone number, not exported",
+                        raw.trim()
+                    ),
+                    fix: "correct the number or clear the cell".to_string(),
+                });
+                continue;
+            }
+            numbers.push((name, index, number));
+        }
+        match self.order {
+            PhoneOrder::MobileFirst => numbers.sort_by_key(|(_, _, n)| !n.starts_with("00324")),
+            PhoneOrder::LandlineFirst => numbers.sort_by_key(|(_, _, n)| n.starts_with("00324")),
+            PhoneOrder::Input => {}
+        }
+        let mut numbers = numbers.into_iter();
+        for (field, (_, index, number)) in TELEFOON.into_iter().zip(numbers.by_ref()) {
+            row.record[field] = number;
+            row.sources[field] = Some(index);
+        }
+        for (name, index, number) in numbers {
+            summary.phone_overflow += 1;
+            summary.warn_cell(CellWarning {
+                row: row.row_nr,
+                column: Some(index),
+                field: name.to_string(),
+                message: format!(
+                    "{} does not fit in Telefoon 2..7 (six numbers), not exported",
+                    number
+                ),
+                value: number,
+                fix: "remove numbers no longer in use, or change extra_phone_order".to_string(),
+            });
+        }
+        true
+    }
+}
+
+/// SMS voorkeur: the mobile number of a contact preferring SMS (by the
+/// profile's `sms_preference`) moves from Tel/Ref. to SMS. Landline numbers
+/// cannot get an SMS and stay in Tel/Ref., as do numbers with an empty or
+/// unreadable choice; both of the latter with a cell warning.
+struct ApplySmsVoorkeur {
+    column: usize,
+    preference: SmsPreference,
+}
+
+/// J/N cell as yes (true) or no (false); None when not readable as either.
+fn yes_no(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "j" | "ja" | "y" | "yes" | "x" | "1" | "o" | "oui" | "waar" | "true" => Some(true),
+        "n" | "nee" | "no" | "0" | "non" | "onwaar" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+impl Transform for ApplySmsVoorkeur {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let text = row
+            .row
+            .get(self.column)
+            .map(cell_to_string)
+            .unwrap_or_default();
+        if text.trim().is_empty() {
+            return true;
+        }
+        let warn = |summary: &mut ConvertSummary, message: String, fix: &str| {
+            summary.warn_cell(CellWarning {
+                row: row.row_nr,
+                column: Some(self.column),
+                field: SMS_VOORKEUR_COLUMN.to_string(),
+                value: text.clone(),
+                message,
+                fix: fix.to_string(),
+            })
+        };
+        let Some(yes) = yes_no(&text) else {
+            warn(
+                summary,
+                format!("\"{}\" is not J or N, number kept in Tel/Ref.", text.trim()),
+                "enter J or N",
+            );
+            return true;
+        };
+        let number = &row.record[TEL_REF];
+        if yes != (self.preference == SmsPreference::Sms) || number.is_empty() {
+            return true;
+        }
+        if !number.starts_with("00324") {
+            let message = format!(
+                "SMS preferred, but {} is not a mobile number; kept in Tel/Ref.",
+                number
+            );
+            warn(
+                summary,
+                message,
+                "enter a mobile number (04..) or choose calls",
+            );
+            return true;
+        }
+        row.record[SMS] = std::mem::take(&mut row.record[TEL_REF]);
+        row.sources[SMS] = row.sources[TEL_REF].take();
+        summary.sms_preferred += 1;
+        true
+    }
+}
+
+/// Placeholders: test and placeholder values (see placeholder.rs) in the
+/// phone numbers, names, e-mail and Straat, handled by the profile's
+/// `placeholder_values` policy.
+struct FlagPlaceholders<'a> {
+    cols: &'a Columns,
+    table: Placeholders,
+    policy: PlaceholderPolicy,
+}
+
+impl Transform for FlagPlaceholders<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let fields: Vec<usize> = [TEL_REF, SMS]
+            .into_iter()
+            .chain(FAX)
+            .filter(|&i| self.table.is_fake_number(&row.record[i]))
+            .chain(
+                [NAAM, VOORNAAM, EMAIL]
+                    .into_iter()
+                    .filter(|&i| self.table.is_placeholder(&row.record[i])),
+            )
+            .collect();
+        let mut found: Vec<(Option<usize>, String, String)> = fields
+            .into_iter()
+            .map(|i| {
+                let value = match self.policy {
+                    PlaceholderPolicy::Blank => std::mem::take(&mut row.record[i]),
+                    _ => row.record[i].clone(),
+                };
+                (row.sources[i], OUTPUT_HEADER[i].to_string(), value)
+            })
+            .collect();
+        let (straat, _) = street_and_number(self.cols, row.row);
+        if self.table.is_placeholder(&straat) {
+            let column = self
+                .cols
+                .get("Straat")
+                .or_else(|| self.cols.get(ADDRESS_COLUMN))
+                .copied();
+            found.push((column, "Straat".to_string(), straat));
+        }
+
+        for (column, field, value) in found {
+            summary.placeholders += 1;
+            if self.policy == PlaceholderPolicy::Reject {
+                summary.rejected.push(row.rejected(
+                    &field,
+                    column,
+                    RowErrorKind::Invalid {
+                        value,
+                        reason: "is a placeholder or test value".to_string(),
+                    },
+                ));
+                return false;
+            }
+            let blanked = self.policy == PlaceholderPolicy::Blank && field != "Straat";
+            summary.warn_cell(CellWarning {
+                row: row.row_nr,
+                column,
+                message: format!(
+                    "\"{}\" looks like a placeholder or test value{}",
+                    value,
+                    if blanked { ", left empty" } else { "" }
+                ),
+                field,
+                value,
+                fix: "enter the real value or clear the cell".to_string(),
+            });
+        }
+        true
+    }
+}
+
+/// E-mail domains: an address whose domain looks like a typo of a common one
+/// (see email_domains.rs) gets the suggestion in a cell warning, or is
+/// corrected under `email_typos = "fix"`.
+struct CheckEmailDomains {
+    table: EmailDomains,
+    policy: EmailTypoPolicy,
+}
+
+impl Transform for CheckEmailDomains {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let Some(suggestion) = self.table.suggest(&row.record[EMAIL]) else {
+            return true;
+        };
+        summary.email_typos += 1;
+        let value = row.record[EMAIL].clone();
+        let (message, fix) = if self.policy == EmailTypoPolicy::Fix {
+            row.record[EMAIL] = suggestion.clone();
+            (
+                format!("\"{}\" corrected to \"{}\"", value, suggestion),
+                "check the correction and make it in the register too".to_string(),
+            )
+        } else {
+            (
+                format!(
+                    "\"{}\" looks like a typo; did you mean \"{}\"?",
+                    value, suggestion
+                ),
+                format!("correct it to {} if that is the address", suggestion),
+            )
+        };
+        summary.warn_cell(CellWarning {
+            row: row.row_nr,
+            column: row.sources[EMAIL],
+            field: OUTPUT_HEADER[EMAIL].to_string(),
+            value,
+            message,
+            fix,
+        });
+        true
+    }
+}
+
+/// Contacts without any phone number (email-only) are left out when the
+/// profile does not allow them. A number in SMS ("SMS voorkeur") counts.
+struct RequirePhone;
+
+impl Transform for RequirePhone {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        if !row.record[TEL_REF].is_empty() || !row.record[SMS].is_empty() {
+            return true;
+        }
+        summary
+            .rejected
+            .push(row.rejected("Tel/Ref.", row.sources[TEL_REF], RowErrorKind::Empty));
+        false
+    }
+}
+
+/// Clean address: "Adres incl huisnummer" uses ONLY the numeric part of
+/// Huisnummer (11A -> 11, "12 Bus 3" -> 12). A house number without street
+/// is handled by the profile's `missing_street` policy, with a cell warning.
+struct CleanAddress<'a> {
+    cols: &'a Columns,
+    policy: MissingStreetPolicy,
+    placeholder: &'a str,
+}
+
+impl Transform for CleanAddress<'_> {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let (straat, huisnr) = street_and_number(self.cols, row.row);
+        let huisnr = extract_house_number(&huisnr);
+        if !straat.trim().is_empty() || huisnr.is_empty() {
+            row.record[ADRES] = format!("{} {}", straat, huisnr).trim().to_string();
+            return true;
+        }
+        let (adres, message) = match self.policy {
+            MissingStreetPolicy::Reject => {
+                summary.rejected.push(row.rejected(
+                    "Straat",
+                    row.sources[ADRES],
+                    RowErrorKind::Empty,
+                ));
+                return false;
+            }
+            MissingStreetPolicy::Keep => (
+                huisnr.clone(),
+                "no street, exported as the house number only".to_string(),
+            ),
+            MissingStreetPolicy::Blank => (
+                String::new(),
+                "no street, address exported empty".to_string(),
+            ),
+            MissingStreetPolicy::Placeholder => {
+                let adres = format!("{} {}", self.placeholder.trim(), huisnr);
+                let message = format!("no street, exported as \"{}\"", adres);
+                (adres, message)
+            }
+        };
+        summary.missing_street += 1;
+        summary.warn_cell(CellWarning {
+            row: row.row_nr,
+            column: row.sources[ADRES],
+            field: "Straat".to_string(),
+            value: huisnr,
+            message,
+            fix: "fill in the street (BE-Alert cannot locate a house number alone)".to_string(),
+        });
+        row.record[ADRES] = adres;
+        true
+    }
+}
+
+/// Abbreviations: street name abbreviations in "Adres incl huisnummer"
+/// written out ("Kon. Albertlaan" -> "Koning Albertlaan"), counted.
+struct ExpandAbbreviations {
+    table: Abbreviations,
+}
+
+impl Transform for ExpandAbbreviations {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        if let Some(adres) = self.table.expand(&row.record[ADRES]) {
+            row.record[ADRES] = adres;
+            summary.abbreviations_expanded += 1;
+        }
+        true
+    }
+}
+
+/// Row hash: the first 16 hex digits of a SHA-256 of the input row or the
+/// contact's name and address (profile `row_hash`) in Zone libre 3. Cells
+/// holding a rijksregisternummer are left out of the hash.
+struct HashRow<'a> {
+    cols: &'a Columns,
+    mode: RowHash,
+    salt: &'a str,
+}
+
+impl Transform for HashRow<'_> {
+    fn apply(&self, row: &mut PipelineRow, _summary: &mut ConvertSummary) -> bool {
+        let parts: Vec<String> = match self.mode {
+            RowHash::Off => return true,
+            RowHash::Row => row
+                .row
+                .iter()
+                .map(|cell| cell_to_string(cell).trim().to_string())
+                .filter(|cell| !rijksregister::contains(cell))
+                .collect(),
+            RowHash::Contact => {
+                let (straat, huisnummer) = street_and_number(self.cols, row.row);
+                [
+                    get(self.cols, row.row, "Naam"),
+                    get(self.cols, row.row, "Voornaam"),
+                    straat,
+                    huisnummer,
+                ]
+                .iter()
+                .map(|part| part.to_lowercase())
+                .collect()
+            }
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        for part in &parts {
+            // Separator, so ("ab", "c") and ("a", "bc") differ
+            hasher.update([0x1f]);
+            hasher.update(part.as_bytes());
+        }
+        row.sources[ZONE_LIBRE[2]] = None;
+        row.record[ZONE_LIBRE[2]] = hasher.finalize()[..8]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        true
+    }
+}
+
+/// Validate: fields that must hold a specific kind of value are emptied
+/// (with a cell warning) when they do not.
+struct Validate;
+
+/// "Aantal inwoners" must be a whole number of at least 1.
+pub(crate) fn is_residents_count(value: &str) -> bool {
+    value.chars().all(|c| c.is_ascii_digit()) && value.parse::<u32>().is_ok_and(|n| n > 0)
+}
+
+impl Transform for Validate {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let inwoners = &mut row.record[AANTAL_INWONERS];
+        if !inwoners.is_empty() && !is_residents_count(inwoners) {
+            summary.warn_cell(CellWarning {
+                row: row.row_nr,
+                column: row.sources[AANTAL_INWONERS],
+                field: INWONERS_COLUMN.to_string(),
+                value: inwoners.clone(),
+                message: format!(
+                    "\"{}\" is not a number of residents, exported empty",
+                    inwoners
+                ),
+                fix: "enter a whole number".to_string(),
+            });
+            inwoners.clear();
+        }
+        true
+    }
+}
+
+/// Anonymize: rijksregisternummers replaced by '*' (profile `rijksregister = "mask"`).
+struct MaskRijksregister;
+
+impl Transform for MaskRijksregister {
+    fn apply(&self, row: &mut PipelineRow, _summary: &mut ConvertSummary) -> bool {
+        for field in &mut row.record {
+            *field = rijksregister::mask(field);
+        }
+        true
+    }
+}
+
+/// Profile script: after the other changes, so it sees the record as it would be written.
+#[cfg(feature = "scripting")]
+struct ScriptHook {
+    script: RowScript,
+}
+
+#[cfg(feature = "scripting")]
+impl Transform for ScriptHook {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let message = match self.script.before_write(&mut row.record) {
+            Ok(true) => return true,
+            Ok(false) => "dropped the row".to_string(),
+            Err(e) => e,
+        };
+        summary
+            .rejected
+            .push(row.rejected("script", None, RowErrorKind::Script(message)));
+        false
+    }
+}
+
+/// Languages BE-Alert accepts in Taal.
+const LANGUAGES: [&str; 4] = ["NL", "FR", "DE", "EN"];
+
+/// What output field `field` should hold when `value` is not accepted by
+/// BE-Alert, or None. Empty values pass; only Postcode, Taal, Land and the
+/// GPS coordinates are checked.
+pub(crate) fn field_problem(field: &str, value: &str) -> Option<&'static str> {
+    if value.is_empty() {
+        return None;
+    }
+    match field {
+        "Postcode" if !(value.len() == 4 && value.bytes().all(|b| b.is_ascii_digit())) => {
+            Some("4 digits")
+        }
+        "Taal" if !LANGUAGES.contains(&value) => Some("NL, FR, DE or EN"),
+        "Land" if !(value.len() == 2 && value.bytes().all(|b| b.is_ascii_uppercase())) => {
+            Some("a 2-letter country code (BE)")
+        }
+        "GPS coördinaten" if !is_gps(value) => Some("\"lat,long\" (e.g. 50.87,5.30)"),
+        _ => None,
+    }
+}
+
+/// "lat,long" in decimal degrees.
+fn is_gps(value: &str) -> bool {
+    let Some((lat, long)) = value.split_once(',') else {
+        return false;
+    };
+    match (lat.trim().parse::<f64>(), long.trim().parse::<f64>()) {
+        (Ok(lat), Ok(long)) => lat.abs() <= 90.0 && long.abs() <= 180.0,
+        _ => false,
+    }
+}
+
+/// Canonicalize (profile `reproducible`): every field trimmed, with runs of
+/// whitespace (tabs, line breaks, non-breaking spaces) as one space and
+/// zero-width characters removed, so incidental formatting in the source
+/// never shows up as a change between two exports.
+struct Canonicalize;
+
+/// `text` with whitespace collapsed and zero-width characters dropped.
+pub(crate) fn canonical_text(text: &str) -> String {
+    text.split(|c: char| c.is_whitespace())
+        .map(|word| word.replace(['\u{200b}', '\u{200c}', '\u{200d}', '\u{feff}'], ""))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Transform for Canonicalize {
+    fn apply(&self, row: &mut PipelineRow, _summary: &mut ConvertSummary) -> bool {
+        for field in &mut row.record {
+            *field = canonical_text(field);
+        }
+        true
+    }
+}
+
+/// Check fields: rows with a value BE-Alert would refuse in Postcode, Taal,
+/// Land or GPS are left out. Last, so values set by the script are checked too.
+struct CheckFields;
+
+impl Transform for CheckFields {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let problem = OUTPUT_HEADER
+            .iter()
+            .zip(&row.record)
+            .enumerate()
+            .find_map(|(i, (field, value))| Some((i, field, value, field_problem(field, value)?)));
+        let Some((i, field, value, expected)) = problem else {
+            return true;
+        };
+        let kind = RowErrorKind::Invalid {
+            value: value.clone(),
+            reason: format!("is not valid for BE-Alert, expected {}", expected),
+        };
+        summary
+            .rejected
+            .push(row.rejected(field, row.sources[i], kind));
+        false
+    }
+}
+
+/// Keep note: the "Opmerking" cell of a row that is written goes to the
+/// summary, for the report and the history. Last, so only rows that made it.
+struct KeepNote {
+    column: usize,
+}
+
+impl Transform for KeepNote {
+    fn apply(&self, row: &mut PipelineRow, summary: &mut ConvertSummary) -> bool {
+        let text = row
+            .row
+            .get(self.column)
+            .map(cell_to_string)
+            .unwrap_or_default();
+        // Masked in any case: the note is stored in the history
+        let text = rijksregister::mask(text.trim());
+        if !text.is_empty() {
+            summary.notes.push(RowNote {
+                row: row.row_nr,
+                column: self.column,
+                name: format!("{} {}", row.record[VOORNAAM], row.record[NAAM])
+                    .trim()
+                    .to_string(),
+                tel_ref: row.record[TEL_REF].clone(),
+                text,
+            });
+        }
+        true
+    }
+}
+
+pub struct Pipeline<'a> {
+    stages: Vec<Box<dyn Transform + 'a>>,
+}
+
+impl<'a> Pipeline<'a> {
+    /// The stages for one profile, reading columns by the header mapping `cols`.
+    /// Fails only when the profile's script cannot be loaded.
+    pub fn for_profile(cols: &'a Columns, profile: &'a Profile) -> Result<Self> {
+        let mut stages: Vec<Box<dyn Transform + 'a>> =
+            vec![Box::new(ReadCells::new(cols, profile))];
+        // check_profile_columns makes sure the columns the profile names are
+        // there: the lookups below find them whenever the option is set
+        if let Some(name) = profile.export_column.as_deref() {
+            if let Some(&column) = cols.get(name) {
+                stages.push(Box::new(SkipMarked { name, column }));
+            }
+        }
+        if let (Some(min_age), Some(&column)) = (profile.min_age, cols.get(GEBOORTEDATUM_COLUMN)) {
+            stages.push(Box::new(AgeFilter {
+                column,
+                min_age,
+                today: Local::now().date_naive(),
+                formats: &profile.date_formats,
+            }));
+        }
+        stages.push(Box::new(MapColumns {
+            cols,
+            profile,
+            gemeente: profile.official_gemeente(),
+        }));
+        if let Some(column) = profile.type_contact_column.as_deref() {
+            if let Some(&index) = cols.get(column) {
+                stages.push(Box::new(MapTypeContact::new(column, index, profile)));
+            }
+        }
+        if profile.group_column.is_some() || !profile.group.is_empty() {
+            let column = profile
+                .group_column
+                .as_deref()
+                .and_then(|name| Some((name, *cols.get(name)?)));
+            stages.push(Box::new(MapGroup::new(column, profile)));
+        }
+        if let (Some(zone), Some(&column)) =
+            (profile.age_bracket_zone, cols.get(GEBOORTEDATUM_COLUMN))
+        {
+            let mut brackets = profile.age_brackets.clone();
+            brackets.sort_unstable_by(|a, b| b.cmp(a));
+            stages.push(Box::new(AgeBracket {
+                column,
+                field: ZONE_LIBRE[zone - 1],
+                brackets,
+                today: Local::now().date_naive(),
+                formats: &profile.date_formats,
+                warn: profile.min_age.is_none(),
+            }));
+        }
+        stages.push(Box::new(NormalizePhone {
+            cols,
+            policy: profile.unknown_phone,
+            channels: profile
+                .phone_channels
+                .iter()
+                .map(|&channel| channel_column(channel))
+                .filter_map(|name| Some((name, *cols.get(name)?)))
+                .collect(),
+        }));
+        if !profile.extra_phone_columns.is_empty() {
+            // check_profile_columns makes sure the extra columns are there
+            let columns = profile
+                .phone_channels
+                .iter()
+                .map(|&channel| channel_column(channel))
+                .chain(profile.extra_phone_columns.iter().map(String::as_str))
+                .filter_map(|name| Some((name, *cols.get(name)?)))
+                .collect();
+            stages.push(Box::new(FillExtraPhones {
+                columns,
+                order: profile.extra_phone_order,
+            }));
+        }
+        if let Some(&column) = cols.get(SMS_VOORKEUR_COLUMN) {
+            stages.push(Box::new(ApplySmsVoorkeur {
+                column,
+                preference: profile.sms_preference,
+            }));
+        }
+        stages.push(Box::new(FlagPlaceholders {
+            cols,
+            table: Placeholders::new(&profile.placeholder_patterns)?,
+            policy: profile.placeholder_values,
+        }));
+        stages.push(Box::new(CheckEmailDomains {
+            table: EmailDomains::new(&profile.email_domains),
+            policy: profile.email_typos,
+        }));
+        if !profile.allow_email_only {
+            stages.push(Box::new(RequirePhone));
+        }
+        stages.push(Box::new(CleanAddress {
+            cols,
+            policy: profile.missing_street,
+            placeholder: &profile.street_placeholder,
+        }));
+        if profile.expand_abbreviations {
+            stages.push(Box::new(ExpandAbbreviations {
+                table: Abbreviations::new(&profile.street_abbreviations),
+            }));
+        }
+        if profile.row_hash != RowHash::Off {
+            stages.push(Box::new(HashRow {
+                cols,
+                mode: profile.row_hash,
+                salt: &profile.row_hash_salt,
+            }));
+        }
+        stages.push(Box::new(Validate));
+        if profile.rijksregister == RijksregisterPolicy::Mask {
+            stages.push(Box::new(MaskRijksregister));
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = RowScript::load(profile)? {
+            stages.push(Box::new(ScriptHook { script }));
+        }
+        #[cfg(not(feature = "scripting"))]
+        if let Some(script) = &profile.script {
+            return Err(ConvertError::Script {
+                path: script.into(),
+                message: "this build has no scripting support".to_string(),
+            });
+        }
+        if profile.reproducible {
+            stages.push(Box::new(Canonicalize));
+        }
+        stages.push(Box::new(CheckFields));
+        if let Some(&column) = cols.get(OPMERKING_COLUMN) {
+            stages.push(Box::new(KeepNote { column }));
+        }
+        Ok(Pipeline { stages })
+    }
+
+    /// Run every row through the stages; rows a stage drops are left out.
+    pub fn run(&self, rows: &[SheetRow], summary: &mut ConvertSummary) -> Vec<Record> {
+        self.run_with_progress(rows, summary, &|_, _| {})
+    }
+
+    /// As `run`, calling `progress` with (rows done, rows in total) every
+    /// PROGRESS_STEP rows and once at the end, e.g. for a time estimate.
+    pub fn run_with_progress(
+        &self,
+        rows: &[SheetRow],
+        summary: &mut ConvertSummary,
+        progress: &dyn Fn(usize, usize),
+    ) -> Vec<Record> {
+        let mut records = Vec::with_capacity(rows.len());
+        'rows: for (done, &(row_nr, row)) in rows.iter().enumerate() {
+            if done % PROGRESS_STEP == 0 {
+                progress(done, rows.len());
+            }
+            let mut item = PipelineRow {
+                row_nr,
+                row,
+                record: Vec::new(),
+                sources: Vec::new(),
+            };
+            for stage in &self.stages {
+                if !stage.apply(&mut item, summary) {
+                    continue 'rows;
+                }
+            }
+            records.push((row_nr, item.record));
+        }
+        progress(rows.len(), rows.len());
+        records
+    }
+}
@@ -0,0 +1,89 @@
+// src/placeholder.rs
+// Placeholder and test values
+// ---------------------------
+//
+// Registers keep values that were never real: "0000000000" or "0123456789"
+// typed to get past a required field, "test@test.be", "XXX", "onbekend".
+// Exported, they become alert contacts nobody receives. Names, e-mail, Straat
+// and phone numbers are checked against the built-in patterns plus the
+// profile's `placeholder_patterns` (case-insensitive, `*` and `?` as in file
+// names); phone numbers also count as fake when their digits are all the same
+// or count up or down. What happens to a match is the profile's
+// `placeholder_values` policy (see pipeline.rs).
+
+use crate::error::{ConvertError, Result};
+use glob::{MatchOptions, Pattern};
+
+const BUILT_IN: [&str; 24] = [
+    "test",
+    "test *",
+    "* test",
+    "xxx*",
+    "onbekend*",
+    "unknown",
+    "inconnu*",
+    "n/a",
+    "nvt",
+    "n.v.t.",
+    "geen",
+    "aucun",
+    "-",
+    "[?]",
+    "...",
+    "dummy",
+    "fake",
+    "test@*",
+    "*@test.*",
+    "*@example.*",
+    "*@voorbeeld.*",
+    "noreply@*",
+    "nomail@*",
+    "geen@*",
+];
+
+const MATCH: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+/// Patterns of one profile.
+pub struct Placeholders {
+    patterns: Vec<Pattern>,
+}
+
+impl Placeholders {
+    /// The built-in patterns and the profile's `placeholder_patterns`.
+    pub fn new(profile_patterns: &[String]) -> Result<Self> {
+        let mut patterns: Vec<Pattern> = BUILT_IN
+            .iter()
+            .filter_map(|p| Pattern::new(p).ok())
+            .collect();
+        for text in profile_patterns {
+            let pattern =
+                Pattern::new(text.trim()).map_err(|e| ConvertError::InvalidProfileValue {
+                    field: "placeholder_patterns".to_string(),
+                    value: text.clone(),
+                    expected: format!("a pattern with * and ? ({})", e.msg),
+                })?;
+            patterns.push(pattern);
+        }
+        Ok(Placeholders { patterns })
+    }
+
+    /// A name, e-mail address or street that is a placeholder.
+    pub fn is_placeholder(&self, value: &str) -> bool {
+        let value = value.trim();
+        !value.is_empty() && self.patterns.iter().any(|p| p.matches_with(value, MATCH))
+    }
+
+    /// A phone number (as normalized, 0032...) that is a placeholder.
+    pub fn is_fake_number(&self, number: &str) -> bool {
+        let national = number.strip_prefix("0032").unwrap_or(number);
+        let digits: Vec<u32> = national.chars().filter_map(|c| c.to_digit(10)).collect();
+        let same = digits.windows(2).all(|d| d[0] == d[1]);
+        let up = digits.windows(2).all(|d| d[1] == d[0] + 1);
+        let down = digits.windows(2).all(|d| d[0] == d[1] + 1);
+        (digits.len() >= 6 && (same || up || down)) || self.is_placeholder(number)
+    }
+}
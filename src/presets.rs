@@ -0,0 +1,213 @@
+// src/presets.rs
+// Population register export layouts
+// ----------------------------------
+//
+// Flemish gemeenten export their BIN lists from a handful of population
+// register packages, each with its own header names, address columns and
+// date format. A preset holds one such layout, so a neighbouring gemeente
+// only has to add its fixed values:
+//
+//   name = "Bilzen"
+//   postcode = "3740"
+//   gemeente = "Bilzen"
+//   preset = "cevi"
+//
+// A preset carries no fixed values: a profile with a preset must set its own
+// `postcode` and `gemeente`, or the conversion is refused (it never falls back
+// to Alken's). Each preset is also listed as a built-in profile without fixed
+// values, to be filled in with "Fixed values for this session". The profile's
+// own `[columns]`, `date_formats` and `allowed_columns` win over the preset's.
+//
+// The layouts below are taken from exports seen at gemeenten, not from vendor
+// documentation, and differ between versions of the software: check the header
+// row of your own export when a column is reported missing.
+
+use crate::convert::{ADDRESS_COLUMN, GEBOORTEDATUM_COLUMN};
+use crate::profile::Profile;
+
+pub struct Preset {
+    /// Value of `preset` in a profile.
+    pub key: &'static str,
+    /// Name of the built-in profile.
+    pub name: &'static str,
+    /// Expected column name -> header in the export (as in `[columns]`).
+    columns: &'static [(&'static str, &'static str)],
+    /// "Geboortedatum" text formats (chrono syntax).
+    date_formats: &'static [&'static str],
+    /// Other columns of the export, accepted in strict mode.
+    allowed_columns: &'static [&'static str],
+}
+
+pub const PRESETS: [Preset; 3] = [
+    Preset {
+        key: "cevi",
+        name: "Cevi register export",
+        columns: &[
+            ("Naam", "Familienaam"),
+            (ADDRESS_COLUMN, "Adres"),
+            ("Mobiel nummer", "GSM"),
+            ("E-mailadres", "E-mail"),
+        ],
+        date_formats: &["%Y%m%d"],
+        allowed_columns: &["Geslacht", "Nationaliteit", "Burgerlijke staat"],
+    },
+    Preset {
+        key: "remmicom",
+        name: "Remmicom register export",
+        columns: &[
+            ("Voornaam", "Voornamen"),
+            ("Straat", "Straatnaam"),
+            ("Huisnummer", "Huisnr"),
+            ("Mobiel nummer", "Gsm"),
+        ],
+        date_formats: &["%d.%m.%Y"],
+        allowed_columns: &["Bus", "Geslacht"],
+    },
+    Preset {
+        key: "cipal",
+        name: "Cipal Schaubroeck register export",
+        columns: &[
+            ("Naam", "NAAM"),
+            ("Voornaam", "VOORNAAM"),
+            (ADDRESS_COLUMN, "ADRES"),
+            ("Mobiel nummer", "GSM-NUMMER"),
+            ("E-mailadres", "EMAIL"),
+            (GEBOORTEDATUM_COLUMN, "GEBOORTEDATUM"),
+        ],
+        date_formats: &["%Y-%m-%d"],
+        allowed_columns: &["GESLACHT"],
+    },
+];
+
+impl Preset {
+    /// Built-in profile with this layout and no postcode or gemeente.
+    pub fn profile(&self) -> Profile {
+        let mut profile = Profile {
+            name: self.name.to_string(),
+            preset: Some(self.key.to_string()),
+            postcode: String::new(),
+            gemeente: String::new(),
+            ..Profile::default()
+        };
+        self.add_to(&mut profile);
+        profile
+    }
+
+    fn add_to(&self, profile: &mut Profile) {
+        for &(expected, header) in self.columns {
+            profile
+                .columns
+                .entry(expected.to_string())
+                .or_insert_with(|| header.to_string());
+        }
+        profile
+            .date_formats
+            .extend(self.date_formats.iter().map(|f| f.to_string()));
+        for &column in self.allowed_columns {
+            if !profile.allowed_columns.iter().any(|c| c == column) {
+                profile.allowed_columns.push(column.to_string());
+            }
+        }
+    }
+}
+
+/// Clear the `postcode` and `gemeente` a preset profile did not set itself
+/// (`is_set` tells whether the file has the key), so it does not convert with
+/// the Alken defaults it was parsed with.
+pub fn clear_unset(profile: &mut Profile, is_set: impl Fn(&str) -> bool) {
+    if profile.preset.is_none() {
+        return;
+    }
+    if !is_set("postcode") {
+        profile.postcode.clear();
+    }
+    if !is_set("gemeente") {
+        profile.gemeente.clear();
+    }
+}
+
+/// Add the layout of the profile's `preset` (if any); an unknown preset is an error.
+pub fn apply(profile: &mut Profile) -> Result<(), String> {
+    let Some(key) = profile.preset.as_deref() else {
+        return Ok(());
+    };
+    let preset = PRESETS
+        .iter()
+        .find(|p| p.key.eq_ignore_ascii_case(key))
+        .ok_or_else(|| {
+            let keys: Vec<&str> = PRESETS.iter().map(|p| p.key).collect();
+            format!("unknown preset \"{}\" (known: {})", key, keys.join(", "))
+        })?;
+    preset.add_to(profile);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_toml(text: &str) -> Profile {
+        let table: toml::Table = toml::from_str(text).unwrap();
+        let mut profile: Profile = toml::from_str(text).unwrap();
+        clear_unset(&mut profile, |key| table.contains_key(key));
+        apply(&mut profile).unwrap();
+        profile
+    }
+
+    #[test]
+    fn built_in_preset_profiles_have_no_fixed_values() {
+        for preset in &PRESETS {
+            let profile = preset.profile();
+            assert_eq!(profile.postcode, "");
+            assert_eq!(profile.gemeente, "");
+            assert_eq!(profile.preset.as_deref(), Some(preset.key));
+        }
+    }
+
+    #[test]
+    fn a_preset_file_without_fixed_values_does_not_get_alken() {
+        let profile = from_toml("name = \"Bilzen\"\npreset = \"cevi\"\n");
+        assert_eq!(profile.postcode, "");
+        assert_eq!(profile.gemeente, "");
+
+        let profile = from_toml(
+            "name = \"Bilzen\"\npostcode = \"3740\"\ngemeente = \"Bilzen\"\npreset = \"cevi\"\n",
+        );
+        assert_eq!(profile.postcode, "3740");
+        assert_eq!(profile.gemeente, "Bilzen");
+
+        // Without a preset the defaults stay as they were
+        let profile = from_toml("name = \"Alken 2\"\n");
+        assert_eq!(profile.postcode, Profile::default().postcode);
+    }
+
+    #[test]
+    fn cipal_layout() {
+        let profile = from_toml("name = \"Bilzen\"\npreset = \"CIPAL\"\n");
+        assert_eq!(profile.columns["Naam"], "NAAM");
+        assert_eq!(profile.columns[ADDRESS_COLUMN], "ADRES");
+        assert_eq!(profile.columns[GEBOORTEDATUM_COLUMN], "GEBOORTEDATUM");
+        assert_eq!(profile.date_formats, ["%Y-%m-%d"]);
+        assert_eq!(profile.allowed_columns, ["GESLACHT"]);
+    }
+
+    #[test]
+    fn own_columns_win_over_the_preset() {
+        let profile = from_toml(
+            "name = \"Bilzen\"\npreset = \"remmicom\"\nallowed_columns = [\"Bus\"]\n\n[columns]\n\"Mobiel nummer\" = \"GSM 1\"\n",
+        );
+        assert_eq!(profile.columns["Mobiel nummer"], "GSM 1");
+        assert_eq!(profile.columns["Straat"], "Straatnaam");
+        assert_eq!(profile.allowed_columns, ["Bus", "Geslacht"]);
+    }
+
+    #[test]
+    fn unknown_preset_is_an_error() {
+        let mut profile = Profile {
+            preset: Some("acme".to_string()),
+            ..Profile::default()
+        };
+        let error = apply(&mut profile).unwrap_err();
+        assert!(error.contains("cevi, remmicom, cipal"), "{}", error);
+    }
+}
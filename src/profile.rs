@@ -0,0 +1,584 @@
+// src/profile.rs
+// Municipality profiles
+// ---------------------
+//
+// A profile holds the fixed output values for one gemeente (Postcode, Gemeente,
+// Taal, Land, ...). The built-in "Alken" profile is always available; extra
+// profiles are TOML files in the profiles folder (see paths.rs), e.g.
+//
+//   name = "Bilzen"
+//   postcode = "3740"
+//   gemeente = "Bilzen"
+//   fax_columns = ["Fax"]
+//   zone_libre_columns = ["Wijk", "Dossier nr", "Zorgbehoevend"]
+//   row_hash = "contact"      # key in Zone libre 3: "off" (default), "row" or "contact"
+//   row_hash_salt = "..."     # secret mixed into the key, so it cannot be recomputed from the register
+//   civilite = "M/Mme"        # Civilité when no salutation column is mapped (default empty)
+//   civilite_column = "Aanspreking"
+//   type_contact_column = "Categorie"   # Type Contact from this column, see [type_contacts]
+//   export_column = "Exporteren?"       # rows with N (nee, no, 0) here are left out and counted
+//   group_column = "Wijkcode" # BE-Alert group of the contact from this column, see [groups]
+//   group = "Alken"           # group when group_column is not set or empty (default none)
+//   group_zone = 2            # Zone libre 1, 2 or 3 the group is written to (default 1)
+//   sort = "address"          # "input" (default), "address" or "name"
+//   reproducible = true       # byte-identical CSV for the same contacts (overrides sort)
+//   strict = "block"          # "off" (default), "warn" or "block"
+//   allowed_columns = ["Opmerking"]
+//   rijksregister = "mask"    # "refuse" (default) or "mask"
+//   unknown_phone = "blank"   # "keep" (default), "blank" or "reject"
+//   missing_street = "placeholder"   # house number without Straat: "keep" (default), "blank",
+//   street_placeholder = "Onbekend"  # "reject" or "placeholder" (this street name)
+//   expand_abbreviations = true      # write out "Kon.", "St.", "stwg" (default: as written)
+//   placeholder_values = "reject"    # "0000000000", "test@test.be", "XXX": "warn" (default),
+//   placeholder_patterns = ["zzz*"]  # "blank" or "reject"; patterns added to the built-in ones
+//   email_typos = "fix"       # "gmial.com", "telnet.be": "warn" (default, suggest the domain) or
+//   email_domains = ["alken.be"]      # "fix" (correct it, listed in the report); domains known too
+//   header = "none"           # "standard" (default), "none" or "described"
+//   line_ending = "crlf"      # "crlf" or "lf" (default: crlf)
+//   phone_channels = ["mobile", "landline"]   # Tel/Ref. source, first with a number wins
+//   extra_phone_columns = ["GSM 2", "Werk"]   # more numbers for Telefoon 2..7, with the channels
+//   extra_phone_order = "input"   # not in Tel/Ref.; which fit first: "mobile_first" (default),
+//                                 # "landline_first" or "input" (column order); the rest is reported
+//   sms_preference = "voice"  # what J in "SMS voorkeur" means: "sms" (default, number to the
+//                             # SMS field) or "voice" (column marks calls; N means SMS)
+//   allow_email_only = false  # leave out contacts without phone number (default true)
+//   min_age = 18              # leave out contacts younger (needs a "Geboortedatum" column)
+//   age_bracket_zone = 2      # write "65+" / "80+" from Geboortedatum to this Zone libre (default off);
+//   age_brackets = [65, 75, 85]   # ages the brackets start at (default [65, 80]), no birth date exported
+//   max_rejected_percent = 5  # refuse the export when more rows fail (default: never)
+//   expected_tolerance_percent = 2   # refuse when the contacts differ more from the count the
+//                                    # operator expects (default 5)
+//   quota = 12000             # BE-Alert contact quota of the gemeente (warns when exceeded)
+//   delete_file = true        # also write <csv>_delete.csv: numbers gone since the last run
+//   input_columns = ["Voornaam", "Naam", "", "Mobiel nummer"]   # list without header row
+//   skip_leading_rows = 2     # fixed title lines above the header row (default 0)
+//   skip_trailing_rows = 1    # fixed footer lines below the list, e.g. "Totaal: 812" (default 0)
+//   preset = "cevi"           # export layout of the register software (see presets.rs)
+//   date_formats = ["%Y%m%d"] # extra Geboortedatum text formats (chrono syntax)
+//   script = "alken.rhai"     # per-row hook, relative to this folder (see script.rs)
+//
+//   [columns]                 # input headers that differ from the expected names
+//   "Mobiel nummer" = "GSM"
+//   "E-mailadres" = "Email"
+//
+//   [duplicate_columns]       # header name found more than once -> which of those columns
+//   Naam = 1                  # to read (1 = leftmost); the GUI asks when it is not set
+//
+//   [type_contacts]           # value in type_contact_column -> Type Contact (case-insensitive;
+//   inwoner = "P"             # empty or unknown values get type_contact, unknown ones are reported)
+//   bedrijf = "B"
+//   school = "S"
+//
+//   [groups]                  # value in group_column -> group name (case-insensitive;
+//   W01 = "Centrum"           # unknown values are written as they are, and reported)
+//   W02 = "Kolmont"
+//
+//   [street_abbreviations]    # added to the built-in ones (see abbreviations.rs);
+//   "pr." = "Prins"           # "-x" is a word ending, "" switches a built-in one off
+//   "dr." = ""
+//
+//   [gemeente_aliases]        # local spellings -> official name (see gemeente.rs)
+//   "Alken-Centrum" = "Alken"
+//
+//   [template]                # own BE-Alert import template, made by "CSV template"
+//   delimiter = ","           # (see template.rs)
+//   columns = [{ header = "Tél/Réf.", field = "Tel/Ref." }, { header = "Nom", field = "Naam" }]
+//
+// Missing keys fall back to the Alken defaults.
+
+use crate::gemeente;
+use crate::paths;
+use crate::presets;
+use crate::template::OutputTemplate;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// What to do with header columns the conversion does not use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrictMode {
+    #[default]
+    Off,
+    /// Convert, but list the unexpected columns in the status.
+    Warn,
+    /// Refuse to convert.
+    Block,
+}
+
+/// What to do when a cell looks like a rijksregisternummer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RijksregisterPolicy {
+    /// Refuse to convert the file.
+    #[default]
+    Refuse,
+    /// Convert, replacing the digits with '*' in the output.
+    Mask,
+}
+
+/// What to do with a phone number that matches no known Belgian pattern.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownPhonePolicy {
+    /// Write the digits as-is (BE-Alert may reject the row on import).
+    #[default]
+    Keep,
+    /// Leave Tel/Ref. empty.
+    Blank,
+    /// Leave the row out of the CSV (listed in the report).
+    Reject,
+}
+
+/// What to do with a placeholder or test value (see placeholder.rs).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderPolicy {
+    /// Export the value, with a cell warning.
+    #[default]
+    Warn,
+    /// Leave the field empty, with a cell warning (Straat is only warned about).
+    Blank,
+    /// Leave the row out of the CSV (listed in the report).
+    Reject,
+}
+
+/// What to do with an e-mail address whose domain looks like a typo of a
+/// common one (see email_domains.rs).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTypoPolicy {
+    /// Export the address as written, with the suggested domain in a cell warning.
+    #[default]
+    Warn,
+    /// Write the suggested domain, listed in the cell warnings for review.
+    Fix,
+}
+
+/// What to do with a row that has a house number but no street (BE-Alert
+/// cannot geocode an address like "12").
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingStreetPolicy {
+    /// Write the house number alone.
+    #[default]
+    Keep,
+    /// Leave "Adres incl huisnummer" empty.
+    Blank,
+    /// Leave the row out of the CSV (listed in the report).
+    Reject,
+    /// Write the profile's `street_placeholder` before the house number.
+    Placeholder,
+}
+
+/// Reconciliation key written to Zone libre 3.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowHash {
+    /// Zone libre 3 as mapped by `zone_libre_columns`.
+    #[default]
+    Off,
+    /// Hash of every input cell of the row: changes with any edit.
+    Row,
+    /// Hash of Naam, Voornaam, Straat and Huisnummer: stays the same when
+    /// only the phone number or e-mail address changes.
+    Contact,
+}
+
+/// Order of the data rows in the output CSV.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Same order as the XLSX.
+    #[default]
+    Input,
+    /// Straat, then numeric house number, then the full Huisnummer ("12" < "12A" < "13").
+    Address,
+    /// Naam, then Voornaam.
+    Name,
+}
+
+/// Line terminator of the output CSV. Some BE-Alert importers only accept CRLF.
+/// CRLF by default, on every platform: the same profile makes the same file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    #[default]
+    Crlf,
+    Lf,
+}
+
+/// Input column a Tel/Ref. number can come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhoneChannel {
+    /// "Mobiel nummer"
+    Mobile,
+    /// "Vast nummer"
+    Landline,
+}
+
+/// Which numbers get Telefoon 2..7 when a contact has more than six extra ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneOrder {
+    /// Mobile numbers (0032 4..) before landlines, otherwise in column order.
+    #[default]
+    MobileFirst,
+    LandlineFirst,
+    /// Channels, then `extra_phone_columns`, as listed in the profile.
+    Input,
+}
+
+/// What a yes ("J", "ja", "x", ...) in the "SMS voorkeur" column asks for.
+/// Rows preferring SMS get their mobile number in the SMS field instead of
+/// Tel/Ref.; an empty cell keeps the number in Tel/Ref.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmsPreference {
+    /// Yes: SMS, no: voice call.
+    #[default]
+    Sms,
+    /// Yes: voice call, no: SMS (registers asking "Belvoorkeur").
+    Voice,
+}
+
+/// Header line(s) at the top of the output CSV.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderRows {
+    /// The BE-Alert column names.
+    #[default]
+    Standard,
+    /// No header line, for tools that concatenate several CSVs.
+    None,
+    /// Column names plus a second line describing each column.
+    Described,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub postcode: String,
+    pub gemeente: String,
+    pub taal: String,
+    pub land: String,
+    pub rode_lijst: String,
+    pub type_contact: String,
+    /// Input columns written (phone-normalized) to FAX, FAX2 and FAX3, in that order.
+    pub fax_columns: Vec<String>,
+    /// Input columns copied as-is to Zone libre 1, 2 and 3, in that order.
+    pub zone_libre_columns: Vec<String>,
+    /// Short hash of the row or contact in Zone libre 3, to refer to a row in
+    /// later comparisons and support tickets without more personal data.
+    pub row_hash: RowHash,
+    pub row_hash_salt: String,
+    /// Civilité written when `civilite_column` is not set or empty on a row.
+    pub civilite: String,
+    /// Input column with the salutation, copied to Civilité.
+    pub civilite_column: Option<String>,
+    /// Input column with a category mapped through `type_contacts`.
+    pub type_contact_column: Option<String>,
+    /// Category (case-insensitive) -> Type Contact; other values get `type_contact`.
+    pub type_contacts: BTreeMap<String, String>,
+    /// Input column marking rows to export (J/N): rows marked N are skipped,
+    /// so the list can be curated in Excel without deleting rows.
+    pub export_column: Option<String>,
+    /// Input column with the contact's BE-Alert group (a wijk code, ...),
+    /// mapped through `groups`, so contacts can be filtered into groups
+    /// after the import.
+    pub group_column: Option<String>,
+    /// Value in group_column (case-insensitive) -> group name.
+    pub groups: BTreeMap<String, String>,
+    /// Group of contacts without one from group_column.
+    pub group: String,
+    /// Zone libre (1, 2 or 3) the group is written to.
+    pub group_zone: usize,
+    pub sort: SortOrder,
+    /// Reproducible output: fields canonicalized and the rows sorted on
+    /// their content, so exports of the same contacts compare equal byte for
+    /// byte whatever the order or spacing in the source.
+    pub reproducible: bool,
+    /// Strict mode guards against exporting the wrong register (e.g. one with
+    /// a "Rijksregisternummer" column): every column that is not required,
+    /// mapped or listed in `allowed_columns` counts as unexpected.
+    pub strict: StrictMode,
+    pub allowed_columns: Vec<String>,
+    pub rijksregister: RijksregisterPolicy,
+    pub unknown_phone: UnknownPhonePolicy,
+    pub missing_street: MissingStreetPolicy,
+    /// Street written for `missing_street = "placeholder"`.
+    pub street_placeholder: String,
+    /// Write out abbreviations in street names (see abbreviations.rs).
+    pub expand_abbreviations: bool,
+    pub placeholder_values: PlaceholderPolicy,
+    /// Extra placeholder patterns, `*` and `?` as in file names.
+    pub placeholder_patterns: Vec<String>,
+    pub email_typos: EmailTypoPolicy,
+    /// Mail domains known to be right, besides the built-in common ones.
+    pub email_domains: Vec<String>,
+    pub header: HeaderRows,
+    pub line_ending: LineEnding,
+    /// Channels in order of priority for Tel/Ref.
+    pub phone_channels: Vec<PhoneChannel>,
+    /// Input columns with more phone numbers, written to Telefoon 2..7
+    /// together with the channels that did not fill Tel/Ref.
+    pub extra_phone_columns: Vec<String>,
+    pub extra_phone_order: PhoneOrder,
+    pub sms_preference: SmsPreference,
+    /// Export contacts with no phone number (reachable by e-mail only).
+    pub allow_email_only: bool,
+    /// Contacts younger than this (by the input "Geboortedatum") are left out.
+    pub min_age: Option<u32>,
+    /// Zone libre (1, 2 or 3) that gets the contact's age bracket ("80+"),
+    /// for care-recipient lists: responders see who to help first without
+    /// the birth date leaving the register.
+    pub age_bracket_zone: Option<usize>,
+    /// Ages the brackets start at.
+    pub age_brackets: Vec<u32>,
+    /// Refuse the whole export when more than this percentage of the rows is
+    /// rejected; below it the good rows are exported. None: never refuse.
+    pub max_rejected_percent: Option<u32>,
+    /// How far (in percent) the number of contacts converted may be from
+    /// the number the operator entered from the register report.
+    pub expected_tolerance_percent: u32,
+    /// Number of contacts the gemeente's BE-Alert subscription accepts; an
+    /// upload over it is partially dropped.
+    pub quota: Option<usize>,
+    /// Write the Tel/Ref. numbers of the last run of the same input that are
+    /// no longer exported to a second CSV, for BE-Alert's removal upload.
+    pub delete_file: bool,
+    /// Column names in order for lists without a header row ("" for columns
+    /// not used); the first row is then data. Empty: the first row is the header.
+    pub input_columns: Vec<String>,
+    /// Rows above the header row left out (a title, the export date), and
+    /// rows at the end of the sheet (a total line), before the header is read.
+    pub skip_leading_rows: usize,
+    pub skip_trailing_rows: usize,
+    /// Export layout of a population register package (presets.rs); its
+    /// column names, date formats and allowed columns add to the profile's own.
+    pub preset: Option<String>,
+    /// Text formats of "Geboortedatum" tried before the usual ones.
+    pub date_formats: Vec<String>,
+    /// Rhai script with a before_write(record) hook.
+    pub script: Option<String>,
+    /// Expected column name -> header used in this source's XLSX.
+    pub columns: BTreeMap<String, String>,
+    /// Header name that appears more than once -> which of those columns
+    /// (1-based, left to right) is read.
+    pub duplicate_columns: BTreeMap<String, usize>,
+    /// Gemeente spelling in the register -> official name.
+    pub gemeente_aliases: BTreeMap<String, String>,
+    /// Street name abbreviation -> written out, over the built-in table.
+    pub street_abbreviations: BTreeMap<String, String>,
+    /// Column order, headers and separator of the output, when the
+    /// gemeente's BE-Alert import differs from the standard 33 columns.
+    pub template: Option<OutputTemplate>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            name: "Alken".to_string(),
+            postcode: "3570".to_string(),
+            gemeente: "Alken".to_string(),
+            taal: "NL".to_string(),
+            land: "BE".to_string(),
+            rode_lijst: "0".to_string(),
+            type_contact: "P".to_string(),
+            fax_columns: Vec::new(),
+            zone_libre_columns: Vec::new(),
+            row_hash: RowHash::Off,
+            row_hash_salt: String::new(),
+            civilite: String::new(),
+            civilite_column: None,
+            type_contact_column: None,
+            type_contacts: BTreeMap::new(),
+            export_column: None,
+            group_column: None,
+            groups: BTreeMap::new(),
+            group: String::new(),
+            group_zone: 1,
+            sort: SortOrder::Input,
+            reproducible: false,
+            strict: StrictMode::Off,
+            allowed_columns: Vec::new(),
+            rijksregister: RijksregisterPolicy::Refuse,
+            unknown_phone: UnknownPhonePolicy::Keep,
+            missing_street: MissingStreetPolicy::Keep,
+            street_placeholder: String::new(),
+            expand_abbreviations: false,
+            placeholder_values: PlaceholderPolicy::Warn,
+            placeholder_patterns: Vec::new(),
+            email_typos: EmailTypoPolicy::Warn,
+            email_domains: Vec::new(),
+            header: HeaderRows::Standard,
+            line_ending: LineEnding::default(),
+            phone_channels: vec![PhoneChannel::Mobile],
+            extra_phone_columns: Vec::new(),
+            extra_phone_order: PhoneOrder::MobileFirst,
+            sms_preference: SmsPreference::Sms,
+            allow_email_only: true,
+            min_age: None,
+            age_bracket_zone: None,
+            age_brackets: vec![65, 80],
+            max_rejected_percent: None,
+            expected_tolerance_percent: 5,
+            quota: None,
+            delete_file: false,
+            input_columns: Vec::new(),
+            skip_leading_rows: 0,
+            skip_trailing_rows: 0,
+            preset: None,
+            date_formats: Vec::new(),
+            script: None,
+            columns: BTreeMap::new(),
+            duplicate_columns: BTreeMap::new(),
+            gemeente_aliases: BTreeMap::new(),
+            street_abbreviations: BTreeMap::new(),
+            template: None,
+        }
+    }
+}
+
+impl Profile {
+    /// Does a row with this (input) postcode / gemeente belong to the profile?
+    /// Postcodes are compared on digits only ("BE-3570" == "3570"),
+    /// gemeente names in any language or spelling ("ALKEN (Limburg)" == "Alken").
+    pub fn matches(&self, postcode: &str, gemeente: &str) -> bool {
+        let digits = |s: &str| s.chars().filter(|c| c.is_ascii_digit()).collect::<String>();
+        let row_postcode = digits(postcode);
+        if !row_postcode.is_empty() {
+            return row_postcode == digits(&self.postcode);
+        }
+        !gemeente.trim().is_empty()
+            && gemeente::same(gemeente, &self.gemeente, &self.gemeente_aliases)
+    }
+
+    /// Gemeente as written to the output: the official spelling.
+    pub fn official_gemeente(&self) -> String {
+        gemeente::normalize(&self.gemeente, &self.taal, &self.gemeente_aliases)
+    }
+
+    /// Input columns the profile maps in addition to REQUIRED_COLUMNS.
+    pub fn mapped_columns(&self) -> impl Iterator<Item = &str> {
+        self.fax_columns
+            .iter()
+            .chain(&self.zone_libre_columns)
+            .chain(&self.civilite_column)
+            .chain(&self.type_contact_column)
+            .chain(&self.export_column)
+            .chain(&self.group_column)
+            .chain(&self.extra_phone_columns)
+            .map(String::as_str)
+    }
+
+    /// Does the profile read "Geboortedatum" (min_age, age brackets)?
+    pub fn uses_birth_date(&self) -> bool {
+        self.min_age.is_some() || self.age_bracket_zone.is_some()
+    }
+
+    /// Hash of every setting (16 hex digits), stamped on the report so an
+    /// export can be reproduced with exactly the same profile. Any change to
+    /// the profile file changes it; the name alone does not identify it.
+    pub fn settings_hash(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        Sha256::digest(&json)[..8]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// File-name friendly version of the profile name.
+    pub fn file_tag(&self) -> String {
+        self.name
+            .trim()
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+            .collect()
+    }
+}
+
+/// Profile by name (case-insensitive), the first one when no name is given.
+pub fn find_profile(profiles: &[Profile], name: Option<&str>) -> Result<Profile, String> {
+    let Some(name) = name else {
+        return Ok(profiles[0].clone());
+    };
+    profiles
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+        .cloned()
+        .ok_or_else(|| {
+            let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+            format!(
+                "unknown profile \"{}\" (available: {})",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Write a new profile file <profiles folder>/<file tag>.toml; an existing
+/// file is never overwritten. Returns the path written.
+pub fn save_new(profile: &Profile) -> Result<PathBuf, String> {
+    let dir = paths::profiles_dir().ok_or("no profiles folder")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.toml", profile.file_tag()));
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    let text = toml::to_string(profile).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Built-in profiles first (Alken, then one per register preset), then every
+/// *.toml in the profiles folder (sorted by file name). A file profile with the
+/// same name replaces a built-in one. Files that cannot be parsed or name an
+/// unknown preset are skipped and reported in the second vector.
+pub fn load_profiles() -> (Vec<Profile>, Vec<String>) {
+    let mut profiles = vec![Profile::default()];
+    profiles.extend(presets::PRESETS.iter().map(presets::Preset::profile));
+    let mut problems = Vec::new();
+
+    let Some(dir) = paths::profiles_dir() else {
+        return (profiles, problems);
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return (profiles, problems);
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml")))
+        .collect();
+    files.sort();
+
+    for path in files {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                let table = toml::from_str::<toml::Table>(&text).map_err(|e| e.to_string())?;
+                let mut profile = toml::from_str::<Profile>(&text).map_err(|e| e.to_string())?;
+                presets::clear_unset(&mut profile, |key| table.contains_key(key));
+                presets::apply(&mut profile)?;
+                Ok(profile)
+            });
+        match parsed {
+            Ok(profile) => match profiles.iter_mut().find(|p| p.name == profile.name) {
+                Some(existing) => *existing = profile,
+                None => profiles.push(profile),
+            },
+            Err(e) => problems.push(format!(
+                "{}: {}",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                e
+            )),
+        }
+    }
+
+    (profiles, problems)
+}
@@ -0,0 +1,95 @@
+// src/python.rs
+// Python module
+// -------------
+//
+// `be_alert_convert` for Python (feature `python`), built with maturin
+// (see pyproject.toml):
+//
+//   import be_alert_convert, io, pandas as pd
+//   result = be_alert_convert.convert("alken.xlsx", profile="Alken")
+//   df = pd.read_csv(io.StringIO(result["csv"]), sep=";", dtype=str)
+//   result["warnings"], result["rejected"], result["cell_warnings"]
+//
+//   be_alert_convert.validate("alken.xlsx")   # {"ok": True, "rows": ..., ...}
+//
+// Profiles are looked up by name in the profiles folder, as in the GUI.
+
+use crate::convert::{
+    convert_xlsx_bytes, prepare_xlsx_export, validate_xlsx_columns, write_export,
+};
+use crate::error::ConvertError;
+use crate::profile::{self, Profile};
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use std::fs;
+
+fn load_profile(name: Option<&str>) -> PyResult<Profile> {
+    let (profiles, _) = profile::load_profiles();
+    profile::find_profile(&profiles, name).map_err(PyValueError::new_err)
+}
+
+/// JSON value to the matching Python dict/list/str/int.
+fn to_python(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    let json = py.import("json")?;
+    Ok(json.call_method1("loads", (value.to_string(),))?.unbind())
+}
+
+/// Convert an XLSX file. Returns the report as a dict, with the CSV text
+/// under "csv". With `output`, the CSV and its HTML report are also written
+/// (as in the GUI) and "output" holds the path.
+#[pyfunction]
+#[pyo3(signature = (path, profile=None, output=None))]
+fn convert(
+    py: Python<'_>,
+    path: &str,
+    profile: Option<&str>,
+    output: Option<&str>,
+) -> PyResult<PyObject> {
+    let profile = load_profile(profile)?;
+    let value_error = |e: ConvertError| PyValueError::new_err(e.to_string());
+    let os_error = |e: std::io::Error| PyOSError::new_err(format!("{}: {}", path, e));
+
+    let Some(output) = output else {
+        let bytes = fs::read(path).map_err(os_error)?;
+        let export = convert_xlsx_bytes(path, &bytes, &profile).map_err(value_error)?;
+        let mut report = export.summary.to_json();
+        report["csv"] = String::from_utf8_lossy(&export.csv).into_owned().into();
+        return to_python(py, &report);
+    };
+
+    let prepared = prepare_xlsx_export(path, &profile).map_err(value_error)?;
+    let summary = write_export(prepared, output).map_err(value_error)?;
+    let mut report = summary.to_json();
+    report["csv"] = fs::read_to_string(output).map_err(os_error)?.into();
+    report["output"] = output.into();
+    report["report"] = summary.report.map(|p| p.display().to_string()).into();
+    to_python(py, &report)
+}
+
+/// Check an XLSX file without writing anything: {"ok": True, plus the
+/// report} when it converts, {"ok": False, "error": "..."} when it does not.
+#[pyfunction]
+#[pyo3(signature = (path, profile=None))]
+fn validate(py: Python<'_>, path: &str, profile: Option<&str>) -> PyResult<PyObject> {
+    let profile = load_profile(profile)?;
+    let checked =
+        validate_xlsx_columns(path, &profile).and_then(|_| prepare_xlsx_export(path, &profile));
+    let report = match checked {
+        Ok(mut prepared) => {
+            prepared.summary.rows = prepared.records.len();
+            prepared.summary.count_streets(&prepared.records);
+            let mut report = prepared.summary.to_json();
+            report["ok"] = true.into();
+            report
+        }
+        Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+    };
+    to_python(py, &report)
+}
+
+#[pymodule]
+fn be_alert_convert(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(convert, m)?)?;
+    m.add_function(wrap_pyfunction!(validate, m)?)?;
+    Ok(())
+}
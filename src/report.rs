@@ -0,0 +1,889 @@
+// src/report.rs
+// Conversion summary and HTML report
+// ----------------------------------
+//
+// Every export writes <input stem>_report.html next to the CSV, so the
+// person uploading to BE-Alert can review what the convertor did:
+// - summary (operator and remark, convertor version and profile with its
+//   settings hash, rows, warnings, change since the last run of the same file)
+// - rejected rows and cell warnings
+// - coverage per street: contacts reachable by mobile, landline or e-mail only,
+//   and those without any channel
+// - phone audit: the number as read, with its column ("Mobiel nummer", "Vast
+//   nummer"), next to the Tel/Ref. written
+
+use crate::convert::split_address;
+use crate::dedup::{self, Record};
+use crate::error::RowErrorKind;
+use crate::history::RunDelta;
+use crate::i18n::{tr, translate, trf, Lang};
+use crate::profile::Profile;
+use crate::rijksregister;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Version of the convertor, stamped on every report.
+pub const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Profile an export was made with: when BE-Alert refuses a file, the
+/// settings hash tells whether today's profile still produces it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileStamp {
+    pub name: String,
+    /// See Profile::settings_hash.
+    pub settings_hash: String,
+}
+
+impl ProfileStamp {
+    pub fn of(profile: &Profile) -> Self {
+        ProfileStamp {
+            name: profile.name.clone(),
+            settings_hash: profile.settings_hash(),
+        }
+    }
+}
+
+/// Original and normalized phone number of one data row.
+pub struct PhoneAudit {
+    /// 1-based Excel row number.
+    pub row: usize,
+    /// 0-based input column of the number, None when the row has none.
+    pub column: Option<usize>,
+    /// Input column of the phone channel Tel/Ref. was read from.
+    pub channel: String,
+    pub name: String,
+    pub original: String,
+    pub normalized: String,
+    /// Matches no known pattern (passed through, e.g. starts with 3 or 9).
+    pub unknown: bool,
+    /// What the unknown-number policy did ("blanked", "row rejected").
+    pub note: String,
+}
+
+/// Who made an export and why, for the audit trail: written to the history
+/// and the report.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunInfo {
+    pub operator: String,
+    pub remark: String,
+    /// Number of residents the register report gives, entered by the
+    /// operator; the export is refused when the contacts converted differ
+    /// too much (see convert::check_expected).
+    pub expected_contacts: Option<usize>,
+    /// Language of the report and status line.
+    pub lang: Lang,
+}
+
+impl RunInfo {
+    /// The logged-in user of the OS as operator and the language of the
+    /// locale variables (see Lang::from_env), without remark.
+    pub fn from_os() -> RunInfo {
+        RunInfo {
+            operator: std::env::var("USERNAME")
+                .or_else(|_| std::env::var("USER"))
+                .unwrap_or_default(),
+            remark: String::new(),
+            expected_contacts: None,
+            lang: Lang::from_env(),
+        }
+    }
+}
+
+/// A data row left out of the output.
+pub struct RejectedRow {
+    /// 1-based Excel row number.
+    pub row: usize,
+    /// 0-based input column of `field`, None when the value is not from the
+    /// sheet (fixed value, profile script).
+    pub column: Option<usize>,
+    pub field: String,
+    pub kind: RowErrorKind,
+    /// The input row as text, for the rejected-rows file.
+    pub cells: Vec<String>,
+}
+
+/// A cell that converted, but not cleanly (date-formatted, #N/A, ...).
+pub struct CellWarning {
+    /// 1-based Excel row number.
+    pub row: usize,
+    /// 0-based input column, None when the value is not from the sheet.
+    pub column: Option<usize>,
+    pub field: String,
+    /// The cell as read.
+    pub value: String,
+    pub message: String,
+    /// What to change in the source, for the review CSV.
+    pub fix: String,
+}
+
+/// A required column that is in the header but (almost) empty: usually an
+/// export made with the wrong selection or layout in the register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmptyColumn {
+    pub column: String,
+    /// Rows with a value in the column.
+    pub filled: usize,
+    pub rows: usize,
+}
+
+impl EmptyColumn {
+    /// "\"Mobiel nummer\" is empty in 998 of 1000 rows"
+    pub fn text(&self) -> String {
+        format!(
+            "\"{}\" is empty in {} of {} rows",
+            self.column,
+            self.rows - self.filled,
+            self.rows
+        )
+    }
+}
+
+/// Text of the "Opmerking" column of a row that was written. Only for the
+/// report and the history: notes never go to BE-Alert.
+pub struct RowNote {
+    /// 1-based Excel row number.
+    pub row: usize,
+    pub column: usize,
+    pub name: String,
+    pub tel_ref: String,
+    pub text: String,
+}
+
+/// Excel name of a 0-based column: 0 -> A, 25 -> Z, 26 -> AA.
+pub fn column_name(index: usize) -> String {
+    let mut name = Vec::new();
+    let mut n = index + 1;
+    while n > 0 {
+        n -= 1;
+        name.push(b'A' + (n % 26) as u8);
+        n /= 26;
+    }
+    name.iter().rev().map(|&b| b as char).collect()
+}
+
+/// Where a problem is in the workbook: the cell ("E127") when the column is
+/// known, else the row ("row 127").
+pub fn cell_ref(row: usize, column: Option<usize>) -> String {
+    match column {
+        Some(column) => format!("{}{}", column_name(column), row),
+        None => format!("row {}", row),
+    }
+}
+
+/// Contacts of one street by the best channel BE-Alert can reach them on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreetCoverage {
+    /// Tel/Ref. is a mobile number (0032 4..): calls and SMS.
+    pub mobile: usize,
+    /// Tel/Ref. is another number: calls only.
+    pub landline: usize,
+    pub email_only: usize,
+    /// Neither Tel/Ref. nor Email.
+    pub unreachable: usize,
+}
+
+impl StreetCoverage {
+    pub fn contacts(&self) -> usize {
+        self.mobile + self.landline + self.email_only + self.unreachable
+    }
+
+    /// Less than half of the contacts can get an SMS.
+    pub fn is_gap(&self) -> bool {
+        self.mobile * 2 < self.contacts()
+    }
+}
+
+/// What a conversion did, for the status line and the report.
+#[derive(Default)]
+pub struct ConvertSummary {
+    /// Operator and remark given for the export.
+    pub run: RunInfo,
+    /// Profiles used, in order (several for a multi-profile export or a merge).
+    pub profiles: Vec<ProfileStamp>,
+    pub rows: usize,
+    /// Rows no selected profile matched (multi-profile export only).
+    pub unmatched: usize,
+    pub warnings: Vec<String>,
+    /// Phone numbers that match no known pattern, whatever the policy did with them.
+    pub unknown_phones: usize,
+    pub rejected: Vec<RejectedRow>,
+    pub cell_warnings: Vec<CellWarning>,
+    /// Used cells holding an Excel date/time instead of text or a number.
+    pub datetime_cells: usize,
+    /// Used cells holding an Excel error (#N/A, #VALUE!, ...); exported empty.
+    pub error_cells: usize,
+    /// Used cells that could not be converted exactly (see
+    /// `convert::is_lossy`), per input column.
+    pub lossy_cells: BTreeMap<String, usize>,
+    pub phone_audit: Vec<PhoneAudit>,
+    /// Notes of the rows written, in row order.
+    pub notes: Vec<RowNote>,
+    /// Contacts left out for being younger than the profile's `min_age`.
+    pub under_age: usize,
+    /// Rows marked N in the profile's `export_column`, left out.
+    pub skipped: usize,
+    /// Rows written with a house number but no street (profile `missing_street`).
+    pub missing_street: usize,
+    /// Contacts whose number went to SMS instead of Tel/Ref. ("SMS voorkeur").
+    pub sms_preferred: usize,
+    /// Addresses with street name abbreviations written out (see abbreviations.rs).
+    pub abbreviations_expanded: usize,
+    /// Placeholder or test values found (see placeholder.rs), whatever the
+    /// policy did with them.
+    pub placeholders: usize,
+    /// Extra phone numbers left out because Telefoon 2..7 were full.
+    pub phone_overflow: usize,
+    /// E-mail addresses with a domain that looks like a typo (see
+    /// email_domains.rs), corrected or not.
+    pub email_typos: usize,
+    /// Required columns that are (almost) empty (see convert::empty_columns).
+    pub empty_columns: Vec<EmptyColumn>,
+    /// Channels per street of the contacts written (see `count_streets`).
+    pub streets: BTreeMap<String, StreetCoverage>,
+    /// Comparison with the last run of the same input file (see history.rs).
+    pub delta: Option<RunDelta>,
+    /// Id of the run stored in the history, once written.
+    pub history_run: Option<i64>,
+    /// Set once the HTML report is written.
+    pub report: Option<PathBuf>,
+    /// `<source stem>_review.csv` next to the report: every problem on one
+    /// line, for correcting the source register in Excel. Written when there
+    /// is at least one problem.
+    pub review_file: Option<PathBuf>,
+    /// `<output stem>_rejected.csv`, written when rows were rejected.
+    pub rejected_file: Option<PathBuf>,
+    /// `<output stem>_delete.csv` with the numbers gone since the last run
+    /// (profiles with `delete_file`), and how many it holds.
+    pub delete_file: Option<(PathBuf, usize)>,
+}
+
+impl ConvertSummary {
+    /// Record a cell warning. Rijksregisternummers in it are masked as in the
+    /// CSV: the warnings end up in the report, the review CSV and the JSON.
+    pub fn warn_cell(&mut self, warning: CellWarning) {
+        self.cell_warnings.push(CellWarning {
+            value: rijksregister::mask(&warning.value),
+            message: rijksregister::mask(&warning.message),
+            fix: rijksregister::mask(&warning.fix),
+            ..warning
+        });
+    }
+
+    /// Add the written records to the per-street coverage. The street is
+    /// "Adres incl huisnummer" without the number.
+    pub fn count_streets(&mut self, records: &[Record]) {
+        // Output fields: Adres incl huisnummer, Email
+        for (_, record) in records {
+            let (street, _) = split_address(&record[4]);
+            let coverage = self.streets.entry(street).or_default();
+            let (tel_ref, email) = (dedup::contact_number(record), &record[9]);
+            if tel_ref.starts_with("00324") {
+                coverage.mobile += 1;
+            } else if !tel_ref.is_empty() {
+                coverage.landline += 1;
+            } else if !email.is_empty() {
+                coverage.email_only += 1;
+            } else {
+                coverage.unreachable += 1;
+            }
+        }
+    }
+
+    pub fn warning_text(&self) -> String {
+        let lang = self.run.lang;
+        let mut text = String::new();
+        if self.unknown_phones > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} unknown phone numbers.",
+                &[&self.unknown_phones],
+            ));
+        }
+        if !self.rejected.is_empty() {
+            text.push_str(&trf(lang, " {} rows rejected.", &[&self.rejected.len()]));
+        }
+        if self.under_age > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} contacts under the minimum age left out.",
+                &[&self.under_age],
+            ));
+        }
+        if self.skipped > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} rows marked not to export.",
+                &[&self.skipped],
+            ));
+        }
+        if self.missing_street > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} addresses without street (see report).",
+                &[&self.missing_street],
+            ));
+        }
+        if self.placeholders > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} placeholder or test values (see report).",
+                &[&self.placeholders],
+            ));
+        }
+        if self.phone_overflow > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} phone numbers did not fit in Telefoon 2..7 (see report).",
+                &[&self.phone_overflow],
+            ));
+        }
+        if self.email_typos > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} e-mail domains look mistyped (see report).",
+                &[&self.email_typos],
+            ));
+        }
+        if self.datetime_cells + self.error_cells > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} date cells, {} error cells (see report).",
+                &[&self.datetime_cells, &self.error_cells],
+            ));
+        }
+        let lossy: usize = self.lossy_cells.values().sum();
+        if lossy > 0 {
+            text.push_str(&trf(
+                lang,
+                " {} cells not converted exactly (see report).",
+                &[&lossy],
+            ));
+        }
+        if !self.empty_columns.is_empty() {
+            let columns: Vec<String> = self
+                .empty_columns
+                .iter()
+                .map(|c| translate(lang, &c.text()))
+                .collect();
+            text.push_str(&trf(lang, " Wrong export? {}.", &[&columns.join("; ")]));
+        }
+        if !self.warnings.is_empty() {
+            let warnings: Vec<String> = self.warnings.iter().map(|w| translate(lang, w)).collect();
+            text.push_str(&trf(lang, " Warning: {}", &[&warnings.join("; ")]));
+        }
+        if let Some(delta) = &self.delta {
+            text.push_str(&format!(" {}.", translate(lang, &delta.text())));
+        }
+        if let Some((file, count)) = &self.delete_file {
+            text.push_str(&trf(
+                lang,
+                " {} numbers to remove in {}.",
+                &[count, &file.display()],
+            ));
+        }
+        text
+    }
+
+    /// The summary as JSON, for callers outside Rust (C API, Python).
+    pub fn to_json(&self) -> serde_json::Value {
+        let rejected: Vec<_> = self
+            .rejected
+            .iter()
+            .map(|r| {
+                json!({"row": r.row, "cell": cell_ref(r.row, r.column), "field": r.field, "problem": r.kind.to_string(), "cells": r.cells})
+            })
+            .collect();
+        let cell_warnings: Vec<_> = self
+            .cell_warnings
+            .iter()
+            .map(|w| {
+                json!({"row": w.row, "cell": cell_ref(w.row, w.column), "field": w.field, "value": w.value, "message": w.message, "fix": w.fix})
+            })
+            .collect();
+        let notes: Vec<_> = self
+            .notes
+            .iter()
+            .map(|n| {
+                json!({"row": n.row, "cell": cell_ref(n.row, Some(n.column)), "name": n.name,
+                       "tel_ref": n.tel_ref, "note": n.text})
+            })
+            .collect();
+        let streets: Vec<_> = self
+            .streets
+            .iter()
+            .map(|(street, c)| {
+                json!({"street": street, "contacts": c.contacts(), "mobile": c.mobile, "landline": c.landline,
+                       "email_only": c.email_only, "unreachable": c.unreachable})
+            })
+            .collect();
+        let profiles: Vec<_> = self
+            .profiles
+            .iter()
+            .map(|p| json!({"name": p.name, "settings_hash": p.settings_hash}))
+            .collect();
+        json!({
+            "version": TOOL_VERSION,
+            "profiles": profiles,
+            "operator": self.run.operator,
+            "remark": self.run.remark,
+            "expected_contacts": self.run.expected_contacts,
+            "rows": self.rows,
+            "unmatched": self.unmatched,
+            "unknown_phones": self.unknown_phones,
+            "datetime_cells": self.datetime_cells,
+            "error_cells": self.error_cells,
+            "lossy_cells": self.lossy_cells,
+            "under_age": self.under_age,
+            "skipped": self.skipped,
+            "missing_street": self.missing_street,
+            "sms_preferred": self.sms_preferred,
+            "abbreviations_expanded": self.abbreviations_expanded,
+            "placeholders": self.placeholders,
+            "phone_overflow": self.phone_overflow,
+            "email_typos": self.email_typos,
+            "warnings": self.warnings,
+            "empty_columns": self.empty_columns.iter().map(|c| json!({
+                "column": c.column,
+                "filled": c.filled,
+                "rows": c.rows,
+            })).collect::<Vec<_>>(),
+            "rejected": rejected,
+            "cell_warnings": cell_warnings,
+            "notes": notes,
+            "streets": streets,
+            "delta": self.delta.as_ref().map(RunDelta::text),
+            "delete_file": self.delete_file.as_ref().map(|(file, _)| file.display().to_string()),
+            "removed": self.delete_file.as_ref().map(|(_, count)| count),
+        })
+    }
+}
+
+pub fn report_path(output_dir: &Path, input_stem: &str) -> PathBuf {
+    output_dir.join(format!("{}_report.html", input_stem))
+}
+
+/// The review CSV next to a report: `<stem>_report.html` -> `<stem>_review.csv`.
+pub fn review_path(report: &Path) -> PathBuf {
+    let name = report.file_name().unwrap_or_default().to_string_lossy();
+    let stem = name.strip_suffix("_report.html").unwrap_or(&name);
+    report.with_file_name(format!("{}_review.csv", stem))
+}
+
+/// One line of the review CSV.
+pub struct ReviewLine {
+    /// 1-based Excel row number; None for problems of the whole file.
+    pub row: Option<usize>,
+    /// Excel cell ("E127"), empty when the problem is not one cell.
+    pub cell: String,
+    pub column: String,
+    pub value: String,
+    pub problem: String,
+    pub fix: String,
+}
+
+/// Every problem of the conversion, in row order: cell warnings, rejected
+/// rows, phone numbers matching no pattern, then the file-level warnings.
+pub fn review_lines(summary: &ConvertSummary) -> Vec<ReviewLine> {
+    let mut lines: Vec<ReviewLine> = summary
+        .cell_warnings
+        .iter()
+        .map(|w| ReviewLine {
+            row: Some(w.row),
+            cell: w
+                .column
+                .map_or_else(String::new, |c| cell_ref(w.row, Some(c))),
+            column: w.field.clone(),
+            value: w.value.clone(),
+            problem: w.message.clone(),
+            fix: w.fix.clone(),
+        })
+        .collect();
+    lines.extend(summary.rejected.iter().map(|r| {
+        let (value, fix) = match &r.kind {
+            RowErrorKind::Empty => (String::new(), "fill in the cell"),
+            RowErrorKind::Invalid { value, .. } => (value.clone(), "correct the value"),
+            RowErrorKind::NationalRegisterNumber => {
+                (String::new(), "remove the number from the source")
+            }
+            RowErrorKind::Script(_) => (String::new(), "see the profile script"),
+        };
+        ReviewLine {
+            row: Some(r.row),
+            cell: r
+                .column
+                .map_or_else(String::new, |c| cell_ref(r.row, Some(c))),
+            column: r.field.clone(),
+            value,
+            problem: format!("row left out: {}", r.kind),
+            fix: fix.to_string(),
+        }
+    }));
+    // Rejected unknown numbers are already listed above
+    lines.extend(
+        summary
+            .phone_audit
+            .iter()
+            .filter(|a| a.unknown && a.note != "row rejected")
+            .map(|a| ReviewLine {
+                row: Some(a.row),
+                cell: a
+                    .column
+                    .map_or_else(String::new, |c| cell_ref(a.row, Some(c))),
+                column: a.channel.clone(),
+                value: a.original.clone(),
+                problem: if a.note.is_empty() {
+                    "not a recognised Belgian phone number, exported as is".to_string()
+                } else {
+                    format!("not a recognised Belgian phone number, {}", a.note)
+                },
+                fix: "enter the number as 04xx xx xx xx or +32 ...".to_string(),
+            }),
+    );
+    lines.sort_by_key(|l| l.row);
+    lines.extend(summary.warnings.iter().map(|w| ReviewLine {
+        row: None,
+        cell: String::new(),
+        column: String::new(),
+        value: String::new(),
+        problem: w.clone(),
+        fix: String::new(),
+    }));
+    lines
+}
+
+/// A file written next to the report, by its name: the report stays right
+/// when the files are moved together (a batch writes them elsewhere first).
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    )
+}
+
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn write_html(
+    path: &Path,
+    input: &str,
+    outputs: &[PathBuf],
+    summary: &ConvertSummary,
+) -> io::Result<()> {
+    fs::write(path, html(input, outputs, summary))
+}
+
+/// Start of a table, with its column titles.
+fn table_header(titles: &[&str]) -> String {
+    let cells: String = titles
+        .iter()
+        .map(|title| format!("<th>{}</th>", title))
+        .collect();
+    format!("<table>\n<tr>{}</tr>\n", cells)
+}
+
+/// The report as an HTML page, in the run's language (see i18n.rs).
+pub fn html(input: &str, outputs: &[PathBuf], summary: &ConvertSummary) -> String {
+    let t = |en| tr(summary.run.lang, en);
+    let title = t("BE-Alert conversion report");
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", title));
+    html.push_str(
+        "<style>body{font-family:sans-serif}table{border-collapse:collapse}\
+         td,th{border:1px solid #ccc;padding:2px 6px}.suspect{background:#fde2e2}</style>\n",
+    );
+    html.push_str(&format!("</head><body>\n<h1>{}</h1>\n", title));
+
+    html.push_str(&format!("<h2>{}</h2>\n<ul>\n", t("Summary")));
+    if !summary.run.operator.is_empty() {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Operator"),
+            escape(&summary.run.operator)
+        ));
+    }
+    if !summary.run.remark.is_empty() {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Remark"),
+            escape(&summary.run.remark)
+        ));
+    }
+    html.push_str(&format!(
+        "<li>{}: {}</li>\n",
+        t("Convertor version"),
+        TOOL_VERSION
+    ));
+    for profile in &summary.profiles {
+        html.push_str(&format!(
+            "<li>{}: {} ({} {})</li>\n",
+            t("Profile"),
+            escape(&profile.name),
+            t("settings"),
+            profile.settings_hash
+        ));
+    }
+    html.push_str(&format!("<li>{}: {}</li>\n", t("Input"), escape(input)));
+    for out in outputs {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Output"),
+            escape(&file_name(out))
+        ));
+    }
+    html.push_str(&format!(
+        "<li>{}: {}</li>\n",
+        t("Rows written"),
+        summary.rows
+    ));
+    if let Some(expected) = summary.run.expected_contacts {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Contacts expected from the register"),
+            expected
+        ));
+    }
+    if summary.unmatched > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Rows matching no profile"),
+            summary.unmatched
+        ));
+    }
+    html.push_str(&format!(
+        "<li>{}: {}</li>\n",
+        t("Phone numbers matching no known pattern"),
+        summary.unknown_phones
+    ));
+    html.push_str(&format!(
+        "<li>{}: {}</li>\n",
+        t("Rows rejected"),
+        summary.rejected.len()
+    ));
+    if let Some(file) = &summary.rejected_file {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Rejected rows, with their input"),
+            escape(&file_name(file))
+        ));
+    }
+    if let Some((file, count)) = &summary.delete_file {
+        html.push_str(&format!(
+            "<li>{}: {} ({})</li>\n",
+            t("Contacts gone since the last run, for removal"),
+            count,
+            escape(&file_name(file))
+        ));
+    }
+    if summary.under_age > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Contacts under the minimum age left out"),
+            summary.under_age
+        ));
+    }
+    if summary.skipped > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Rows marked not to export, left out"),
+            summary.skipped
+        ));
+    }
+    if !summary.lossy_cells.is_empty() {
+        let columns: Vec<String> = summary
+            .lossy_cells
+            .iter()
+            .map(|(column, count)| format!("{} {}", escape(column), count))
+            .collect();
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Cells not converted exactly (numbers over 15 digits, unreadable dates)"),
+            columns.join(", ")
+        ));
+    }
+    if summary.missing_street > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Addresses without street (BE-Alert cannot locate them)"),
+            summary.missing_street
+        ));
+    }
+    if summary.sms_preferred > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Contacts preferring SMS (number in the SMS field)"),
+            summary.sms_preferred
+        ));
+    }
+    if summary.placeholders > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Placeholder or test values (\"0000000000\", \"test@test.be\", ...)"),
+            summary.placeholders
+        ));
+    }
+    if summary.phone_overflow > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Phone numbers that did not fit in Telefoon 2..7"),
+            summary.phone_overflow
+        ));
+    }
+    if summary.email_typos > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("E-mail addresses with a mistyped domain (\"gmial.com\" -> \"gmail.com\")"),
+            summary.email_typos
+        ));
+    }
+    if summary.abbreviations_expanded > 0 {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Addresses with abbreviations written out (\"Kon.\" -> \"Koning\")"),
+            summary.abbreviations_expanded
+        ));
+    }
+    if let Some(delta) = &summary.delta {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            t("Compared with the last run"),
+            escape(&translate(summary.run.lang, &delta.text()))
+        ));
+    }
+    html.push_str("</ul>\n");
+
+    if !summary.rejected.is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n", t("Rejected rows")));
+        html.push_str(&table_header(&[t("Cell"), t("Column"), t("Problem")]));
+        for r in &summary.rejected {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                cell_ref(r.row, r.column),
+                escape(&r.field),
+                escape(&r.kind.to_string())
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !summary.cell_warnings.is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n", t("Cell warnings")));
+        html.push_str(&table_header(&[t("Cell"), t("Column"), t("Problem")]));
+        for w in &summary.cell_warnings {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                cell_ref(w.row, w.column),
+                escape(&w.field),
+                escape(&w.message)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !summary.warnings.is_empty() || !summary.empty_columns.is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n<ul>\n", t("Warnings")));
+        for column in &summary.empty_columns {
+            html.push_str(&format!(
+                "<li class=\"suspect\">{} ({})</li>\n",
+                escape(&translate(summary.run.lang, &column.text())),
+                t("is this the right export?")
+            ));
+        }
+        for w in &summary.warnings {
+            html.push_str(&format!(
+                "<li>{}</li>\n",
+                escape(&translate(summary.run.lang, w))
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !summary.notes.is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n", t("Notes")));
+        html.push_str(&format!(
+            "<p>{}</p>\n",
+            t("From the Opmerking column; not in the CSV.")
+        ));
+        html.push_str(&table_header(&[
+            t("Cell"),
+            t("Name"),
+            "Tel/Ref.",
+            t("Note"),
+        ]));
+        for n in &summary.notes {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                cell_ref(n.row, Some(n.column)),
+                escape(&n.name),
+                escape(&n.tel_ref),
+                escape(&n.text)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !summary.streets.is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n", t("Coverage per street")));
+        html.push_str(&format!(
+            "<p>{}</p>\n",
+            t("Streets where less than half of the contacts can get an SMS are highlighted.")
+        ));
+        html.push_str(&table_header(&[
+            t("Street"),
+            t("Contacts"),
+            t("Mobile"),
+            t("Landline only"),
+            t("E-mail only"),
+            t("Unreachable"),
+        ]));
+        for (street, c) in &summary.streets {
+            html.push_str(&format!(
+                "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                if c.is_gap() { " class=\"suspect\"" } else { "" },
+                if street.is_empty() {
+                    t("(no address)").to_string()
+                } else {
+                    escape(street)
+                },
+                c.contacts(),
+                c.mobile,
+                c.landline,
+                c.email_only,
+                c.unreachable
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str(&format!("<h2>{}</h2>\n", t("Phone audit")));
+    html.push_str(&table_header(&[
+        t("Cell"),
+        t("Name"),
+        t("Column"),
+        t("Number read"),
+        "Tel/Ref.",
+        t("Note"),
+    ]));
+    for a in &summary.phone_audit {
+        html.push_str(&format!(
+            "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            if a.unknown { " class=\"suspect\"" } else { "" },
+            cell_ref(a.row, a.column),
+            escape(&a.name),
+            escape(&a.channel),
+            escape(&a.original),
+            escape(&a.normalized),
+            escape(&a.note)
+        ));
+    }
+    html.push_str("</table>\n</body></html>\n");
+
+    html
+}
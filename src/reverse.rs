@@ -0,0 +1,262 @@
+// src/reverse.rs
+// BE-Alert CSV back to an XLSX contact list
+// -----------------------------------------
+//
+// When the only copy of a contact list left is the BE-Alert CSV, it is turned
+// back into the six-column input layout (Voornaam, Naam, Straat, Huisnummer,
+// Mobiel nummer, E-mailadres) so it can be edited in Excel and converted
+// again:
+//
+// - Naam and Voornaam are read back from the fields MapColumns (pipeline.rs)
+//   writes them to
+// - Tel/Ref. 0032475123456 becomes 0475123456 (SMS when Tel/Ref. is empty:
+//   the contact preferred SMS, see "SMS voorkeur")
+// - "Adres incl huisnummer" is split before the house number
+//
+// The header line (also a `header = "described"` second line) is optional.
+// A CSV written with a profile's `[template]` (see template.rs) is read by its
+// header: the separator is sniffed and each column put back in its place
+// among the 33 fields. A header that is not all BE-Alert columns is refused.
+
+use crate::convert::{
+    check_not_input, split_address, OUTPUT_DESCRIPTIONS, OUTPUT_HEADER, REQUIRED_COLUMNS,
+};
+use crate::dialect;
+use crate::error::{ConvertError, Result};
+use crate::template;
+use rust_xlsxwriter::{Format, Workbook};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Output fields read back (index into OUTPUT_HEADER)
+const TEL_REF: usize = 0;
+const NAAM: usize = 2;
+const VOORNAAM: usize = 3;
+const ADRES: usize = 4;
+const EMAIL: usize = 9;
+const SMS: usize = 21;
+
+/// One contact in the input layout, in REQUIRED_COLUMNS order, from a
+/// record in OUTPUT_HEADER order.
+fn input_row(record: &[&str]) -> [String; 6] {
+    let field = |i: usize| {
+        record
+            .get(i)
+            .copied()
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+    let (straat, huisnummer) = split_address(&field(ADRES));
+    let mut phone = field(TEL_REF);
+    if phone.is_empty() {
+        phone = field(SMS);
+    }
+    [
+        field(VOORNAAM),
+        field(NAAM),
+        straat,
+        huisnummer,
+        local_phone(&phone),
+        field(EMAIL),
+    ]
+}
+
+/// 0032475123456 -> 0475123456; numbers in another format are kept.
+fn local_phone(tel_ref: &str) -> String {
+    match tel_ref.strip_prefix("0032") {
+        Some(rest) if !rest.is_empty() => format!("0{}", rest),
+        _ => tel_ref.to_string(),
+    }
+}
+
+/// Contacts of a BE-Alert CSV, in the input layout.
+pub fn read_contacts(input_csv: &Path) -> Result<Vec<[String; 6]>> {
+    let unsupported = |reason: String| ConvertError::UnsupportedFormat {
+        path: input_csv.to_path_buf(),
+        reason,
+    };
+    let bytes = fs::read(input_csv).map_err(|e| ConvertError::io_at(input_csv, e))?;
+    let text = String::from_utf8(bytes)
+        .map_err(|_| unsupported("not a UTF-8 CSV as written by the convertor".to_string()))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+
+    let delimiter = dialect::sniff(text).delimiter;
+    // A template's layout: each column's place among the 33 fields
+    let layout = match template::infer(text.as_bytes()) {
+        Ok(inferred) if inferred.template.header() == OUTPUT_HEADER => None,
+        Ok(inferred) => Some(inferred.template.fields()),
+        // No header line at all: the standard layout, counted per record below
+        Err(_) if !first_line_is_header(text, delimiter) => None,
+        Err(reason) => return Err(unsupported(reason)),
+    };
+    let width = layout.as_ref().map_or(OUTPUT_HEADER.len(), Vec::len);
+    let descriptions = layout
+        .as_ref()
+        .map(|fields| template::reorder(fields, &OUTPUT_DESCRIPTIONS));
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut contacts = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let fields: Vec<&str> = record.iter().collect();
+        if i == 0 && layout.is_some() {
+            continue;
+        }
+        if fields == OUTPUT_HEADER
+            || fields == OUTPUT_DESCRIPTIONS
+            || descriptions.as_ref().is_some_and(|d| fields == *d)
+        {
+            continue;
+        }
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        if fields.len() != width {
+            return Err(unsupported(format!(
+                "record {} has {} fields, not the {} columns of the header",
+                i + 1,
+                fields.len(),
+                width
+            )));
+        }
+        let Some(layout) = &layout else {
+            contacts.push(input_row(&fields));
+            continue;
+        };
+        // Back in the standard order; fields the template leaves out are empty
+        let mut standard = vec![""; OUTPUT_HEADER.len()];
+        for (field, value) in layout.iter().zip(&fields) {
+            if let Some(i) = field {
+                standard[*i] = value;
+            }
+        }
+        contacts.push(input_row(&standard));
+    }
+    Ok(contacts)
+}
+
+/// Whether the first line names at least one BE-Alert column (in any of the
+/// languages a template may use), rather than holding a contact.
+fn first_line_is_header(text: &str, delimiter: u8) -> bool {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    match reader.records().next() {
+        Some(Ok(record)) => record.iter().any(template::is_header_name),
+        _ => false,
+    }
+}
+
+/// Write the contacts of `input_csv` as an XLSX in the input layout.
+/// Returns the number of contacts.
+pub fn csv_to_xlsx(input_csv: &Path, output_xlsx: &Path) -> Result<usize> {
+    check_not_input(output_xlsx, &[input_csv.to_path_buf()])?;
+    let contacts = read_contacts(input_csv)?;
+    let xlsx_error = |e: rust_xlsxwriter::XlsxError| ConvertError::IoError(io::Error::other(e));
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    let bold = Format::new().set_bold();
+    for (col, name) in REQUIRED_COLUMNS.iter().enumerate() {
+        sheet
+            .write_string_with_format(0, col as u16, *name, &bold)
+            .map_err(xlsx_error)?;
+    }
+    for (row, contact) in contacts.iter().enumerate() {
+        for (col, value) in contact.iter().enumerate() {
+            // As text, so Excel keeps the leading 0 of phone numbers
+            if !value.is_empty() {
+                sheet
+                    .write_string(row as u32 + 1, col as u16, value)
+                    .map_err(xlsx_error)?;
+            }
+        }
+    }
+    sheet.autofit();
+    workbook.save(output_xlsx).map_err(|e| match e {
+        rust_xlsxwriter::XlsxError::IoError(e) => ConvertError::io_at(output_xlsx, e),
+        e => xlsx_error(e),
+    })?;
+    Ok(contacts.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contacts_of(name: &str, csv: &str) -> Result<Vec<[String; 6]>> {
+        let path = std::env::temp_dir().join(format!(
+            "be-alert-reverse-{}-{}.csv",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, csv).unwrap();
+        let contacts = read_contacts(&path);
+        let _ = fs::remove_file(&path);
+        contacts
+    }
+
+    fn jan() -> [String; 6] {
+        [
+            "Jan",
+            "Peeters",
+            "Kerkstraat",
+            "12",
+            "0475123456",
+            "jan@telenet.be",
+        ]
+        .map(String::from)
+    }
+
+    #[test]
+    fn a_reordered_french_template_is_read_by_its_header() {
+        let csv = "Courriel,Prénom,Nom,Adresse,Tél/Réf.\r\n\
+                   jan@telenet.be,Jan,Peeters,Kerkstraat 12,0032475123456\r\n";
+        assert_eq!(contacts_of("fr", csv).unwrap(), vec![jan()]);
+    }
+
+    #[test]
+    fn a_template_header_with_semicolons_is_no_contact() {
+        let csv = "Naam;Voornaam;Tel/Ref.;Adres incl huisnummer;Email\n\
+                   Peeters;Jan;0032475123456;Kerkstraat 12;jan@telenet.be\n";
+        assert_eq!(contacts_of("nl", csv).unwrap(), vec![jan()]);
+    }
+
+    #[test]
+    fn the_standard_layout_with_or_without_header() {
+        let mut record = vec![""; OUTPUT_HEADER.len()];
+        record[TEL_REF] = "0032475123456";
+        record[NAAM] = "Peeters";
+        record[VOORNAAM] = "Jan";
+        record[ADRES] = "Kerkstraat 12";
+        record[EMAIL] = "jan@telenet.be";
+        let line = format!("{}\n", record.join(";"));
+        let header = format!("{}\n", OUTPUT_HEADER.join(";"));
+        assert_eq!(
+            contacts_of("std", &format!("{}{}", header, line)).unwrap(),
+            vec![jan()]
+        );
+        assert_eq!(contacts_of("bare", &line).unwrap(), vec![jan()]);
+    }
+
+    #[test]
+    fn an_unknown_layout_is_refused() {
+        for csv in [
+            "Tel/Ref.;Naam;Wijk\n0032475123456;Peeters;Centrum\n",
+            "a;b;c\n1;2;3\n",
+        ] {
+            match contacts_of("unknown", csv) {
+                Err(ConvertError::UnsupportedFormat { .. }) => {}
+                other => panic!("expected a refused layout, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// src/rijksregister.rs
+// Detection of Belgian national register numbers (rijksregisternummers)
+// ---------------------------------------------------------------------
+//
+// A rijksregisternummer is 11 digits: YYMMDD SSS CC, usually typed as
+// "85.07.30-033.28". CC = 97 - (YYMMDDSSS mod 97), with a leading "2" added
+// to the nine digits for people born from 2000 on. Bis numbers add 20 or 40
+// to the month. Date and checksum are both checked, so ordinary phone numbers
+// and postcodes do not trigger.
+//
+// This data must never end up in a BE-Alert upload (GDPR).
+
+use std::ops::Range;
+
+const SEPARATORS: [char; 3] = ['.', '-', '/'];
+
+/// Byte ranges of candidate numbers: runs of digits and separators, with and
+/// without spaces allowed inside ("85 07 30 033 28").
+fn candidates(text: &str) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    for allow_space in [false, true] {
+        let is_part = |c: char| c.is_ascii_digit() || SEPARATORS.contains(&c) || (allow_space && c == ' ');
+        let mut start: Option<usize> = None;
+        for (i, c) in text.char_indices().chain(std::iter::once((text.len(), '\0'))) {
+            match (start, is_part(c) && i < text.len()) {
+                (None, true) if c.is_ascii_digit() => start = Some(i),
+                (Some(s), false) => {
+                    let run = text[s..i].trim_end_matches(|c: char| !c.is_ascii_digit());
+                    out.push(s..s + run.len());
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+fn is_valid(digits: &str) -> bool {
+    if digits.len() != 11 {
+        return false;
+    }
+    let num = |r: Range<usize>| digits[r].parse::<u64>().unwrap_or(u64::MAX);
+    let (month, day) = (num(2..4), num(4..6));
+    if month > 52 || month % 20 > 12 || day > 31 {
+        return false;
+    }
+    let base = num(0..9);
+    let check = num(9..11);
+    97 - base % 97 == check || 97 - (2_000_000_000 + base) % 97 == check
+}
+
+fn found(text: &str) -> Vec<Range<usize>> {
+    candidates(text)
+        .into_iter()
+        .filter(|r| {
+            let digits: String = text[r.clone()].chars().filter(|c| c.is_ascii_digit()).collect();
+            is_valid(&digits)
+        })
+        .collect()
+}
+
+pub fn contains(text: &str) -> bool {
+    !found(text).is_empty()
+}
+
+/// Replace the digits of every rijksregisternummer in `text` with '*'.
+pub fn mask(text: &str) -> String {
+    let ranges = found(text);
+    text.char_indices()
+        .map(|(i, c)| {
+            if c.is_ascii_digit() && ranges.iter().any(|r| r.contains(&i)) {
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn born_before_and_after_2000() {
+        assert!(contains("85.07.30-033.28"));
+        // From 2000 on the checksum is taken over 2 followed by the nine digits
+        assert!(contains("01.01.01-001.26"));
+        // The same digits with the old checksum: someone born in 1901
+        assert!(contains("01.01.01-001.94"));
+        assert!(!contains("01.01.01-001.95"));
+    }
+
+    #[test]
+    fn bis_numbers_add_20_or_40_to_the_month() {
+        assert!(contains("85.27.30-033.71"));
+        assert!(contains("85.47.30-033.17"));
+    }
+
+    #[test]
+    fn a_wrong_checksum_is_no_number() {
+        assert!(!contains("85.07.30-033.29"));
+        assert_eq!(mask("85.07.30-033.29"), "85.07.30-033.29");
+    }
+
+    #[test]
+    fn typed_with_spaces() {
+        assert!(contains("rijksregister 85 07 30 033 28"));
+        assert_eq!(mask("rr: 85 07 30 033 28."), "rr: ** ** ** *** **.");
+        assert_eq!(mask("85073003328"), "***********");
+    }
+
+    #[test]
+    fn phone_numbers_and_postcodes_do_not_match() {
+        let texts = ["0475 12 34 56", "+32 475 12 34 56", "0032475123456", "3570", "3570 Alken"];
+        for text in texts {
+            assert!(!contains(text), "{}", text);
+            assert_eq!(mask(text), text);
+        }
+    }
+}
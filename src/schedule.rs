@@ -0,0 +1,579 @@
+// src/schedule.rs
+// Scheduled export
+// ----------------
+//
+// While the GUI or `be-alert-convert --schedule` runs, the newest XLSX in a
+// source folder is converted at the times of a cron expression, configured
+// in settings.toml:
+//
+//   [schedule]
+//   cron = "0 7 * * 1-5"                 # minute hour day month weekday
+//   source_folder = 'S:\BIN\exports'
+//   output_folder = 'S:\BE-Alert\uploads'  # optional; default: the source folder
+//   profile = "Alken"                     # optional; default: the first profile
+//   quarantine = true                     # optional; see below
+//   jobs = 4                              # optional; files converted in parallel (default 1)
+//   timeout_secs = 600                    # optional; a file taking longer counts as failed
+//   retries = 3                           # optional; attempts after a transient I/O error (default 3)
+//   retry_delay_secs = 30                 # optional; first wait, doubled each retry (default 30)
+//
+// Times are local. When the clocks go forward, a time in the skipped hour
+// runs at the end of it; when they go back, a time in the repeated hour runs
+// once, the first time.
+//
+// A file that has not changed since the last scheduled run is not converted
+// again. Every run is appended to <app dir>/schedule.log, with the number of
+// retries a file needed when the network share dropped out.
+//
+// With `quarantine = true` every Excel file in the source folder is converted
+// at each run and then moved out of the way: to processed/ when it converted,
+// to failed/ with a <name>.error.txt next to it when it did not. Operators see
+// at a glance which nightly files need attention.
+
+use crate::batch::{self, BatchInput, BatchLimits, FileResult};
+use crate::convert::EXCEL_EXTENSIONS;
+use crate::paths;
+use crate::profile::Profile;
+use crate::report::RunInfo;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub cron: String,
+    pub source_folder: PathBuf,
+    pub output_folder: Option<PathBuf>,
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub quarantine: bool,
+    /// Files converted at the same time in quarantine mode.
+    pub jobs: Option<usize>,
+    /// Seconds one file may take before it counts as failed.
+    pub timeout_secs: Option<u64>,
+    /// Attempts after a transient I/O error.
+    pub retries: Option<usize>,
+    /// Seconds before the first retry; doubled for every next one.
+    pub retry_delay_secs: Option<u64>,
+}
+
+/// Nobody watches a scheduled run: network hiccups are retried by default.
+const DEFAULT_RETRIES: usize = 3;
+const DEFAULT_RETRY_DELAY_SECS: u64 = 30;
+
+impl Schedule {
+    pub fn limits(&self) -> BatchLimits {
+        BatchLimits {
+            jobs: self.jobs.unwrap_or(1),
+            timeout: self.timeout_secs.map(std::time::Duration::from_secs),
+            retries: self.retries.unwrap_or(DEFAULT_RETRIES),
+            retry_delay: std::time::Duration::from_secs(
+                self.retry_delay_secs.unwrap_or(DEFAULT_RETRY_DELAY_SECS),
+            ),
+        }
+    }
+}
+
+/// A five-field cron expression. Each field is `*`, a number, a range
+/// `a-b`, a step `*/n` or `a-b/n`, or a comma separated list of those.
+/// Weekdays are 0-7 with both 0 and 7 meaning Sunday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    /// Day and weekday both restricted: either may match (as in cron).
+    day_or_weekday: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronError(String);
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid cron expression: {}", self.0)
+    }
+}
+
+impl std::error::Error for CronError {}
+
+/// Values allowed by one field, as a table indexed by value.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>, CronError> {
+    let bad = || CronError(format!("\"{}\" (allowed {}-{})", field, min, max));
+    let number = |s: &str| -> Result<u32, CronError> {
+        s.parse::<u32>()
+            .ok()
+            .filter(|n| (min..=max).contains(n))
+            .ok_or_else(bad)
+    };
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| bad())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(bad());
+        }
+        let (from, to) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (number(a)?, number(b)?),
+                None => {
+                    let n = number(range)?;
+                    // "5/10" means from 5 to the end in steps of 10
+                    (n, if part.contains('/') { max } else { n })
+                }
+            },
+        };
+        if from > to {
+            return Err(bad());
+        }
+        for v in (from..=to).step_by(step as usize) {
+            allowed[v as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+impl Cron {
+    pub fn parse(expression: &str) -> Result<Cron, CronError> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(CronError(format!(
+                "\"{}\" needs 5 fields (minute hour day month weekday)",
+                expression
+            )));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+        Ok(Cron {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            day_or_weekday: day != "*" && weekday != "*",
+        })
+    }
+
+    pub fn matches(&self, t: &NaiveDateTime) -> bool {
+        let day = self.days[t.day() as usize];
+        let weekday = self.weekdays[t.weekday().num_days_from_sunday() as usize];
+        let date = if self.day_or_weekday {
+            day || weekday
+        } else {
+            day && weekday
+        };
+        self.minutes[t.minute() as usize]
+            && self.hours[t.hour() as usize]
+            && self.months[t.month() as usize]
+            && date
+    }
+
+    /// First matching minute after `after`, searching at most a year ahead
+    /// (None for expressions like "0 0 31 2 *").
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let end = t + Duration::days(366);
+        while t < end {
+            if self.matches(&t) {
+                return Some(t);
+            }
+            t += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+/// The moment a run at local time `next` happens in `tz`: the first of a time
+/// that occurs twice (clocks going back), the end of the gap for a time that
+/// is skipped (clocks going forward). The wait is taken between moments, not
+/// local times, so a change of clocks does not shift the run by an hour.
+fn run_moment<Tz: TimeZone>(tz: &Tz, next: NaiveDateTime) -> Option<DateTime<Tz>> {
+    (0..=180).find_map(|minutes| {
+        tz.from_local_datetime(&(next + Duration::minutes(minutes)))
+            .earliest()
+    })
+}
+
+/// Subfolders of the source folder for `quarantine = true`.
+const PROCESSED_DIR: &str = "processed";
+const FAILED_DIR: &str = "failed";
+
+/// Most recently modified Excel file directly in `folder`.
+pub fn newest_input(folder: &Path) -> Option<(PathBuf, SystemTime)> {
+    excel_inputs(folder)
+        .into_iter()
+        .max_by_key(|(_, modified)| *modified)
+}
+
+/// Excel files directly in `folder`, oldest first.
+pub fn excel_inputs(folder: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut inputs: Vec<(PathBuf, SystemTime)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.extension().is_some_and(|ext| {
+                    EXCEL_EXTENSIONS
+                        .iter()
+                        .any(|x| ext.to_string_lossy().eq_ignore_ascii_case(x))
+                })
+        })
+        .filter_map(|p| {
+            let modified = fs::metadata(&p).and_then(|m| m.modified()).ok()?;
+            Some((p, modified))
+        })
+        .collect();
+    inputs.sort_by_key(|(_, modified)| *modified);
+    inputs
+}
+
+/// Move a converted input to processed/ in its folder, or a failed one to
+/// failed/ with the error in `<name>.error.txt`. A file of the same name
+/// already there is kept: the new one gets the time in its name.
+pub fn quarantine(result: &FileResult) -> io::Result<PathBuf> {
+    let input = &result.input;
+    let folder = input.parent().unwrap_or(Path::new("."));
+    let dir = folder.join(if result.error.is_none() {
+        PROCESSED_DIR
+    } else {
+        FAILED_DIR
+    });
+    fs::create_dir_all(&dir)?;
+    let name = input.file_name().unwrap_or_default();
+    let mut target = dir.join(name);
+    if target.exists() {
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        let ext = input.extension().unwrap_or_default().to_string_lossy();
+        target = dir.join(format!(
+            "{}_{}.{}",
+            stem,
+            Local::now().format("%Y%m%d-%H%M%S"),
+            ext
+        ));
+    }
+    fs::rename(input, &target)?;
+    if let Some(error) = &result.error {
+        let mut report = target.clone().into_os_string();
+        report.push(".error.txt");
+        fs::write(
+            report,
+            format!(
+                "{}\r\nInput: {}\r\nError: {}\r\n",
+                Local::now().format("%d/%m/%Y %H:%M:%S"),
+                input.display(),
+                error
+            ),
+        )?;
+    }
+    Ok(target)
+}
+
+/// Append a line to <app dir>/schedule.log. Logging never stops the schedule.
+pub fn log(line: &str) {
+    let Some(path) = paths::schedule_log_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(
+            file,
+            "{}  {}",
+            Local::now().format("%d/%m/%Y %H:%M:%S"),
+            line
+        );
+    }
+}
+
+/// What a scheduled run did, for the log and the notification.
+pub enum RunOutcome {
+    Converted(FileResult),
+    NoInput,
+    Unchanged(PathBuf),
+}
+
+impl RunOutcome {
+    pub fn text(&self) -> String {
+        match self {
+            RunOutcome::Converted(r) => {
+                let retried = match r.retries {
+                    0 => String::new(),
+                    1 => " after 1 retry".to_string(),
+                    n => format!(" after {} retries", n),
+                };
+                match &r.error {
+                    None => format!(
+                        "converted {} -> {} ({} rows){}",
+                        r.input.display(),
+                        r.output.display(),
+                        r.rows,
+                        retried
+                    ),
+                    Some(e) => format!("FAILED {}{}: {}", r.input.display(), retried, e),
+                }
+            }
+            RunOutcome::NoInput => "no Excel file in the source folder".to_string(),
+            RunOutcome::Unchanged(p) => format!("{} unchanged since last run", p.display()),
+        }
+    }
+}
+
+/// Run the schedule until the process ends. `notify` is called after every
+/// scheduled time with what happened. Returns only when the cron expression
+/// never matches again.
+pub fn run_forever(
+    schedule: &Schedule,
+    cron: &Cron,
+    profile: &Profile,
+    mut notify: impl FnMut(&RunOutcome),
+) {
+    let out_dir = schedule
+        .output_folder
+        .clone()
+        .unwrap_or_else(|| schedule.source_folder.clone());
+    let mut last: Option<(PathBuf, SystemTime)> = None;
+    log(&format!(
+        "schedule \"{}\" started for {}",
+        schedule.cron,
+        schedule.source_folder.display()
+    ));
+    // The account the service runs under
+    let run = RunInfo {
+        remark: "scheduled export".to_string(),
+        ..RunInfo::from_os()
+    };
+    let limits = schedule.limits();
+    loop {
+        let now = Local::now();
+        let Some(next) = cron
+            .next_after(now.naive_local())
+            .and_then(|next| run_moment(&Local, next))
+        else {
+            log("schedule stopped: the cron expression never matches");
+            return;
+        };
+        let wait = next - now;
+        thread::sleep(wait.to_std().unwrap_or_default());
+
+        if schedule.quarantine {
+            let inputs = excel_inputs(&schedule.source_folder);
+            if inputs.is_empty() {
+                log(&RunOutcome::NoInput.text());
+                notify(&RunOutcome::NoInput);
+            }
+            let inputs: Vec<BatchInput> = inputs
+                .into_iter()
+                .map(|(path, _)| BatchInput {
+                    relative: path.file_name().map(PathBuf::from).unwrap_or_default(),
+                    path,
+                })
+                .collect();
+            batch::run(&inputs, &out_dir, profile, &run, &limits, |result| {
+                match quarantine(result) {
+                    Ok(target) => log(&format!("moved to {}", target.display())),
+                    Err(e) => log(&format!("could not move {}: {}", result.input.display(), e)),
+                }
+                let outcome = RunOutcome::Converted(result.clone());
+                log(&outcome.text());
+                notify(&outcome);
+            });
+            continue;
+        }
+
+        let outcome = match newest_input(&schedule.source_folder) {
+            None => RunOutcome::NoInput,
+            Some(found) if last.as_ref() == Some(&found) => RunOutcome::Unchanged(found.0),
+            Some(found) => {
+                let input = BatchInput {
+                    relative: found.0.file_name().map(PathBuf::from).unwrap_or_default(),
+                    path: found.0.clone(),
+                };
+                let result = batch::convert_one(
+                    &input,
+                    &batch::output_path(&out_dir, &input),
+                    profile,
+                    &run,
+                    &limits,
+                );
+                if result.error.is_none() {
+                    last = Some(found);
+                }
+                RunOutcome::Converted(result)
+            }
+        };
+        log(&outcome.text());
+        notify(&outcome);
+    }
+}
+
+/// Time of the next run, for the status line.
+pub fn next_run_text(cron: &Cron) -> Option<String> {
+    cron.next_after(Local::now().naive_local())
+        .map(|t| t.format("%d/%m/%Y %H:%M").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDate};
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(cron: &str, after: &str) -> String {
+        Cron::parse(cron)
+            .unwrap()
+            .next_after(at(after))
+            .map(|t| t.format("%Y-%m-%d %H:%M %a").to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn parse_reads_lists_ranges_and_steps() {
+        let cron = Cron::parse("0,30 8-10 */10 1-12/3 *").unwrap();
+        let on = |v: &[bool]| -> Vec<usize> { (0..v.len()).filter(|&i| v[i]).collect() };
+        assert_eq!(on(&cron.minutes), [0, 30]);
+        assert_eq!(on(&cron.hours), [8, 9, 10]);
+        assert_eq!(on(&cron.days), [1, 11, 21, 31]);
+        assert_eq!(on(&cron.months), [1, 4, 7, 10]);
+        assert_eq!(
+            on(&Cron::parse("5/20 * * * *").unwrap().minutes),
+            [5, 25, 45]
+        );
+        // 7 is Sunday too
+        assert_eq!(on(&Cron::parse("0 0 * * 7").unwrap().weekdays), [0, 7]);
+    }
+
+    #[test]
+    fn parse_refuses_bad_fields() {
+        for bad in [
+            "0 7 * *",
+            "0 7 * * * *",
+            "60 7 * * *",
+            "0 24 * * *",
+            "0 7 0 * *",
+            "0 7 * 13 *",
+            "0 7 * * 8",
+            "0 7 5-1 * *",
+            "*/0 7 * * *",
+            "a 7 * * *",
+        ] {
+            assert!(Cron::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn next_after_weekdays_and_rollover() {
+        // Friday evening -> Monday morning
+        assert_eq!(
+            next("0 7 * * 1-5", "2025-03-07 18:00"),
+            "2025-03-10 07:00 Mon"
+        );
+        // Strictly after, from the next whole minute
+        assert_eq!(
+            next("0 7 * * *", "2025-03-10 07:00"),
+            "2025-03-11 07:00 Tue"
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2025-03-10 07:14"),
+            "2025-03-10 07:15 Mon"
+        );
+        // Month and year rollover, short months skipped
+        assert_eq!(
+            next("0 0 31 * *", "2025-04-01 00:00"),
+            "2025-05-31 00:00 Sat"
+        );
+        assert_eq!(
+            next("30 23 * * *", "2025-12-31 23:45"),
+            "2026-01-01 23:30 Thu"
+        );
+        assert_eq!(next("0 6 29 2 *", "2025-03-01 00:00"), "");
+        // Day and weekday both set: either matches
+        assert_eq!(
+            next("0 9 13 * 5", "2025-06-01 00:00"),
+            "2025-06-06 09:00 Fri"
+        );
+    }
+
+    /// Brussels in 2025: CET, CEST from 30 March 01:00 UTC to 26 October
+    /// 01:00 UTC.
+    #[derive(Clone, Copy)]
+    struct Brussels;
+
+    impl TimeZone for Brussels {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Brussels
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            local: &NaiveDateTime,
+        ) -> MappedLocalTime<FixedOffset> {
+            let fits: Vec<FixedOffset> = [2, 1]
+                .map(|hours| FixedOffset::east_opt(hours * 3600).unwrap())
+                .into_iter()
+                .filter(|&offset| self.offset_from_utc_datetime(&(*local - offset)) == offset)
+                .collect();
+            match fits[..] {
+                [offset] => MappedLocalTime::Single(offset),
+                [first, second] => MappedLocalTime::Ambiguous(first, second),
+                _ => MappedLocalTime::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let summer = at("2025-03-30 01:00") <= *utc && *utc < at("2025-10-26 01:00");
+            FixedOffset::east_opt(if summer { 7200 } else { 3600 }).unwrap()
+        }
+    }
+
+    fn wait(cron: &str, now: &str) -> i64 {
+        let now = Brussels.from_local_datetime(&at(now)).unwrap();
+        let next = Cron::parse(cron)
+            .unwrap()
+            .next_after(now.naive_local())
+            .unwrap();
+        (run_moment(&Brussels, next).unwrap() - now).num_minutes()
+    }
+
+    #[test]
+    fn waits_follow_the_change_of_clocks() {
+        // Clocks go forward at 02:00: 01:00 -> 07:00 is five hours
+        assert_eq!(wait("0 7 * * *", "2025-03-30 01:00"), 5 * 60);
+        // 02:30 does not exist that night: run at 03:00, one hour later
+        assert_eq!(wait("30 2 * * *", "2025-03-30 01:00"), 60);
+        // Clocks go back at 03:00: 01:00 -> 07:00 is seven hours
+        assert_eq!(wait("0 7 * * *", "2025-10-26 01:00"), 7 * 60);
+        // 02:30 happens twice: the first one
+        assert_eq!(wait("30 2 * * *", "2025-10-26 01:00"), 90);
+        // An ordinary night
+        assert_eq!(wait("0 7 * * *", "2025-06-01 01:00"), 6 * 60);
+    }
+}
@@ -0,0 +1,99 @@
+// src/script.rs
+// Profile scripts
+// ---------------
+//
+// Municipality-specific quirks that will never be a generic option (e.g.
+// translating internal zone codes) go in a small Rhai script referenced by
+// the profile (`script = "alken.rhai"`, relative to the profiles folder):
+//
+//   fn before_write(record) {
+//       if record["Zone libre 1"] == "Z1" { record["Zone libre 1"] = "Centrum"; }
+//       if record["Naam"] == "TEST" { return false; }   // drop the row
+//       record
+//   }
+//
+// `record` maps the output column names to their values, after all built-in
+// cleaning. The function returns the record (changed or not) or `false` to
+// leave the row out. Rows dropped by the script, or for which it fails, are
+// listed in the report. Needs the `scripting` feature (on by default).
+
+use crate::convert::OUTPUT_HEADER;
+use crate::error::{ConvertError, Result};
+use crate::paths;
+use crate::profile::Profile;
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use std::path::PathBuf;
+
+/// Name of the function every script must define.
+const HOOK: &str = "before_write";
+
+/// Upper bound on the work per row, so a script stuck in a loop fails
+/// instead of hanging the conversion.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+pub struct RowScript {
+    engine: Engine,
+    ast: AST,
+}
+
+/// Script file of a profile: absolute, or relative to the profiles folder.
+fn script_path(script: &str) -> PathBuf {
+    let path = PathBuf::from(script);
+    match paths::profiles_dir() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
+
+impl RowScript {
+    /// Compile the profile's script; None when the profile has none.
+    pub fn load(profile: &Profile) -> Result<Option<RowScript>> {
+        let Some(script) = &profile.script else {
+            return Ok(None);
+        };
+        let path = script_path(script);
+        let error = |message: String| ConvertError::Script {
+            path: path.clone(),
+            message,
+        };
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile_file(path.clone())
+            .map_err(|e| error(e.to_string()))?;
+        if !ast.iter_functions().any(|f| f.name == HOOK) {
+            return Err(error(format!("no function {}(record)", HOOK)));
+        }
+        Ok(Some(RowScript { engine, ast }))
+    }
+
+    /// Run the hook on one output record. Ok(false): the script dropped the row.
+    pub fn before_write(&self, record: &mut [String]) -> std::result::Result<bool, String> {
+        let map: Map = OUTPUT_HEADER
+            .iter()
+            .zip(record.iter())
+            .map(|(name, value)| ((*name).into(), Dynamic::from(value.clone())))
+            .collect();
+        let result: Dynamic = self
+            .engine
+            .call_fn(&mut Scope::new(), &self.ast, HOOK, (map,))
+            .map_err(|e| e.to_string())?;
+
+        if result.as_bool() == Ok(false) {
+            return Ok(false);
+        }
+        let Some(map) = result.try_cast::<Map>() else {
+            return Err(format!("{} must return the record or false", HOOK));
+        };
+        for (name, value) in OUTPUT_HEADER.iter().zip(record.iter_mut()) {
+            *value = match map.get(*name) {
+                Some(v) if v.is_string() => v.clone().into_string().unwrap_or_default(),
+                Some(v) if v.is_unit() => String::new(),
+                Some(v) => v.to_string(),
+                None => String::new(),
+            };
+        }
+        Ok(true)
+    }
+}
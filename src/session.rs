@@ -0,0 +1,88 @@
+// src/session.rs
+// Unfinished GUI session
+// ----------------------
+//
+// While a file is being prepared in the GUI (file chosen, profiles ticked,
+// header mapping confirmed, session values typed, duplicate review under
+// way) the state is kept in <app dir>/session.toml. The GUI removes the file
+// when the work is done or the window is closed normally, so a file found at
+// start-up means the app or the machine crashed: the GUI offers to restore
+// it instead of redoing a long duplicate review.
+
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Duplicate review in progress.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Review {
+    /// CSV the review writes to.
+    pub out: PathBuf,
+    /// Option chosen per duplicated Tel/Ref. (0 = keep first, see main.rs).
+    pub choices: BTreeMap<String, i32>,
+}
+
+/// "Fixed values for this session" as typed, empty when not overridden.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Overrides {
+    pub postcode: String,
+    pub gemeente: String,
+    pub taal: String,
+    pub land: String,
+    pub type_contact: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub input_file: String,
+    /// Names of the ticked profiles.
+    pub profiles: Vec<String>,
+    /// Confirmed columns of a list without header row (see headerless.rs).
+    pub input_columns: Vec<String>,
+    /// Column chosen per header name found more than once (see the
+    /// profile's `[duplicate_columns]`).
+    pub column_choices: BTreeMap<String, usize>,
+    pub merge_file: String,
+    pub merge_profile: String,
+    pub merge_second_wins: bool,
+    pub overrides: Overrides,
+    pub operator: String,
+    pub remark: String,
+    pub review: Option<Review>,
+}
+
+impl Session {
+    /// The session left by a crashed run, if any. An unreadable file is
+    /// treated as no session.
+    pub fn load() -> Option<Session> {
+        let text = fs::read_to_string(paths::session_path()?).ok()?;
+        toml::from_str(&text).ok()
+    }
+
+    /// Written to a temporary file first, so a crash while saving leaves the
+    /// previous state instead of half a file.
+    pub fn save(&self) -> io::Result<()> {
+        let path = paths::session_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config folder"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(io::Error::other)?;
+        let temp = path.with_extension("toml.tmp");
+        fs::write(&temp, text)?;
+        fs::rename(temp, path)
+    }
+
+    /// Forget the session (work finished or abandoned on purpose).
+    pub fn clear() {
+        if let Some(path) = paths::session_path() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// src/settings.rs
+// User settings
+// -------------
+//
+// GUI choices that survive a restart, in <app dir>/settings.toml, e.g.
+//
+//   quick_export = true
+//   output_folder = 'S:\BE-Alert\uploads'   # optional; default: next to the XLSX
+//
+//   [[preview_columns]]        # preview layout, in display order (written by the GUI)
+//   name = "Tel/Ref."
+//   visible = true
+//   width = 110
+//
+//   [schedule]                 # optional, see schedule.rs
+//   cron = "0 7 * * 1-5"
+//   source_folder = 'S:\BIN\exports'
+//
+//   [[mappings]]               # confirmed columns of lists without header row
+//   source = "buurt_zuid.xlsx" # (written by the GUI, see headerless.rs)
+//   fingerprint = "3fa1c2d09b7e5a44"
+//   columns = ["Voornaam", "Naam", "Straat", "Huisnummer", "Mobiel nummer", ""]
+//
+// A missing or unreadable file gives the defaults.
+
+use crate::paths;
+use crate::schedule::Schedule;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Suffix of the CSV written by quick export: <input stem>_BEAlert.csv
+pub const QUICK_EXPORT_SUFFIX: &str = "_BEAlert";
+
+/// Column mapping the user confirmed for a list without header row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RememberedMapping {
+    /// File name of the list it was confirmed for, lower case.
+    pub source: String,
+    /// headerless::fingerprint of `columns`.
+    pub fingerprint: String,
+    /// Input column name per column, as `input_columns`.
+    pub columns: Vec<String>,
+}
+
+/// One output column in the preview grid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnLayout {
+    /// Output column name (see convert::OUTPUT_HEADER).
+    pub name: String,
+    pub visible: bool,
+    /// Width in logical pixels.
+    pub width: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Save without a dialog, see `quick_export_dir`.
+    pub quick_export: bool,
+    pub output_folder: Option<PathBuf>,
+    /// Scheduled export of the newest file in a folder.
+    pub schedule: Option<Schedule>,
+    /// Preview grid columns as last arranged; empty until changed.
+    pub preview_columns: Vec<ColumnLayout>,
+    /// Mappings confirmed per source file, newest last.
+    pub mappings: Vec<RememberedMapping>,
+}
+
+/// Key of a source file in `mappings`: its file name, lower case.
+fn source_key(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+impl Settings {
+    pub fn load() -> Settings {
+        paths::settings_path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = paths::settings_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config folder"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    /// Mapping confirmed before for a list with this layout (any file).
+    pub fn remembered_mapping(&self, fingerprint: &str) -> Option<&RememberedMapping> {
+        self.mappings
+            .iter()
+            .rev()
+            .find(|m| m.fingerprint == fingerprint)
+    }
+
+    /// Whether `source` had another layout when its mapping was confirmed.
+    pub fn layout_changed(&self, source: &Path, fingerprint: &str) -> bool {
+        let key = source_key(source);
+        self.mappings
+            .iter()
+            .any(|m| m.source == key && m.fingerprint != fingerprint)
+    }
+
+    /// Keep a confirmed mapping, replacing the one of the same source file.
+    pub fn remember_mapping(&mut self, source: &Path, columns: &[String], fingerprint: String) {
+        let source = source_key(source);
+        self.mappings.retain(|m| m.source != source);
+        self.mappings.push(RememberedMapping {
+            source,
+            fingerprint,
+            columns: columns.to_vec(),
+        });
+    }
+
+    /// Folder quick export writes to: the configured output folder, or the
+    /// folder of the input file.
+    pub fn quick_export_dir(&self, input: &Path) -> PathBuf {
+        match &self.output_folder {
+            Some(dir) => dir.clone(),
+            None => input.parent().map(Path::to_path_buf).unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// src/template.rs
+// Output template from an accepted BE-Alert CSV
+// ---------------------------------------------
+//
+// Some BE-Alert tenants were set up with their own import template: French
+// or English headers, another column order, a comma as separator. Instead of
+// describing it by hand, an example CSV that BE-Alert accepted is read and
+// the template inferred from it:
+//
+// - the separator, sniffed as for CSV input (see dialect.rs)
+// - per column, which of the 33 output fields it holds, recognized by its
+//   header in Dutch, French or English (accents and case ignored); an
+//   unknown header is refused rather than guessed, since a column written
+//   with the wrong field would be uploaded without anyone noticing
+//
+// The template is stored in a profile (`[template]`, see profile.rs) and
+// used whenever that profile writes a CSV: the example's headers, in its
+// order, with its separator.
+
+use crate::convert::OUTPUT_HEADER;
+use crate::dialect;
+use crate::error::{ConvertError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Column layout of the output CSV.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputTemplate {
+    /// Field separator.
+    pub delimiter: char,
+    pub columns: Vec<TemplateColumn>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateColumn {
+    /// Header written for the column, as in the example.
+    pub header: String,
+    /// Output field (an OUTPUT_HEADER name) written in the column; empty
+    /// for a column always left empty.
+    #[serde(default)]
+    pub field: String,
+}
+
+/// Template read from an example CSV, with what was recognized.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inferred {
+    pub template: OutputTemplate,
+    /// Language most headers are in: "NL", "FR" or "EN".
+    pub language: &'static str,
+}
+
+/// Header names per output field besides OUTPUT_HEADER (Dutch), as (field
+/// index, language, name).
+const ALIASES: [(usize, &str, &str); 63] = [
+    (0, "FR", "Tél/Réf."),
+    (0, "EN", "Phone/Ref."),
+    (1, "NL", "Aanspreking"),
+    (1, "EN", "Title"),
+    (2, "FR", "Nom"),
+    (2, "EN", "Last name"),
+    (3, "FR", "Prénom"),
+    (3, "EN", "First name"),
+    (4, "FR", "Adresse incl numéro"),
+    (4, "FR", "Adresse"),
+    (4, "EN", "Address"),
+    (5, "FR", "Adresse complémentaire"),
+    (5, "EN", "Additional address"),
+    (6, "FR", "Code postal"),
+    (6, "EN", "Zip code"),
+    (6, "EN", "Postal code"),
+    (7, "FR", "Commune"),
+    (7, "FR", "Localité"),
+    (7, "EN", "Municipality"),
+    (7, "EN", "City"),
+    (8, "FR", "Date de naissance"),
+    (8, "EN", "Date of birth"),
+    (9, "FR", "Courriel"),
+    (13, "FR", "Étage"),
+    (13, "EN", "Floor"),
+    (14, "FR", "Nombre d'habitants"),
+    (14, "EN", "Number of residents"),
+    (15, "FR", "Téléphone 2"),
+    (15, "EN", "Phone 2"),
+    (16, "FR", "Téléphone 3"),
+    (16, "EN", "Phone 3"),
+    (17, "FR", "Téléphone 4"),
+    (17, "EN", "Phone 4"),
+    (18, "FR", "Téléphone 5"),
+    (18, "EN", "Phone 5"),
+    (19, "FR", "Téléphone 6"),
+    (19, "NL", "Telefoon 6"),
+    (19, "EN", "Phone 6"),
+    (20, "FR", "Téléphone 7"),
+    (20, "EN", "Phone 7"),
+    (21, "EN", "SMS number"),
+    (22, "EN", "SMS number 2"),
+    (23, "EN", "SMS number 3"),
+    (25, "NL", "Vrije zone 1"),
+    (25, "EN", "Free field 1"),
+    (26, "NL", "Vrije zone 2"),
+    (26, "EN", "Free field 2"),
+    (27, "NL", "Vrije zone 3"),
+    (27, "EN", "Free field 3"),
+    (28, "FR", "Langue"),
+    (28, "EN", "Language"),
+    (29, "FR", "Pays"),
+    (29, "EN", "Country"),
+    (30, "FR", "Liste rouge"),
+    (30, "NL", "Zwarte lijst"),
+    (30, "EN", "Red list"),
+    (31, "FR", "Type de contact"),
+    (31, "FR", "Type contact"),
+    (31, "EN", "Contact type"),
+    (32, "FR", "Coordonnées GPS"),
+    (32, "NL", "GPS coordinaten"),
+    (32, "EN", "GPS"),
+    (32, "EN", "GPS coordinates"),
+];
+
+/// Header compared without case, accents, spaces and punctuation.
+fn key(header: &str) -> String {
+    header
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' => 'i',
+            'ô' | 'ö' => 'o',
+            'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            c => c,
+        })
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// Output field and language of a header.
+fn recognize(header: &str) -> Option<(usize, &'static str)> {
+    let header = key(header);
+    let dutch = OUTPUT_HEADER.iter().enumerate().map(|(i, h)| (i, "NL", *h));
+    dutch
+        .chain(ALIASES)
+        .find(|&(_, _, name)| key(name) == header)
+        .map(|(i, language, _)| (i, language))
+}
+
+/// Whether a header names one of the 33 output fields.
+pub(crate) fn is_header_name(header: &str) -> bool {
+    recognize(header).is_some()
+}
+
+/// Infer the template of an example CSV (see the top of this file).
+pub fn infer_file(path: &Path) -> Result<Inferred> {
+    let bytes = fs::read(path).map_err(|e| ConvertError::io_at(path, e))?;
+    infer(&bytes).map_err(|reason| ConvertError::UnsupportedFormat {
+        path: path.to_path_buf(),
+        reason,
+    })
+}
+
+pub fn infer(bytes: &[u8]) -> std::result::Result<Inferred, String> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| "not UTF-8; save the example as \"CSV UTF-8\"".to_string())?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let delimiter = dialect::sniff(text).delimiter as char;
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let header = match reader.records().next() {
+        Some(Ok(record)) => record,
+        Some(Err(e)) => return Err(e.to_string()),
+        None => return Err("the file is empty".to_string()),
+    };
+
+    let recognized: Vec<Option<(usize, &str)>> = header.iter().map(recognize).collect();
+    let unknown: Vec<&str> = header
+        .iter()
+        .zip(&recognized)
+        .filter(|(_, found)| found.is_none())
+        .map(|(name, _)| name.trim())
+        .collect();
+    if unknown.len() == header.len() {
+        return Err("the first line is not a BE-Alert header (no column recognized)".to_string());
+    }
+    if !unknown.is_empty() {
+        return Err(format!(
+            "unknown column headers: {}; rename them to a BE-Alert column name",
+            unknown.join(", ")
+        ));
+    }
+    let columns = header
+        .iter()
+        .zip(&recognized)
+        .map(|(name, found)| TemplateColumn {
+            header: name.trim().to_string(),
+            field: found
+                .map(|(field, _)| OUTPUT_HEADER[field].to_string())
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let count = |language: &str| {
+        recognized
+            .iter()
+            .flatten()
+            .filter(|(_, l)| *l == language)
+            .count()
+    };
+    let language = ["NL", "FR", "EN"]
+        .into_iter()
+        .max_by_key(|&language| count(language))
+        .unwrap_or("NL");
+    Ok(Inferred {
+        template: OutputTemplate { delimiter, columns },
+        language,
+    })
+}
+
+impl OutputTemplate {
+    /// Index into OUTPUT_HEADER of each column (None: left empty).
+    pub(crate) fn fields(&self) -> Vec<Option<usize>> {
+        self.columns
+            .iter()
+            .map(|c| OUTPUT_HEADER.iter().position(|h| *h == c.field))
+            .collect()
+    }
+
+    /// A problem that makes the template unusable, for the profile check.
+    pub fn problem(&self) -> Option<String> {
+        if !self.delimiter.is_ascii() || matches!(self.delimiter, '"' | '\r' | '\n') {
+            return Some(format!("separator {:?}", self.delimiter));
+        }
+        if self.columns.is_empty() {
+            return Some("no columns".to_string());
+        }
+        self.columns
+            .iter()
+            .find(|c| !c.field.is_empty() && !OUTPUT_HEADER.contains(&c.field.as_str()))
+            .map(|c| format!("unknown field \"{}\"", c.field))
+    }
+
+    pub fn header(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.header.as_str()).collect()
+    }
+}
+
+/// A record (or header line) in OUTPUT_HEADER order, in the order of a
+/// template's `fields`.
+pub(crate) fn reorder<S: AsRef<str>>(fields: &[Option<usize>], record: &[S]) -> Vec<String> {
+    fields
+        .iter()
+        .map(|field| {
+            field
+                .and_then(|i| record.get(i))
+                .map(|value| value.as_ref().to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infer_reads_french_headers_in_their_order() {
+        let inferred = infer("Nom,Prénom,Tél/Réf.,Code postal\r\n".as_bytes()).unwrap();
+        assert_eq!(inferred.template.delimiter, ',');
+        assert_eq!(inferred.language, "FR");
+        assert_eq!(
+            inferred.template.fields(),
+            vec![Some(2), Some(3), Some(0), Some(6)]
+        );
+        assert_eq!(
+            inferred.template.header(),
+            vec!["Nom", "Prénom", "Tél/Réf.", "Code postal"]
+        );
+    }
+
+    #[test]
+    fn infer_ignores_case_accents_and_bom() {
+        let inferred = infer("\u{feff}tel/ref.;NAAM;gps coordinaten\n".as_bytes()).unwrap();
+        assert_eq!(inferred.template.fields(), vec![Some(0), Some(2), Some(32)]);
+        assert_eq!(inferred.language, "NL");
+    }
+
+    #[test]
+    fn infer_refuses_unknown_headers() {
+        let error = infer("Tel/Ref.;Naam;Wijk\n".as_bytes()).unwrap_err();
+        assert!(error.contains("Wijk"), "{}", error);
+        // Descriptions are not header names
+        let error =
+            infer("Tel/Ref.;Mobile number (0032...), contact key\n".as_bytes()).unwrap_err();
+        assert!(error.contains("Mobile number"), "{}", error);
+        assert!(infer("0032475123456;Peeters;Jan\n".as_bytes()).is_err());
+        assert!(infer(b"").is_err());
+        assert!(infer(b"Naam;\xff\n").is_err());
+    }
+
+    #[test]
+    fn reorder_puts_fields_in_template_order() {
+        let record = ["0032475123456", "", "Peeters", "Jan"];
+        let fields = [Some(3), None, Some(0), Some(40)];
+        assert_eq!(
+            reorder(&fields, &record),
+            vec!["Jan", "", "0032475123456", ""]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+// src/verify.rs
+// Checking an existing BE-Alert CSV
+// ---------------------------------
+//
+// "Verify CSV" reads a CSV that is about to be uploaded (made by this tool,
+// by an older version or by a colleague) without changing it, and lists what
+// BE-Alert would refuse or what should not be in it:
+//
+// - not UTF-8, header line missing, or neither the 33 BE-Alert columns nor
+//   the layout of a tenant's own template (see template.rs; its columns are
+//   checked as the fields they hold)
+// - lines with another number of fields
+// - Tel/Ref. and FAX not in 0032 format, contacts without Tel/Ref., SMS and Email,
+//   the same Tel/Ref. on several lines
+// - Postcode, Taal, Land and GPS values BE-Alert does not accept (see
+//   pipeline.rs), "Aantal inwoners" that is not a number, e-mail addresses
+//   without '@' or domain
+// - rijksregisternummers in any field (GDPR)
+
+use crate::convert::{OUTPUT_DESCRIPTIONS, OUTPUT_HEADER};
+use crate::dialect;
+use crate::error::{ConvertError, Result};
+use crate::pipeline::{field_problem, is_residents_count};
+use crate::rijksregister;
+use crate::template;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Output fields the checks look at (index into OUTPUT_HEADER)
+const TEL_REF: usize = 0;
+const EMAIL: usize = 9;
+const FAX: [usize; 3] = [10, 11, 12];
+const AANTAL_INWONERS: usize = 14;
+const SMS: usize = 21;
+
+/// One finding; `line` is the line number in the file (header = line 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub line: usize,
+    pub column: String,
+    pub value: String,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn text(&self) -> String {
+        if self.column.is_empty() {
+            format!("line {}: {}", self.line, self.message)
+        } else if self.value.is_empty() {
+            format!("line {}, {}: {}", self.line, self.column, self.message)
+        } else {
+            format!(
+                "line {}, {}: \"{}\" {}",
+                self.line, self.column, self.value, self.message
+            )
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Verification {
+    /// Contact lines read (header and description lines not counted).
+    pub rows: usize,
+    pub problems: Vec<Problem>,
+}
+
+impl Verification {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// One line for the status bar or the command line.
+    pub fn summary(&self) -> String {
+        if self.is_ok() {
+            format!("{} contacts, no problems found", self.rows)
+        } else {
+            format!("{} contacts, {} problems", self.rows, self.problems.len())
+        }
+    }
+
+    fn problem(&mut self, line: usize, column: &str, value: &str, message: impl Into<String>) {
+        self.problems.push(Problem {
+            line,
+            column: column.to_string(),
+            value: value.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+/// Read `path` and check it; only an unreadable file is an error.
+pub fn verify_csv(path: &Path) -> Result<Verification> {
+    let bytes = fs::read(path).map_err(|e| ConvertError::io_at(path, e))?;
+    Ok(verify_bytes(&bytes))
+}
+
+pub fn verify_bytes(bytes: &[u8]) -> Verification {
+    let mut result = Verification::default();
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        result.problem(
+            1,
+            "",
+            "",
+            "the file is not UTF-8 (saved by Excel as \"CSV (;)\"?); accents will be garbled",
+        );
+        return result;
+    };
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let delimiter = dialect::sniff(text).delimiter as char;
+    // A tenant's own layout: each column's place among the 33 fields
+    let layout = template::infer(text.as_bytes())
+        .ok()
+        .filter(|inferred| inferred.template.header() != OUTPUT_HEADER)
+        .map(|inferred| inferred.template.fields());
+    let descriptions = layout
+        .as_ref()
+        .map(|fields| template::reorder(fields, &OUTPUT_DESCRIPTIONS));
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    // Line in the file where a record starts (quoted values may span lines).
+    // After a CRLF the reader reports the record at the '\n', so line breaks
+    // at the position still belong to the previous line.
+    let line_at = |byte: u64| {
+        let rest = &text[byte as usize..];
+        let start = text.len() - rest.trim_start_matches(['\r', '\n']).len();
+        text[..start].matches('\n').count() + 1
+    };
+    let mut first_line = true;
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |p| line_at(p.byte()));
+                result.problem(line, "", "", format!("cannot be read: {}", e));
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |p| line_at(p.byte()));
+        let fields: Vec<&str> = record.iter().collect();
+        if first_line {
+            first_line = false;
+            if layout.is_some() || check_header(&fields, line, delimiter, &mut result) {
+                continue;
+            }
+        }
+        let Some(layout) = &layout else {
+            if fields == OUTPUT_DESCRIPTIONS {
+                continue;
+            }
+            result.rows += 1;
+            check_row(&fields, line, &mut seen, &mut result);
+            continue;
+        };
+        if descriptions.as_ref().is_some_and(|d| fields == *d) {
+            continue;
+        }
+        result.rows += 1;
+        if fields.len() != layout.len() {
+            result.problem(
+                line,
+                "",
+                "",
+                format!(
+                    "has {} fields instead of {} (a separator inside a value without quotes?)",
+                    fields.len(),
+                    layout.len()
+                ),
+            );
+            continue;
+        }
+        // Back in the standard order; fields the template leaves out are empty
+        let mut standard = vec![""; OUTPUT_HEADER.len()];
+        for (field, value) in layout.iter().zip(&fields) {
+            if let Some(i) = field {
+                standard[*i] = value;
+            }
+        }
+        check_row(&standard, line, &mut seen, &mut result);
+    }
+    result
+}
+
+/// Problems of the first line; true when it is a header line (so not a contact).
+fn check_header(fields: &[&str], line: usize, delimiter: char, result: &mut Verification) -> bool {
+    if fields
+        .first()
+        .is_some_and(|f| f.starts_with("0032") || f.starts_with('+'))
+    {
+        result.problem(
+            line,
+            "",
+            "",
+            "no header line (BE-Alert expects the column names first)",
+        );
+        return false;
+    }
+    if fields.len() != OUTPUT_HEADER.len() {
+        result.problem(
+            line,
+            "",
+            "",
+            format!(
+                "header has {} columns instead of {}; is the separator {:?}?",
+                fields.len(),
+                OUTPUT_HEADER.len(),
+                delimiter
+            ),
+        );
+        return true;
+    }
+    for (found, expected) in fields.iter().zip(OUTPUT_HEADER) {
+        if found.trim() != expected {
+            result.problem(
+                line,
+                expected,
+                found,
+                format!("is not the BE-Alert column name \"{}\"", expected),
+            );
+        }
+    }
+    true
+}
+
+fn is_be_number(value: &str) -> bool {
+    value.strip_prefix("0032").is_some_and(|rest| {
+        (8..=9).contains(&rest.len()) && rest.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !value.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn check_row(
+    fields: &[&str],
+    line: usize,
+    seen: &mut HashMap<String, usize>,
+    result: &mut Verification,
+) {
+    if fields.len() != OUTPUT_HEADER.len() {
+        result.problem(
+            line,
+            "",
+            "",
+            format!(
+                "has {} fields instead of {} (a ';' inside a value without quotes?)",
+                fields.len(),
+                OUTPUT_HEADER.len()
+            ),
+        );
+        return;
+    }
+
+    let tel_ref = fields[TEL_REF];
+    if tel_ref.is_empty() {
+        // A contact preferring SMS has its number in SMS only
+        if fields[EMAIL].is_empty() && fields[SMS].is_empty() {
+            result.problem(
+                line,
+                OUTPUT_HEADER[TEL_REF],
+                "",
+                "empty, and no SMS or Email either",
+            );
+        }
+    } else if let Some(first) = seen.get(tel_ref) {
+        result.problem(
+            line,
+            OUTPUT_HEADER[TEL_REF],
+            tel_ref,
+            format!("is also on line {}", first),
+        );
+    } else {
+        seen.insert(tel_ref.to_string(), line);
+    }
+    for i in std::iter::once(TEL_REF).chain(FAX) {
+        if !fields[i].is_empty() && !is_be_number(fields[i]) {
+            result.problem(line, OUTPUT_HEADER[i], fields[i], "is not in 0032 format");
+        }
+    }
+    let email = fields[EMAIL];
+    if !email.is_empty() && !is_email(email) {
+        result.problem(
+            line,
+            OUTPUT_HEADER[EMAIL],
+            email,
+            "is not an e-mail address",
+        );
+    }
+    let inwoners = fields[AANTAL_INWONERS];
+    if !inwoners.is_empty() && !is_residents_count(inwoners) {
+        result.problem(
+            line,
+            OUTPUT_HEADER[AANTAL_INWONERS],
+            inwoners,
+            "is not a number of residents",
+        );
+    }
+    for (column, value) in OUTPUT_HEADER.iter().zip(fields) {
+        if let Some(expected) = field_problem(column, value) {
+            result.problem(
+                line,
+                column,
+                value,
+                format!("is not valid, expected {}", expected),
+            );
+        }
+        if rijksregister::contains(value) {
+            result.problem(
+                line,
+                column,
+                "",
+                "looks like a rijksregisternummer (GDPR): do not upload this file",
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_ordered_csv_is_checked_by_field() {
+        let csv = "Nom,Tél/Réf.,Courriel\r\nPeeters,0032475123456,jan@example.be\r\nJanssens,0475,an@\r\n";
+        let result = verify_bytes(csv.as_bytes());
+        assert_eq!(result.rows, 2);
+        let columns: Vec<&str> = result.problems.iter().map(|p| p.column.as_str()).collect();
+        assert_eq!(columns, vec!["Tel/Ref.", "Email"]);
+        assert!(result.problems.iter().all(|p| p.line == 3));
+    }
+
+    #[test]
+    fn unknown_header_is_still_reported() {
+        let result = verify_bytes("Tel/Ref.;Wijk\r\n0032475123456;Noord\r\n".as_bytes());
+        assert!(result.problems[0].message.contains("columns instead of 33"));
+    }
+}
@@ -0,0 +1,79 @@
+// src/wasm.rs
+// Browser build
+// -------------
+//
+// JavaScript API of the WebAssembly build (feature `wasm`, without the
+// desktop-only `native` parts), for the intranet conversion page:
+//
+//   import init, { convert } from "./pkg/be_alert_convert.js";
+//   await init();
+//   const result = convert(file.name, new Uint8Array(await file.arrayBuffer()), profileToml);
+//   download(result.csv, "alken.csv"); show(result.report_html, result.warnings);
+//
+// `profile_toml` is a profile file's text (see profile.rs); empty for the
+// built-in Alken profile. Errors are thrown as strings.
+
+use crate::convert::{convert_xlsx_bytes, BufferExport};
+use crate::presets;
+use crate::profile::Profile;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct ConvertResult {
+    export: BufferExport,
+}
+
+#[wasm_bindgen]
+impl ConvertResult {
+    /// The CSV file, ready to download.
+    #[wasm_bindgen(getter)]
+    pub fn csv(&self) -> Vec<u8> {
+        self.export.csv.clone()
+    }
+
+    /// The HTML report (same as the desktop `_report.html`).
+    #[wasm_bindgen(getter)]
+    pub fn report_html(&self) -> String {
+        self.export.report_html.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.export.summary.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn unknown_phones(&self) -> usize {
+        self.export.summary.unknown_phones
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rejected(&self) -> usize {
+        self.export.summary.rejected.len()
+    }
+
+    /// Warnings, as in the desktop status line.
+    #[wasm_bindgen(getter)]
+    pub fn warnings(&self) -> Vec<String> {
+        self.export.summary.warnings.clone()
+    }
+}
+
+/// Convert an XLSX file's bytes with a profile.
+#[wasm_bindgen]
+pub fn convert(name: &str, xlsx: &[u8], profile_toml: &str) -> Result<ConvertResult, String> {
+    let mut profile = if profile_toml.trim().is_empty() {
+        Profile::default()
+    } else {
+        let table =
+            toml::from_str::<toml::Table>(profile_toml).map_err(|e| format!("profile: {}", e))?;
+        let mut profile =
+            toml::from_str::<Profile>(profile_toml).map_err(|e| format!("profile: {}", e))?;
+        presets::clear_unset(&mut profile, |key| table.contains_key(key));
+        profile
+    };
+    presets::apply(&mut profile).map_err(|e| format!("profile: {}", e))?;
+    convert_xlsx_bytes(name, xlsx, &profile)
+        .map(|export| ConvertResult { export })
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,59 @@
+// tests/batch.rs
+// Staged batch
+// ------------
+//
+// A staged batch (`run_staged`) with one input that cannot be converted must
+// leave the output directory as it was: no CSV or report of the inputs that
+// did convert, and no staging folder. The history is kept in a temporary
+// config folder, so the test never touches the user's own.
+
+use be_alert_convert::batch::{self, BatchInput, BatchLimits};
+use be_alert_convert::profile::Profile;
+use be_alert_convert::report::RunInfo;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> BatchInput {
+    BatchInput {
+        path: Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name),
+        relative: PathBuf::from(name),
+    }
+}
+
+#[test]
+fn failed_staged_batch_writes_nothing() {
+    let root = env::temp_dir().join(format!("be-alert-staged-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    env::set_var("XDG_CONFIG_HOME", root.join("config"));
+    let out_dir = root.join("out");
+    fs::create_dir_all(&out_dir).unwrap();
+    fs::write(out_dir.join("normal.csv"), "previous run").unwrap();
+
+    let inputs = [fixture("normal.xlsx"), fixture("broken.xlsx")];
+    let report = batch::run_staged(
+        &inputs,
+        &out_dir,
+        &Profile::default(),
+        &RunInfo::default(),
+        &BatchLimits::default(),
+        |_| {},
+    )
+    .unwrap();
+
+    assert_eq!(report.committed, Some(false));
+    assert_eq!((report.converted, report.failed), (1, 1));
+    let left: Vec<_> = fs::read_dir(&out_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(left, vec!["normal.csv"]);
+    assert_eq!(
+        fs::read_to_string(out_dir.join("normal.csv")).unwrap(),
+        "previous run"
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,113 @@
+// tests/golden.rs
+// Golden-file tests
+// -----------------
+//
+// Every tests/fixtures/<case>.xlsx is converted in memory with the built-in
+// Alken profile (LF line endings, the same on every platform) and the CSV
+// compared byte for byte with tests/golden/<case>.csv; a case that fails to
+// convert is compared with the message in tests/golden/<case>.err. So a
+// refactor cannot change the output without a golden file changing with it.
+//
+// After an intended change of the output:
+//
+//   UPDATE_GOLDEN=1 cargo test --test golden
+//
+// rewrites the golden files; review their diff before committing. A missing
+// golden file fails the test; UPDATE_GOLDEN=1 writes it. The fixtures
+// themselves are made by examples/golden_fixtures.rs.
+
+use be_alert_convert::convert::convert_xlsx_bytes;
+use be_alert_convert::profile::{LineEnding, Profile};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name)
+}
+
+/// The output of one fixture: the CSV, or the error message.
+fn convert(path: &Path) -> (&'static str, Vec<u8>) {
+    let xlsx = fs::read(path).unwrap();
+    let name = path.file_name().unwrap().to_string_lossy();
+    let profile = Profile {
+        line_ending: LineEnding::Lf,
+        ..Profile::default()
+    };
+    match convert_xlsx_bytes(&name, &xlsx, &profile) {
+        Ok(export) => ("csv", export.csv),
+        Err(e) => ("err", format!("{}\n", e).into_bytes()),
+    }
+}
+
+/// First line where `actual` and `expected` differ, for the failure message.
+fn first_difference(actual: &[u8], expected: &[u8]) -> String {
+    let actual = String::from_utf8_lossy(actual);
+    let expected = String::from_utf8_lossy(expected);
+    let mut expected_lines = expected.lines();
+    for (nr, line) in actual.lines().enumerate() {
+        match expected_lines.next() {
+            Some(want) if want == line => {}
+            want => {
+                return format!(
+                    "line {}:\n  got      {}\n  expected {}",
+                    nr + 1,
+                    line,
+                    want.unwrap_or("(end of file)")
+                )
+            }
+        }
+    }
+    match expected_lines.next() {
+        Some(want) => format!("output ends before the expected line\n  {}", want),
+        None => "same lines, different line endings or trailing bytes".to_string(),
+    }
+}
+
+#[test]
+fn fixtures_match_golden_files() {
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(dir("fixtures"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xlsx"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures in tests/fixtures");
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let case = fixture.file_stem().unwrap().to_string_lossy();
+        let (kind, actual) = convert(fixture);
+        let golden = dir("golden").join(format!("{}.{}", case, kind));
+        let other = golden.with_extension(if kind == "csv" { "err" } else { "csv" });
+        if other.exists() && !update {
+            failures.push(format!("{}: expected {}", case, other.display()));
+            continue;
+        }
+        match fs::read(&golden) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) if !update => failures.push(format!(
+                "{}: differs from {}\n{}",
+                case,
+                golden.display(),
+                first_difference(&actual, &expected)
+            )),
+            Err(_) if !update => {
+                failures.push(format!("{}: {} is missing", case, golden.display()))
+            }
+            _ => {
+                fs::create_dir_all(dir("golden")).unwrap();
+                fs::write(&golden, &actual).unwrap();
+                eprintln!("wrote {}", golden.display());
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\n\nIf the change is intended: UPDATE_GOLDEN=1 cargo test --test golden",
+        failures.join("\n\n")
+    );
+}
@@ -0,0 +1,110 @@
+// tests/normalizers.rs
+// Property tests of the cell normalizers
+// --------------------------------------
+//
+// normalize_be_phone, normalize_email, extract_house_number and split_address
+// decide every Tel/Ref., e-mail and address of the alert list, so they are checked on generated
+// input rather than a handful of examples: any cell text, and the notations
+// registers use for the same number or address. fuzz/ runs the same
+// properties under cargo-fuzz.
+
+use be_alert_convert::convert::{
+    extract_house_number, normalize_be_phone, normalize_email, split_address,
+};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn phone_normalization_is_idempotent(cell in "\\PC*") {
+        let number = normalize_be_phone(&cell);
+        prop_assert_eq!(normalize_be_phone(&number), number);
+    }
+
+    #[test]
+    fn phone_is_digits_only(cell in "\\PC*") {
+        let number = normalize_be_phone(&cell);
+        let digits = number.strip_prefix('+').unwrap_or(&number);
+        prop_assert!(digits.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn mobile_notations_agree(national in "4[0-9]{8}", separator in "[ ./-]") {
+        let expected = format!("0032{}", national);
+        let grouped = format!("0{}{}{}", &national[..3], separator, &national[3..]);
+        for cell in [
+            format!("0{}", national),
+            format!("+32{}", national),
+            format!("+32 (0){}", national),
+            format!("0032{}", national),
+            format!("00 32 {}", national),
+            national.clone(),
+            grouped,
+        ] {
+            prop_assert_eq!(normalize_be_phone(&cell), expected.clone(), "{}", cell);
+        }
+    }
+
+    #[test]
+    fn phone_annotations_are_left_out(national in "4[0-9]{8}", note in "[a-z ]{0,12}") {
+        let cell = format!("0{} ({})", national, note);
+        prop_assert_eq!(normalize_be_phone(&cell), format!("0032{}", national));
+    }
+
+    #[test]
+    fn email_normalization_is_idempotent(cell in "\\PC*") {
+        let email = normalize_email(&cell);
+        prop_assert_eq!(normalize_email(&email), email);
+    }
+
+    #[test]
+    fn pasted_email_notations_agree(
+        local in "[a-z][a-z0-9.]{0,10}",
+        domain in "[a-z]{2,10}\\.(be|com)",
+        name in "[A-Z][a-z]{1,8} [A-Z][a-z]{1,8}",
+    ) {
+        let expected = format!("{}@{}", local, domain);
+        for cell in [
+            expected.clone(),
+            expected.to_uppercase(),
+            format!("mailto:{}", expected),
+            format!("MAILTO:{}", expected),
+            format!("<{}>", expected),
+            format!("{} <{}>", name, expected),
+            format!("\"{}\" <mailto:{}>", name, expected),
+            format!("'{}'", expected),
+            format!("  \"{}\"  ", expected),
+        ] {
+            prop_assert_eq!(normalize_email(&cell), expected.clone(), "{}", cell);
+        }
+    }
+
+    #[test]
+    fn house_number_is_leading_digits(cell in "\\PC*") {
+        let number = extract_house_number(&cell);
+        prop_assert!(number.chars().all(|c| c.is_ascii_digit()));
+        prop_assert!(cell.trim().starts_with(&number));
+    }
+
+    #[test]
+    fn house_number_drops_suffix(number in 1u32..10000, suffix in "([A-Za-z]{0,3}| bus [0-9]{1,2})") {
+        let cell = format!("{}{}", number, suffix);
+        prop_assert_eq!(extract_house_number(&cell), number.to_string());
+    }
+
+    #[test]
+    fn address_split_is_prefix_and_suffix(address in "\\PC*") {
+        let (street, number) = split_address(&address);
+        prop_assert!(address.trim().starts_with(&street));
+        prop_assert!(address.trim().ends_with(&number));
+        prop_assert!(number.is_empty() || number.starts_with(|c: char| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn address_split_round_trip(
+        street in "[A-Z][a-z]{2,12}( [A-Z][a-z]{2,12}){0,2}",
+        number in "[1-9][0-9]{0,3}[A-Z]?( bus [0-9]{1,2})?",
+    ) {
+        let address = format!("{} {}", street, number);
+        prop_assert_eq!(split_address(&address), (street, number));
+    }
+}